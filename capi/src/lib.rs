@@ -0,0 +1,352 @@
+//! The C interface to the uno engine, for embedding games in programs not
+//! written in Rust. `cargo build` makes it as `libuno.so` (or `uno.dll`,
+//! `libuno.dylib`), and `include/uno.h` at the top of the repository
+//! declares everything here.
+//!
+//! Games are opaque `UnoGame` handles made by `uno_game_new` and released by
+//! `uno_game_free`. Every other function returns a status: `UNO_OK` (0) or a
+//! negative error code, which `uno_status_message` describes. Nothing
+//! panics across the boundary; a panic inside the engine is caught and
+//! reported as `UNO_ERR_PANIC`, after which the game should be freed.
+//!
+//! Text goes in and out in the words of the network protocol (see
+//! `ClientLine` and `ServerLine`): actions like `PLAY R5` or `DRAW`, views as the
+//! `key=value` list after `VIEW`, events like `PLAYED 0 R5` and results like
+//! `DREW G2`. Text is written to a caller's buffer with a terminating NUL,
+//! and its length without the NUL is stored in `length` when that isn't
+//! null. A buffer that is too small gets nothing written to it and
+//! `UNO_ERR_BUFFER` is returned with the length needed, so passing a null
+//! buffer of size 0 asks how big it has to be.
+
+use engine::{Action, ClientLine, Player, Rules, ServerLine, TurnResult, Uno};
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Bumped whenever a function's signature or meaning changes.
+pub const UNO_ABI_VERSION: u32 = 1;
+
+pub const UNO_OK: i32 = 0;
+/// A pointer that has to be given was null.
+pub const UNO_ERR_NULL: i32 = -1;
+/// Text that isn't valid UTF-8, or doesn't say anything the engine understands.
+pub const UNO_ERR_INVALID: i32 = -2;
+pub const UNO_ERR_NO_SUCH_SEAT: i32 = -3;
+/// The action is not one the current player may take now. The game is unchanged.
+pub const UNO_ERR_ILLEGAL: i32 = -4;
+pub const UNO_ERR_BUFFER: i32 = -5;
+pub const UNO_ERR_PANIC: i32 = -6;
+
+/// A game and what came of the last action applied to it.
+pub struct UnoGame {
+    uno: Uno,
+    last: Option<TurnResult>,
+}
+
+/// Runs `f`, turning a panic into `UNO_ERR_PANIC`.
+fn guard<F: FnOnce() -> Result<(), i32>>(f: F) -> i32 {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => UNO_OK,
+        Ok(Err(status)) => status,
+        Err(_) => UNO_ERR_PANIC,
+    }
+}
+
+unsafe fn text<'a>(s: *const c_char) -> Result<&'a str, i32> {
+    if s.is_null() {
+        return Err(UNO_ERR_NULL);
+    }
+    CStr::from_ptr(s).to_str().map_err(|_| UNO_ERR_INVALID)
+}
+
+unsafe fn game<'a>(game: *const UnoGame) -> Result<&'a UnoGame, i32> {
+    game.as_ref().ok_or(UNO_ERR_NULL)
+}
+
+unsafe fn write_out<T>(out: *mut T, value: T) -> Result<(), i32> {
+    if out.is_null() {
+        return Err(UNO_ERR_NULL);
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Copies `s` and a NUL into `buffer` if there is room for both.
+unsafe fn write_text(s: &str, buffer: *mut c_char, size: usize, length: *mut usize) -> Result<(), i32> {
+    if !length.is_null() {
+        length.write(s.len());
+    }
+    if s.len() >= size {
+        return Err(UNO_ERR_BUFFER);
+    }
+    if buffer.is_null() {
+        return Err(UNO_ERR_NULL);
+    }
+    ptr::copy_nonoverlapping(s.as_ptr() as *const c_char, buffer, s.len());
+    buffer.add(s.len()).write(0);
+    Ok(())
+}
+
+/// A server line without its first word, e.g. the `key=value` list of a `VIEW`.
+fn line_text(line: ServerLine) -> String {
+    let line = line.to_string();
+    line.split_once(' ').map(|(_, rest)| rest.to_string()).unwrap_or_default()
+}
+
+/// A preset name, or `KEY=VALUE` options separated by commas changing the official rules.
+fn parse_rules(text: &str) -> Option<Rules> {
+    if !text.contains('=') {
+        return Rules::preset(text).ok();
+    }
+    let mut rules = Rules::official();
+    for option in text.split(',') {
+        let (key, value) = option.split_once('=')?;
+        rules.set(key.trim(), value).ok()?;
+    }
+    Some(rules)
+}
+
+#[no_mangle]
+pub extern "C" fn uno_abi_version() -> u32 {
+    UNO_ABI_VERSION
+}
+
+/// A static description of a status, never null.
+#[no_mangle]
+pub extern "C" fn uno_status_message(status: i32) -> *const c_char {
+    let message: &'static [u8] = match status {
+        UNO_OK => b"ok\0",
+        UNO_ERR_NULL => b"a required pointer was null\0",
+        UNO_ERR_INVALID => b"the text was not understood\0",
+        UNO_ERR_NO_SUCH_SEAT => b"there is no such seat\0",
+        UNO_ERR_ILLEGAL => b"the action is not allowed now\0",
+        UNO_ERR_BUFFER => b"the buffer is too small\0",
+        UNO_ERR_PANIC => b"the engine failed unexpectedly\0",
+        _ => b"unknown status\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+/// Deals a new game between `players` people named by `names`, with
+/// `rules` (null for the official ones) and a seed for the shuffle.
+///
+/// # Safety
+///
+/// `names` has to point to `players` NUL terminated strings, `rules` has
+/// to be null or a NUL terminated string, and `game` writable.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_new(names: *const *const c_char, players: usize, rules: *const c_char, seed: u64, game: *mut *mut UnoGame) -> i32 {
+    guard(|| {
+        if names.is_null() || game.is_null() {
+            return Err(UNO_ERR_NULL);
+        }
+        let players = (0..players).map(|i| text(*names.add(i)).map(Player::new)).collect::<Result<Vec<_>, _>>()?;
+        let rules = if rules.is_null() { Rules::official() } else { parse_rules(text(rules)?).ok_or(UNO_ERR_INVALID)? };
+        if players.iter().any(|player| player.name().is_empty()) || rules.check(players.len()).is_err() {
+            return Err(UNO_ERR_INVALID);
+        }
+        let uno = Uno::create_game_with(players, rules, seed);
+        write_out(game, Box::into_raw(Box::new(UnoGame { uno, last: None })))
+    })
+}
+
+/// Releases a game. Null is ignored.
+///
+/// # Safety
+///
+/// `game` has to be null or come from `uno_game_new`, and not be used again.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_free(game: *mut UnoGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// # Safety
+///
+/// `game` has to come from `uno_game_new`, and `seat` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_current_seat(game: *const UnoGame, seat: *mut usize) -> i32 {
+    guard(|| write_out(seat, self::game(game)?.uno.current_seat()))
+}
+
+/// Stores the seat that won, or -1 while the game goes on.
+///
+/// # Safety
+///
+/// `game` has to come from `uno_game_new`, and `winner` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_winner(game: *const UnoGame, winner: *mut i32) -> i32 {
+    guard(|| write_out(winner, self::game(game)?.uno.winner().map_or(-1, |seat| seat as i32)))
+}
+
+/// Writes what `seat` can see of the game.
+///
+/// # Safety
+///
+/// `game` has to come from `uno_game_new`, `buffer` hold `size` bytes, and
+/// `length` be null or writable.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_view(game: *const UnoGame, seat: usize, buffer: *mut c_char, size: usize, length: *mut usize) -> i32 {
+    guard(|| {
+        let uno = &self::game(game)?.uno;
+        if seat >= uno.players().len() {
+            return Err(UNO_ERR_NO_SUCH_SEAT);
+        }
+        write_text(&line_text(ServerLine::View(uno.view(seat))), buffer, size, length)
+    })
+}
+
+/// Writes the current player's legal actions, comma separated.
+///
+/// # Safety
+///
+/// As for `uno_game_view`.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_legal_actions(game: *const UnoGame, buffer: *mut c_char, size: usize, length: *mut usize) -> i32 {
+    guard(|| {
+        let actions = self::game(game)?.uno.legal_actions();
+        write_text(&line_text(ServerLine::YourMove(actions)), buffer, size, length)
+    })
+}
+
+/// Takes an action for the current player.
+///
+/// # Safety
+///
+/// `game` has to come from `uno_game_new` and `action` be a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_apply(game: *mut UnoGame, action: *const c_char) -> i32 {
+    guard(|| {
+        let game = game.as_mut().ok_or(UNO_ERR_NULL)?;
+        let action = match text(action)?.parse() {
+            Ok(ClientLine::Act(action)) => action,
+            _ => return Err(UNO_ERR_INVALID),
+        };
+        if let Action::Swap(seat) = action {
+            if seat >= game.uno.players().len() {
+                return Err(UNO_ERR_NO_SUCH_SEAT);
+            }
+        }
+        let result = game.uno.apply(action);
+        let illegal = matches!(result, TurnResult::IllegalAction(_) | TurnResult::InvalidMove(..) | TurnResult::NotHoldingCard(_));
+        game.last = Some(result);
+        if illegal {
+            return Err(UNO_ERR_ILLEGAL);
+        }
+        Ok(())
+    })
+}
+
+/// Writes what came of the last action applied, or nothing before the first.
+///
+/// # Safety
+///
+/// As for `uno_game_view`.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_last_result(game: *const UnoGame, buffer: *mut c_char, size: usize, length: *mut usize) -> i32 {
+    guard(|| {
+        let result = self::game(game)?.last.clone().map(|result| line_text(ServerLine::Result(result))).unwrap_or_default();
+        write_text(&result, buffer, size, length)
+    })
+}
+
+/// Calls UNO for the current player, before they play their next card.
+///
+/// # Safety
+///
+/// `game` has to come from `uno_game_new`.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_call_uno(game: *mut UnoGame) -> i32 {
+    guard(|| {
+        game.as_mut().ok_or(UNO_ERR_NULL)?.uno.call_uno();
+        Ok(())
+    })
+}
+
+/// Stores how many events have happened, which `uno_game_event` numbers from 0.
+///
+/// # Safety
+///
+/// `game` has to come from `uno_game_new`, and `count` be writable.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_event_count(game: *const UnoGame, count: *mut usize) -> i32 {
+    guard(|| write_out(count, self::game(game)?.uno.events().len()))
+}
+
+/// Writes an event, oldest first. Returns `UNO_ERR_INVALID` past the last.
+///
+/// # Safety
+///
+/// As for `uno_game_view`.
+#[no_mangle]
+pub unsafe extern "C" fn uno_game_event(game: *const UnoGame, index: usize, buffer: *mut c_char, size: usize, length: *mut usize) -> i32 {
+    guard(|| {
+        let event = self::game(game)?.uno.events().get(index).ok_or(UNO_ERR_INVALID)?;
+        write_text(&line_text(ServerLine::Event(*event)), buffer, size, length)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    fn new_game(names: &[&str], rules: Option<&str>) -> (i32, *mut UnoGame) {
+        let names: Vec<CString> = names.iter().map(|&name| CString::new(name).unwrap()).collect();
+        let pointers: Vec<*const c_char> = names.iter().map(|name| name.as_ptr()).collect();
+        let rules = rules.map(|rules| CString::new(rules).unwrap());
+        let mut game = ptr::null_mut();
+        let status = unsafe { uno_game_new(pointers.as_ptr(), pointers.len(), rules.as_ref().map_or(ptr::null(), |rules| rules.as_ptr()), 5, &mut game) };
+        (status, game)
+    }
+
+    fn read(f: impl Fn(*mut c_char, usize, *mut usize) -> i32) -> Result<String, i32> {
+        let mut length = 0;
+        match f(ptr::null_mut(), 0, &mut length) {
+            UNO_ERR_BUFFER => {},
+            status => return Err(status),
+        }
+        let mut buffer = vec![0u8; length + 1];
+        match f(buffer.as_mut_ptr() as *mut c_char, buffer.len(), &mut length) {
+            UNO_OK => Ok(String::from_utf8(buffer[..length].to_vec()).unwrap()),
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn games_are_played_through_handles() {
+        assert_eq!(UNO_ERR_INVALID, new_game(&["Ann"], None).0);
+        assert_eq!(UNO_ERR_INVALID, new_game(&["Ann", "Bo"], Some("hand-size=99")).0);
+        assert_eq!(UNO_ERR_INVALID, new_game(&["Ann", ""], None).0);
+        let (status, game) = new_game(&["Ann", "Bo"], Some("stacking"));
+        assert_eq!(UNO_OK, status);
+
+        unsafe {
+            let view = read(|buffer, size, length| uno_game_view(game, 1, buffer, size, length)).unwrap();
+            assert!(view.starts_with("seat=1 "), "{}", view);
+            assert!(view.contains("stacking=on"), "{}", view);
+            assert_eq!(Err(UNO_ERR_NO_SUCH_SEAT), read(|buffer, size, length| uno_game_view(game, 2, buffer, size, length)));
+
+            let actions = read(|buffer, size, length| uno_game_legal_actions(game, buffer, size, length)).unwrap();
+            assert!(actions.split(',').any(|action| action == "DRAW"), "{}", actions);
+            assert_eq!(UNO_ERR_ILLEGAL, uno_game_apply(game, b"PASS\0".as_ptr() as *const c_char));
+            assert_eq!(UNO_ERR_INVALID, uno_game_apply(game, b"DANCE\0".as_ptr() as *const c_char));
+            assert_eq!(UNO_OK, uno_game_apply(game, b"DRAW\0".as_ptr() as *const c_char));
+            let result = read(|buffer, size, length| uno_game_last_result(game, buffer, size, length)).unwrap();
+            assert!(result.starts_with("DREW "), "{}", result);
+
+            let mut count = 0;
+            assert_eq!(UNO_OK, uno_game_event_count(game, &mut count));
+            let event = read(|buffer, size, length| uno_game_event(game, count - 1, buffer, size, length)).unwrap();
+            assert!(event.starts_with("DREW "), "{}", event);
+            assert_eq!(Err(UNO_ERR_INVALID), read(|buffer, size, length| uno_game_event(game, count, buffer, size, length)));
+
+            assert_eq!(UNO_ERR_NULL, uno_game_current_seat(game, ptr::null_mut()));
+            assert_eq!(UNO_ERR_NULL, uno_game_apply(ptr::null_mut(), b"DRAW\0".as_ptr() as *const c_char));
+            uno_game_free(game);
+        }
+    }
+}
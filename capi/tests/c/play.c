@@ -0,0 +1,82 @@
+/* Plays a game to the end through the C interface, always playing the
+ * first card it can, or else taking the first legal action. Exits with 0 if everything behaved as uno.h says. */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "uno.h"
+
+#define CHECK(call, expected)                                                          \
+    do {                                                                               \
+        int32_t status = (call);                                                       \
+        if (status != (expected)) {                                                    \
+            fprintf(stderr, "%s:%d: %s gave %d (%s)\n", __FILE__, __LINE__, #call,    \
+                    (int)status, uno_status_message(status));                          \
+            return 1;                                                                  \
+        }                                                                              \
+    } while (0)
+
+int main(void) {
+    const char *names[] = {"Ann", "Bo", "Cy"};
+    UnoGame *game = NULL;
+    char text[4096];
+    size_t length = 0;
+
+    CHECK((int32_t)uno_abi_version(), UNO_ABI_VERSION);
+    CHECK(uno_game_new(names, 1, NULL, 7, &game), UNO_ERR_INVALID);
+    CHECK(uno_game_new(names, 3, "house", 7, &game), UNO_ERR_INVALID);
+    CHECK(uno_game_new(names, 3, "hand-size=5", 7, &game), UNO_OK);
+
+    CHECK(uno_game_view(game, 0, NULL, 0, &length), UNO_ERR_BUFFER);
+    CHECK(uno_game_view(game, 0, text, sizeof text, NULL), UNO_OK);
+    if (strlen(text) != length || strncmp(text, "seat=0 ", 7) != 0) {
+        fprintf(stderr, "unexpected view: %s\n", text);
+        return 1;
+    }
+    CHECK(uno_game_view(game, 3, text, sizeof text, NULL), UNO_ERR_NO_SUCH_SEAT);
+    CHECK(uno_game_apply(game, "SHOUT"), UNO_ERR_INVALID);
+
+    int32_t winner = -1;
+    int moves = 0;
+    for (; moves < 5000; moves++) {
+        CHECK(uno_game_winner(game, &winner), UNO_OK);
+        if (winner >= 0) {
+            break;
+        }
+        CHECK(uno_game_legal_actions(game, text, sizeof text, NULL), UNO_OK);
+        char *first = strtok(text, ",");
+        if (first == NULL) {
+            fprintf(stderr, "no legal actions\n");
+            return 1;
+        }
+        char *action = first;
+        while (action != NULL && strncmp(action, "PLAY ", 5) != 0) {
+            action = strtok(NULL, ",");
+        }
+        if (action != NULL) {
+            /* calling early does no harm, and saves a penalty at one card */
+            CHECK(uno_game_call_uno(game), UNO_OK);
+        }
+        CHECK(uno_game_apply(game, action != NULL ? action : first), UNO_OK);
+    }
+    if (winner < 0) {
+        fprintf(stderr, "the game did not end\n");
+        return 1;
+    }
+    CHECK(uno_game_apply(game, "DRAW"), UNO_ERR_ILLEGAL);
+
+    size_t count = 0;
+    CHECK(uno_game_event_count(game, &count), UNO_OK);
+    CHECK(uno_game_event(game, count - 1, text, sizeof text, NULL), UNO_OK);
+    char expected[32];
+    snprintf(expected, sizeof expected, "WON %d", (int)winner);
+    if (strcmp(text, expected) != 0) {
+        fprintf(stderr, "the last event was \"%s\", not \"%s\"\n", text, expected);
+        return 1;
+    }
+    CHECK(uno_game_event(game, count, text, sizeof text, NULL), UNO_ERR_INVALID);
+
+    uno_game_free(game);
+    printf("%s won after %d moves\n", names[winner], moves);
+    return 0;
+}
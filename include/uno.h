@@ -0,0 +1,87 @@
+/*
+ * The C interface to the uno engine, built as libuno.so (uno.dll,
+ * libuno.dylib) by `cargo build`. See capi/src/lib.rs for the conventions:
+ * every function but uno_abi_version, uno_status_message and uno_game_free
+ * returns UNO_OK or a negative error code, and text is exchanged in the
+ * words of the network protocol described in src/protocol.rs.
+ */
+#ifndef UNO_H
+#define UNO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* What uno_abi_version returns for the functions declared here. */
+#define UNO_ABI_VERSION 1
+
+#define UNO_OK 0
+/* A pointer that has to be given was null. */
+#define UNO_ERR_NULL (-1)
+/* Text that isn't valid UTF-8, or doesn't say anything the engine understands. */
+#define UNO_ERR_INVALID (-2)
+#define UNO_ERR_NO_SUCH_SEAT (-3)
+/* The action is not one the current player may take now. The game is unchanged. */
+#define UNO_ERR_ILLEGAL (-4)
+/* Nothing was written; the length needed was stored instead. */
+#define UNO_ERR_BUFFER (-5)
+/* The engine failed unexpectedly. The game should be freed. */
+#define UNO_ERR_PANIC (-6)
+
+typedef struct UnoGame UnoGame;
+
+uint32_t uno_abi_version(void);
+
+/* A static description of a status, never null. */
+const char *uno_status_message(int32_t status);
+
+/*
+ * Deals a new game between `players` people named by `names`. `rules` is
+ * null for the official rules, a preset ("classic", "official", "stacking",
+ * "seven-o") or comma separated options such as "hand-size=5,stacking=on".
+ */
+int32_t uno_game_new(const char *const *names, size_t players, const char *rules, uint64_t seed, UnoGame **game);
+
+/* Releases a game. Null is ignored. */
+void uno_game_free(UnoGame *game);
+
+int32_t uno_game_current_seat(const UnoGame *game, size_t *seat);
+
+/* Stores the seat that won, or -1 while the game goes on. */
+int32_t uno_game_winner(const UnoGame *game, int32_t *winner);
+
+/*
+ * The functions below that take a buffer write NUL terminated text to it
+ * and store its length without the NUL in `length`, which may be null.
+ * When `size` is too small nothing is written and UNO_ERR_BUFFER is
+ * returned, so a null buffer of size 0 asks for the length.
+ */
+
+/* What `seat` can see: the key=value list of a VIEW line. */
+int32_t uno_game_view(const UnoGame *game, size_t seat, char *buffer, size_t size, size_t *length);
+
+/* The current player's legal actions, comma separated, e.g. "PLAY R5,DRAW". */
+int32_t uno_game_legal_actions(const UnoGame *game, char *buffer, size_t size, size_t *length);
+
+/* Takes an action such as "PLAY R5", "PLAY GC" or "DRAW" for the current player. */
+int32_t uno_game_apply(UnoGame *game, const char *action);
+
+/* What came of the last action applied, e.g. "DREW G2", or "" before the first. */
+int32_t uno_game_last_result(const UnoGame *game, char *buffer, size_t size, size_t *length);
+
+/* Calls UNO for the current player, before they play their next card. */
+int32_t uno_game_call_uno(UnoGame *game);
+
+int32_t uno_game_event_count(const UnoGame *game, size_t *count);
+
+/* An event such as "PLAYED 0 R5", counting from 0, oldest first. */
+int32_t uno_game_event(const UnoGame *game, size_t index, char *buffer, size_t size, size_t *length);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -1,34 +1,99 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
 use std::time::SystemTime;
 
-struct Xorshift {
-    state: u32,
+/// A small, fast xorshift generator. Not suitable for cryptography.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Xorshift {
+    state: u64,
 }
 
 impl Xorshift {
-    fn new(state: u32) -> Xorshift {
-        Xorshift { state }
+    /// Seeds the generator. Every seed, including zero, gives a usable stream.
+    pub fn new(seed: u64) -> Xorshift {
+        //splitmix64 so that nearby seeds don't give nearby streams
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        Xorshift { state: if z == 0 { 0x1312E1 } else { z } } //13 - 12 = 1
+    }
+
+    /// Seeds the generator from the system clock.
+    #[cfg(feature = "std")]
+    pub fn from_time() -> Xorshift {
+        let time = SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map(|dur| dur.as_nanos() as u64)
+                        .unwrap_or(0x1312E1);
+        Xorshift::new(time)
     }
 
-    fn next(&mut self) -> u32 {
+    /// Restores a generator from a value returned by `state`.
+    pub fn from_state(state: u64) -> Xorshift {
+        Xorshift { state: if state == 0 { 0x1312E1 } else { state } }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
         let mut s = self.state;
         s ^= s << 13;
-        s ^= s >> 17;
-        s ^= s << 5;
+        s ^= s >> 7;
+        s ^= s << 17;
         self.state = s;
         self.state
     }
+
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// A number in `0..n`. `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, data: &mut [T]) {
+        (1..data.len()).rev().for_each(|i| {
+            let idx = self.below(i + 1);
+            data.swap(i, idx);
+        });
+    }
 }
 
+/// Shuffles `data` with a generator seeded from the system clock.
+#[cfg(feature = "std")]
 pub fn shuffle<T>(data: &mut [T]) {
-    let time = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)
-                    .map(|dur| dur.as_nanos() as u32)
-                    .unwrap_or(0x1312E1); //13 - 12 = 1
-    let mut r = Xorshift::new(time);
-
-    let len = data.len();
-    (0..len).into_iter().for_each(|i| {
-        let idx = usize::min(r.next() as usize % len, i + 1);
-        data.swap(i, idx);
-    });
-}
\ No newline at end of file
+    Xorshift::from_time().shuffle(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_shuffle_is_repeatable() {
+        let mut a: Vec<_> = (0..50).collect();
+        let mut b = a.clone();
+        Xorshift::new(7).shuffle(&mut a);
+        Xorshift::new(7).shuffle(&mut b);
+        assert_eq!(a, b);
+        assert_ne!(a, (0..50).collect::<Vec<_>>());
+
+        a.sort();
+        assert_eq!(a, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let mut rng = Xorshift::new(0);
+        rng.next_u32();
+        let mut copy = Xorshift::from_state(rng.state());
+        assert_eq!(rng.next_u32(), copy.next_u32());
+    }
+}
@@ -0,0 +1,508 @@
+mod http;
+mod rooms;
+
+use crate::card::Card;
+use crate::direction::GameDirection;
+use crate::driver::{Driver, Seat};
+use crate::event::Event;
+use crate::json::Json;
+use crate::lobby::Lobby;
+use crate::player::Player;
+use crate::protocol::new_token;
+use crate::rules::Rules;
+use crate::strategy;
+use crate::uno::{Action, Phase, TurnResult, Uno};
+use crate::view::PlayerView;
+
+use http::{Request, Response};
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a request for events waits for one when it doesn't say.
+const DEFAULT_WAIT: u64 = 25;
+/// The longest a request for events may wait, in seconds.
+const MAX_WAIT: u64 = 60;
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a game nobody asks about is kept.
+const IDLE_TTL: Duration = Duration::from_secs(60 * 60);
+/// How long a finished game is kept after the last request about it, so
+/// that everyone gets to see how it ended.
+const FINISHED_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Games played over HTTP with JSON bodies, for web and mobile front ends.
+///
+/// | request                        | body                                     | answer                          |
+/// |--------------------------------|------------------------------------------|---------------------------------|
+/// | `POST /games`                  | `seats`, `preset`, `options`, `seed`     | `game`, `seats`, `rules`        |
+/// | `POST /games/<id>/join`        | `name`, optionally `seat`                | `seat`, `token`                 |
+/// | `GET /games/<id>/view`         |                                          | the seat's view of the game     |
+/// | `GET /games/<id>/actions`      |                                          | `actions` the seat may take now |
+/// | `POST /games/<id>/actions`     | an action, optionally with `"uno": true` | `result`                        |
+/// | `GET /games/<id>/events`       | query `since` and `wait` (seconds)       | `events`, `next`                |
+///
+/// `seats` lists `"human"` or a bot name for each seat, two humans if left
+/// out. `options` maps rule options to values as `Rules::set` takes them.
+/// Joining hands out a token for the seat, which every other request about
+/// the game sends as `Authorization: Bearer <token>` or `?token=<token>`.
+/// The game starts once every human seat has been joined, and bots move as
+/// soon as it is their turn.
+///
+/// Cards are written as in `Card`'s `Display`, e.g. `"Y0"`. Actions are
+/// objects like `{"type": "play", "card": "GC"}`, `{"type": "draw"}` or
+/// `{"type": "swap", "seat": 2}`, with the types `play`, `draw`, `pass`,
+/// `challenge`, `accept` and `swap`. Asking for events returns those from
+/// index `since` on, waiting up to `wait` seconds for one if there are none
+/// yet, and `next` is the `since` to ask with next time.
+///
+/// Errors come back as `{"error": message}` with a 4xx status. Games are
+/// forgotten an hour after the last request about them, or ten minutes
+/// after it once they are over, and asking about them then answers 404.
+///
+/// Games can also be set up in a room of a `Lobby`, found by a join code,
+/// where players take seats, the host chooses the rules and bots, and the
+/// game starts once everyone is ready:
+///
+/// | request                        | body                                     | answer                          |
+/// |--------------------------------|------------------------------------------|---------------------------------|
+/// | `GET /rooms`                   |                                          | `rooms`                         |
+/// | `POST /rooms`                  | `name`, `seats`, `preset`, `options`     | `room`, `token`                 |
+/// | `GET /rooms/<code>`            |                                          | the room                        |
+/// | `POST /rooms/<code>/claim`     | `name`, optionally `seat`                | `seat`, `token`                 |
+/// | `POST /rooms/<code>/leave`     |                                          |                                 |
+/// | `POST /rooms/<code>/ready`     | optionally `"ready": false`              |                                 |
+/// | `POST /rooms/<code>/rules`     | `preset`, `options` (host only)          | the room                        |
+/// | `POST /rooms/<code>/seats`     | `seat`, `bot` or null (host only)        | the room                        |
+/// | `POST /rooms/<code>/start`     | optionally `seed` (host only)            | the room                        |
+/// | `POST /rooms/<code>/close`     | (host only)                              |                                 |
+/// | `GET /rooms/<code>/view`       |                                          | the seat's view of the game     |
+/// | `GET /rooms/<code>/actions`    |                                          | `actions` the seat may take now |
+/// | `POST /rooms/<code>/actions`   | an action, optionally with `"uno": true` | `result`                        |
+/// | `GET /rooms/<code>/events`     | query `since`                            | `events`, `next`                |
+///
+/// Creating a room seats its host and claiming a seat sits a player down,
+/// and both hand out a token that the room's other requests send as for
+/// games; claiming again with it moves seats. `seats` is the number of
+/// seats, two if left out. Rooms close like games: an hour after the last
+/// request, ten minutes after the game ends, or when the host closes them.
+pub struct Api {
+    games: Mutex<HashMap<u64, Arc<Game>>>,
+    next_id: AtomicU64,
+    idle_ttl: Duration,
+    finished_ttl: Duration,
+    lobby: Lobby,
+    //room tokens, each for a room's code and a player's name there
+    players: Mutex<HashMap<String, (String, String)>>,
+}
+
+struct Game {
+    table: Mutex<Table>,
+    //signalled whenever something happens at the table
+    changed: Condvar,
+    //when the game was last asked about
+    touched: Mutex<Instant>,
+}
+
+impl Game {
+    fn expired(&self, now: Instant, idle_ttl: Duration, finished_ttl: Duration) -> bool {
+        let idle = now.saturating_duration_since(*self.touched.lock().unwrap());
+        //a table that is busy is in use, so only idle ones are looked at
+        let over = self.table.try_lock().is_ok_and(|table| table.driver.uno().winner().is_some());
+        idle >= idle_ttl || (over && idle >= finished_ttl)
+    }
+}
+
+struct Table {
+    driver: Driver,
+    /// Who sits in each seat. Bots are named after their strategy.
+    names: Vec<Option<String>>,
+    tokens: Vec<Option<String>>,
+}
+
+impl Table {
+    fn started(&self) -> bool {
+        (0..self.tokens.len()).all(|seat| !self.driver.is_human(seat) || self.tokens[seat].is_some())
+    }
+}
+
+impl Default for Api {
+    fn default() -> Api {
+        Api::new()
+    }
+}
+
+impl Api {
+    pub fn new() -> Api {
+        Api::with_ttl(IDLE_TTL, FINISHED_TTL)
+    }
+
+    /// An API that forgets games after `idle_ttl` without requests, or after
+    /// `finished_ttl` once they are over.
+    pub fn with_ttl(idle_ttl: Duration, finished_ttl: Duration) -> Api {
+        Api {
+            games: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            idle_ttl,
+            finished_ttl,
+            lobby: Lobby::new(shuffle::Xorshift::from_time().next_u64()),
+            players: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Answers requests from `listener` forever, each connection on a thread
+    /// of its own so that waiting for events holds nobody else up.
+    pub fn serve(self: Arc<Api>, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("error: could not accept a connection: {}", e);
+                    continue;
+                },
+            };
+            let api = Arc::clone(&self);
+            thread::spawn(move || api.connection(stream));
+        }
+        Ok(())
+    }
+
+    fn connection(&self, stream: TcpStream) {
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let response = match Request::read(&mut BufReader::new(&stream)) {
+            Ok(Some(request)) => self.handle(&request),
+            Ok(None) => return,
+            Err(e) => Response::unreadable(e),
+        };
+        let _ = response.write(&mut &stream);
+    }
+
+    pub(crate) fn handle(&self, request: &Request) -> Response {
+        let path: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        let result = match (request.method.as_str(), path.as_slice()) {
+            ("OPTIONS", _) => Ok(Response::new(204, Json::Null)),
+            ("POST", ["games"]) => body(request).and_then(|body| self.create(&body)),
+            (_, ["games"]) => Err(Response::error(405, "use POST to create a game")),
+            (method, ["rooms", path @ ..]) => self.room_request(method, path, request),
+            (method, ["games", id, endpoint]) => {
+                let game = id.parse().ok().and_then(|id| self.games.lock().unwrap().get(&id).cloned());
+                let game = match game {
+                    Some(game) => game,
+                    None => return Response::error(404, format!("there is no game {}", id)),
+                };
+                *game.touched.lock().unwrap() = Instant::now();
+                match (method, *endpoint) {
+                    ("POST", "join") => body(request).and_then(|body| join(&game, &body)),
+                    ("GET", "view") => view(&game, request),
+                    ("GET", "actions") => actions(&game, request),
+                    ("POST", "actions") => body(request).and_then(|body| act(&game, request, &body)),
+                    ("GET", "events") => events(&game, request),
+                    (_, "join") | (_, "view") | (_, "actions") | (_, "events") => Err(Response::error(405, "method not allowed")),
+                    _ => Err(Response::error(404, "no such endpoint")),
+                }
+            },
+            _ => Err(Response::error(404, "no such endpoint")),
+        };
+        result.unwrap_or_else(|error| error)
+    }
+
+    fn create(&self, body: &Json) -> Result<Response, Response> {
+        let seats: Vec<String> = match body.get("seats") {
+            None => vec!["human".into(), "human".into()],
+            Some(Json::Array(seats)) => seats.iter()
+                .map(|seat| seat.as_str().map(|seat| seat.trim().to_lowercase()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| Response::error(400, "seats must be strings"))?,
+            Some(_) => return Err(Response::error(400, "seats must be a list")),
+        };
+        if let Some(bot) = seats.iter().find(|seat| *seat != "human" && strategy::by_name(seat, 0).is_none()) {
+            return Err(Response::error(400, format!("unknown bot \"{}\" (expected human or one of {})", bot, strategy::NAMES.join(", "))));
+        }
+
+        let rules = rules(body, seats.len())?;
+        let seed = seed(body)?;
+        let players = (0..seats.len()).map(|seat| Player::new(format!("Seat {}", seat + 1))).collect();
+        let uno = Uno::create_game_with(players, rules.clone(), seed);
+        let controllers = seats.iter().enumerate().map(|(i, seat)| match seat.as_ref() {
+            "human" => Seat::Human,
+            bot => Seat::Computer(strategy::by_name(bot, seed.wrapping_add(i as u64)).unwrap()),
+        }).collect();
+        let names = seats.iter().map(|seat| Some(seat.clone()).filter(|seat| seat != "human")).collect();
+        let mut table = Table { driver: Driver::new(uno, controllers), names, tokens: vec![None; seats.len()] };
+        if table.started() {
+            table.driver.run();
+        }
+
+        self.forget_expired();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let game = Game { table: Mutex::new(table), changed: Condvar::new(), touched: Mutex::new(Instant::now()) };
+        self.games.lock().unwrap().insert(id, Arc::new(game));
+        Ok(Response::new(201, Json::object(vec![("game", Json::from(id)), ("seats", Json::from(seats)), ("rules", Json::from(rules.to_string()))])))
+    }
+
+    /// Drops the games that have expired. Runs whenever a game is created, so
+    /// that the number kept stays bounded by how many are being played.
+    fn forget_expired(&self) {
+        //copy the games out so that no table is locked under the games lock
+        let games: Vec<(u64, Arc<Game>)> = self.games.lock().unwrap().iter().map(|(&id, game)| (id, Arc::clone(game))).collect();
+        let now = Instant::now();
+        let expired: Vec<u64> = games.iter()
+            .filter(|(_, game)| game.expired(now, self.idle_ttl, self.finished_ttl))
+            .map(|&(id, _)| id)
+            .collect();
+        let mut games = self.games.lock().unwrap();
+        for id in expired {
+            games.remove(&id);
+        }
+    }
+}
+
+/// The request's body as JSON, with an empty body read as `{}`.
+fn body(request: &Request) -> Result<Json, Response> {
+    match request.body.trim() {
+        "" => Ok(Json::Object(Vec::new())),
+        body => body.parse().map_err(|e| Response::error(400, e)),
+    }
+}
+
+/// The rules a body's `preset` and `options` make, checked for `seats` seats.
+fn rules(body: &Json, seats: usize) -> Result<Rules, Response> {
+    let mut rules = match body.get("preset") {
+        None => Rules::default(),
+        Some(Json::String(preset)) => Rules::preset(preset).map_err(|e| Response::error(400, e))?,
+        Some(_) => return Err(Response::error(400, "preset must be a string")),
+    };
+    match body.get("options") {
+        None => {},
+        Some(Json::Object(options)) => {
+            for (key, value) in options {
+                let value = match value {
+                    Json::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                rules.set(key, &value).map_err(|e| Response::error(400, e))?;
+            }
+        },
+        Some(_) => return Err(Response::error(400, "options must be an object")),
+    }
+    rules.check(seats).map_err(|e| Response::error(400, e))?;
+    Ok(rules)
+}
+
+/// A body's `seed`, or one from the clock.
+fn seed(body: &Json) -> Result<u64, Response> {
+    match body.get("seed") {
+        None => Ok(shuffle::Xorshift::from_time().next_u64()),
+        Some(seed) => seed.as_u64().ok_or_else(|| Response::error(400, "seed must be a whole number")),
+    }
+}
+
+/// The token the request carries, if any.
+fn token(request: &Request) -> Option<&str> {
+    let token = request.header("authorization").and_then(|value| value.strip_prefix("Bearer ")).or_else(|| request.query("token"));
+    token.map(str::trim)
+}
+
+/// The seat whose token the request carries.
+fn seat(table: &Table, request: &Request) -> Result<usize, Response> {
+    let token = token(request).ok_or_else(|| Response::error(401, "send your seat's token"))?;
+    table.tokens.iter().position(|seat| seat.as_deref() == Some(token)).ok_or_else(|| Response::error(403, "that token is not for this game"))
+}
+
+fn lock(game: &Game) -> MutexGuard<'_, Table> {
+    game.table.lock().unwrap()
+}
+
+fn join(game: &Game, body: &Json) -> Result<Response, Response> {
+    let name = body.get("name").and_then(Json::as_str).map(str::trim).unwrap_or("");
+    if name.is_empty() {
+        return Err(Response::error(400, "send a name to join under"));
+    }
+
+    let mut table = lock(game);
+    let open = |table: &Table, seat: usize| table.driver.is_human(seat) && table.tokens[seat].is_none();
+    let seat = match body.get("seat") {
+        None => (0..table.tokens.len()).find(|&seat| open(&table, seat)).ok_or_else(|| Response::error(409, "every seat is taken"))?,
+        Some(seat) => match seat.as_u64() {
+            Some(seat) if (seat as usize) < table.tokens.len() && open(&table, seat as usize) => seat as usize,
+            Some(seat) if (seat as usize) < table.tokens.len() => return Err(Response::error(409, format!("seat {} is taken", seat))),
+            _ => return Err(Response::error(400, "no such seat")),
+        },
+    };
+
+    let token = new_token();
+    table.tokens[seat] = Some(token.clone());
+    table.names[seat] = Some(name.to_string());
+    if table.started() {
+        table.driver.run();
+    }
+    game.changed.notify_all();
+    Ok(Response::new(200, Json::object(vec![("seat", Json::from(seat)), ("token", Json::from(token))])))
+}
+
+fn view(game: &Game, request: &Request) -> Result<Response, Response> {
+    let table = lock(game);
+    let seat = seat(&table, request)?;
+    let uno = table.driver.uno();
+    let mut json = view_json(&uno.view(seat));
+    if let Json::Object(fields) = &mut json {
+        fields.push(("names".into(), Json::Array(table.names.iter().map(|name| Json::from(name.clone())).collect())));
+        fields.push(("started".into(), Json::from(table.started())));
+        fields.push(("events".into(), Json::from(uno.events().len())));
+    }
+    Ok(Response::new(200, json))
+}
+
+fn actions(game: &Game, request: &Request) -> Result<Response, Response> {
+    let table = lock(game);
+    let seat = seat(&table, request)?;
+    let uno = table.driver.uno();
+    let actions = match table.started() && uno.current_seat() == seat {
+        true => uno.legal_actions(),
+        false => Vec::new(),
+    };
+    Ok(Response::new(200, Json::object(vec![("actions", Json::Array(actions.into_iter().map(action_json).collect()))])))
+}
+
+fn act(game: &Game, request: &Request, body: &Json) -> Result<Response, Response> {
+    let action = parse_action(body).ok_or_else(|| Response::error(400, "not an action"))?;
+    let mut table = lock(game);
+    let seat = seat(&table, request)?;
+    if !table.started() {
+        return Err(Response::error(409, "the game is waiting for players"));
+    }
+    if table.driver.uno().winner().is_some() {
+        return Err(Response::error(409, "the game is over"));
+    }
+    if table.driver.uno().current_seat() != seat {
+        return Err(Response::error(409, "it is not your turn"));
+    }
+
+    if body.get("uno").and_then(Json::as_bool) == Some(true) {
+        table.driver.call_uno();
+    }
+    let result = table.driver.apply(action);
+    table.driver.run();
+    game.changed.notify_all();
+    Ok(Response::new(200, Json::object(vec![("result", result_json(&result))])))
+}
+
+fn events(game: &Game, request: &Request) -> Result<Response, Response> {
+    let number = |key: &str, default: u64| match request.query(key) {
+        None => Ok(default),
+        Some(value) => value.parse::<u64>().map_err(|_| Response::error(400, format!("{} must be a whole number", key))),
+    };
+    let since = number("since", 0)? as usize;
+    let wait = Duration::from_secs(number("wait", DEFAULT_WAIT)?.min(MAX_WAIT));
+
+    let table = lock(game);
+    seat(&table, request)?;
+    //nothing more will happen once the game is over
+    let (table, _) = game.changed
+        .wait_timeout_while(table, wait, |table| table.driver.uno().events().len() <= since && table.driver.uno().winner().is_none())
+        .unwrap();
+
+    let events = table.driver.uno().events();
+    let new = events.get(since..).unwrap_or(&[]);
+    Ok(Response::new(200, Json::object(vec![
+        ("events", Json::Array(new.iter().map(event_json).collect())),
+        ("next", Json::from(events.len().max(since))),
+    ])))
+}
+
+fn card_json(card: Card) -> Json {
+    Json::from(card.to_string())
+}
+
+fn cards_json(cards: &[Card]) -> Json {
+    Json::Array(cards.iter().map(|&card| card_json(card)).collect())
+}
+
+fn view_json(view: &PlayerView) -> Json {
+    let (phase, offender, winner) = match view.phase {
+        Phase::Play => ("play", None, None),
+        Phase::Drawn => ("drawn", None, None),
+        Phase::Challenge { offender } => ("challenge", Some(offender), None),
+        Phase::Swap => ("swap", None, None),
+        Phase::GameOver { winner } => ("over", None, Some(winner)),
+    };
+    Json::object(vec![
+        ("seat", Json::from(view.seat)),
+        ("hand", cards_json(&view.hand)),
+        ("top", card_json(view.top)),
+        ("discard", cards_json(&view.discard)),
+        ("direction", Json::from(match view.direction {
+            GameDirection::Clockwise => "clockwise",
+            GameDirection::CounterClockwise => "counter-clockwise",
+        })),
+        ("current", Json::from(view.current_seat)),
+        ("phase", Json::from(phase)),
+        ("offender", Json::from(offender)),
+        ("winner", Json::from(winner)),
+        ("drawn", Json::from(view.drawn.map(|card| card.to_string()))),
+        ("pending_draw", Json::from(view.pending_draw)),
+        ("hand_sizes", Json::from(view.hand_sizes.clone())),
+        ("draw_pile", Json::from(view.draw_pile)),
+        ("turn", Json::from(view.turn)),
+        ("rules", Json::from(view.rules.to_string())),
+    ])
+}
+
+fn action_json(action: Action) -> Json {
+    match action {
+        Action::Play(card) => Json::object(vec![("type", Json::from("play")), ("card", card_json(card))]),
+        Action::Draw => Json::object(vec![("type", Json::from("draw"))]),
+        Action::Pass => Json::object(vec![("type", Json::from("pass"))]),
+        Action::Challenge => Json::object(vec![("type", Json::from("challenge"))]),
+        Action::Accept => Json::object(vec![("type", Json::from("accept"))]),
+        Action::Swap(seat) => Json::object(vec![("type", Json::from("swap")), ("seat", Json::from(seat))]),
+    }
+}
+
+fn parse_action(json: &Json) -> Option<Action> {
+    match json.get("type")?.as_str()? {
+        "play" => json.get("card")?.as_str()?.parse().ok().map(Action::Play),
+        "draw" => Some(Action::Draw),
+        "pass" => Some(Action::Pass),
+        "challenge" => Some(Action::Challenge),
+        "accept" => Some(Action::Accept),
+        "swap" => json.get("seat")?.as_u64().map(|seat| Action::Swap(seat as usize)),
+        _ => None,
+    }
+}
+
+fn event_json(event: &Event) -> Json {
+    let kind = |name: &str| ("type", Json::from(name));
+    match *event {
+        Event::Played { seat, card } => Json::object(vec![kind("played"), ("seat", Json::from(seat)), ("card", card_json(card))]),
+        Event::Drew { seat, count, forced } => Json::object(vec![kind("drew"), ("seat", Json::from(seat)), ("count", Json::from(count)), ("forced", Json::from(forced))]),
+        Event::Passed { seat } => Json::object(vec![kind("passed"), ("seat", Json::from(seat))]),
+        Event::Skipped { seat } => Json::object(vec![kind("skipped"), ("seat", Json::from(seat))]),
+        Event::Challenged { seat, offender, bluffed } => Json::object(vec![kind("challenged"), ("seat", Json::from(seat)), ("offender", Json::from(offender)), ("bluffed", Json::from(bluffed))]),
+        Event::Swapped { seat, with } => Json::object(vec![kind("swapped"), ("seat", Json::from(seat)), ("with", Json::from(with))]),
+        Event::Rotated => Json::object(vec![kind("rotated")]),
+        Event::UnoCalled { seat } => Json::object(vec![kind("uno"), ("seat", Json::from(seat))]),
+        Event::UnoPenalty { seat } => Json::object(vec![kind("uno_penalty"), ("seat", Json::from(seat))]),
+        Event::Reshuffled => Json::object(vec![kind("reshuffled")]),
+        Event::Won { seat } => Json::object(vec![kind("won"), ("seat", Json::from(seat))]),
+    }
+}
+
+fn result_json(result: &TurnResult) -> Json {
+    let kind = |name: &str| ("type", Json::from(name));
+    match result {
+        TurnResult::Success(card) => Json::object(vec![kind("played"), ("card", card_json(*card))]),
+        TurnResult::InvalidMove(on, played) => Json::object(vec![kind("invalid"), ("played", card_json(*played)), ("on", card_json(*on))]),
+        TurnResult::NotHoldingCard(card) => Json::object(vec![kind("not_holding"), ("card", card_json(*card))]),
+        TurnResult::Drew(cards) => Json::object(vec![kind("drew"), ("cards", cards_json(cards))]),
+        TurnResult::Passed => Json::object(vec![kind("passed")]),
+        TurnResult::Challenged { bluffed } => Json::object(vec![kind("challenged"), ("bluffed", Json::from(*bluffed))]),
+        TurnResult::Swapped(with) => Json::object(vec![kind("swapped"), ("with", Json::from(*with))]),
+        TurnResult::IllegalAction(action) => Json::object(vec![kind("illegal"), ("action", action_json(*action))]),
+        TurnResult::GameOver => Json::object(vec![kind("won")]),
+    }
+}
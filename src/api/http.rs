@@ -0,0 +1,159 @@
+use crate::json::Json;
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, Read, Write};
+
+/// Requests may not be bigger than this, headers and body alike.
+const MAX_SIZE: usize = 64 * 1024;
+
+/// Why a request that says it is bigger than `MAX_SIZE` was not read.
+#[derive(Debug)]
+pub struct TooLarge;
+
+impl Display for TooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "requests may not be bigger than {} bytes", MAX_SIZE)
+    }
+}
+
+impl Error for TooLarge {}
+
+/// The parts of an HTTP/1.1 request the API looks at.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Request {
+    pub method: String,
+    /// Without the query string.
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    /// Names are lowercase.
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    /// Reads one request. `Ok(None)` means the connection closed before one began.
+    /// A body too big to be read is a `TooLarge` error, found before any of it is.
+    pub fn read<R: BufRead>(reader: &mut R) -> io::Result<Option<Request>> {
+        let bad = |why: &str| io::Error::new(io::ErrorKind::InvalidData, why);
+        let mut reader = reader.take(MAX_SIZE as u64);
+
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let mut words = line.split_whitespace();
+        let (method, target) = match (words.next(), words.next(), words.next()) {
+            (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => (method.to_string(), target.to_string()),
+            _ => return Err(bad("not an HTTP/1 request line")),
+        };
+
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(bad("headers cut off"));
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line.split_once(':').ok_or_else(|| bad("header without a colon"))?;
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+
+        let length = match headers.iter().find(|(name, _)| name == "content-length") {
+            Some((_, length)) => length.parse::<u64>().map_err(|_| bad("bad content length"))?,
+            None => 0,
+        };
+        if length > reader.limit() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, TooLarge));
+        }
+        let length = length as usize;
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        let body = String::from_utf8(body).map_err(|_| bad("body is not UTF-8"))?;
+
+        let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+        let query = query.split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect();
+
+        Ok(Some(Request { method, path: path.to_string(), query, headers, body }))
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn query(&self, key: &str) -> Option<&str> {
+        self.query.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+}
+
+/// A JSON response.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Json,
+}
+
+impl Response {
+    pub fn new(status: u16, body: Json) -> Response {
+        Response { status, body }
+    }
+
+    /// The answer to a request that could not be read.
+    pub fn unreadable(error: io::Error) -> Response {
+        match error.get_ref() {
+            Some(inner) if inner.is::<TooLarge>() => Response::error(413, inner),
+            _ => Response::error(400, error),
+        }
+    }
+
+    /// `{"error": message}` with the given status.
+    pub fn error<S: ToString>(status: u16, message: S) -> Response {
+        Response::new(status, Json::object(vec![("error", Json::from(message.to_string()))]))
+    }
+
+    /// Writes the response and asks for the connection to be closed. Any
+    /// origin may call the API, so browsers can use it from other sites.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            201 => "Created",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            _ => "Internal Server Error",
+        };
+        let body = match self.status {
+            204 => String::new(),
+            _ => self.body.to_string(),
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Access-Control-Allow-Origin: *\r\n\
+             Access-Control-Allow-Headers: Authorization, Content-Type\r\n\
+             Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+             Connection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            body.len(),
+            body
+        )?;
+        writer.flush()
+    }
+}
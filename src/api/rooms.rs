@@ -0,0 +1,194 @@
+use super::http::{Request, Response};
+use super::{action_json, body, event_json, parse_action, result_json, rules, seed, token, view_json, Api};
+use crate::json::Json;
+use crate::lobby::{LobbyError, Occupant, RoomInfo, RoomStatus};
+use crate::protocol::new_token;
+
+impl Api {
+    /// Answers a request under `/rooms`, `path` being what follows it.
+    pub(super) fn room_request(&self, method: &str, path: &[&str], request: &Request) -> Result<Response, Response> {
+        match (method, path) {
+            ("GET", []) => {
+                let rooms = self.lobby.rooms().iter().map(room_json).collect();
+                Ok(Response::new(200, Json::object(vec![("rooms", Json::Array(rooms))])))
+            },
+            ("POST", []) => body(request).and_then(|body| self.create_room(&body)),
+            (_, []) => Err(Response::error(405, "use GET to list rooms or POST to create one")),
+            ("GET", [code]) => self.room(code),
+            (_, [_]) => Err(Response::error(405, "method not allowed")),
+            ("POST", [code, "claim"]) => body(request).and_then(|body| self.claim(code, request, &body)),
+            ("POST", [code, "leave"]) => {
+                let name = self.player(code, request)?;
+                self.lobby.leave(code, &name).map_err(lobby_error)?;
+                self.players.lock().unwrap().remove(token(request).unwrap());
+                Ok(Response::new(200, Json::Object(Vec::new())))
+            },
+            ("POST", [code, "ready"]) => {
+                let body = body(request)?;
+                let ready = body.get("ready").map(|ready| ready.as_bool().ok_or_else(|| Response::error(400, "ready must be true or false"))).transpose()?;
+                self.lobby.ready(code, &self.player(code, request)?, ready.unwrap_or(true)).map_err(lobby_error)?;
+                self.room(code)
+            },
+            ("POST", [code, "rules"]) => {
+                let body = body(request)?;
+                let seats = self.lobby.room(code).map_err(lobby_error)?.seats.len();
+                self.lobby.set_rules(code, &self.player(code, request)?, rules(&body, seats)?).map_err(lobby_error)?;
+                self.room(code)
+            },
+            ("POST", [code, "seats"]) => {
+                let body = body(request)?;
+                let seat = body.get("seat").and_then(Json::as_u64).ok_or_else(|| Response::error(400, "send the seat to fill or empty"))?;
+                let bot = match body.get("bot") {
+                    None | Some(Json::Null) => None,
+                    Some(bot) => Some(bot.as_str().ok_or_else(|| Response::error(400, "bot must be a string or null"))?),
+                };
+                self.lobby.set_seat(code, &self.player(code, request)?, seat as usize, bot).map_err(lobby_error)?;
+                self.room(code)
+            },
+            ("POST", [code, "start"]) => {
+                let seed = seed(&body(request)?)?;
+                self.lobby.start(code, &self.player(code, request)?, seed).map_err(lobby_error)?;
+                self.room(code)
+            },
+            ("POST", [code, "close"]) => {
+                self.lobby.close(code, &self.player(code, request)?).map_err(lobby_error)?;
+                self.forget_players();
+                Ok(Response::new(200, Json::Object(Vec::new())))
+            },
+            ("GET", [code, "view"]) => {
+                let view = self.lobby.view(code, &self.player(code, request)?).map_err(lobby_error)?;
+                Ok(Response::new(200, view_json(&view)))
+            },
+            ("GET", [code, "actions"]) => {
+                let actions = self.lobby.legal_actions(code, &self.player(code, request)?).map_err(lobby_error)?;
+                Ok(Response::new(200, Json::object(vec![("actions", Json::Array(actions.into_iter().map(action_json).collect()))])))
+            },
+            ("POST", [code, "actions"]) => {
+                let body = body(request)?;
+                let action = parse_action(&body).ok_or_else(|| Response::error(400, "not an action"))?;
+                let uno = body.get("uno").and_then(Json::as_bool) == Some(true);
+                let result = self.lobby.play(code, &self.player(code, request)?, action, uno).map_err(lobby_error)?;
+                Ok(Response::new(200, Json::object(vec![("result", result_json(&result))])))
+            },
+            ("GET", [code, "events"]) => {
+                self.player(code, request)?;
+                let since = match request.query("since") {
+                    None => 0,
+                    Some(since) => since.parse().map_err(|_| Response::error(400, "since must be a whole number"))?,
+                };
+                let events = self.lobby.events(code, since).map_err(lobby_error)?;
+                Ok(Response::new(200, Json::object(vec![
+                    ("events", Json::Array(events.iter().map(event_json).collect())),
+                    ("next", Json::from(since + events.len())),
+                ])))
+            },
+            (_, [_, "claim"]) | (_, [_, "leave"]) | (_, [_, "ready"]) | (_, [_, "rules"]) | (_, [_, "seats"]) | (_, [_, "start"]) | (_, [_, "close"]) | (_, [_, "view"]) | (_, [_, "actions"]) | (_, [_, "events"]) => {
+                Err(Response::error(405, "method not allowed"))
+            },
+            _ => Err(Response::error(404, "no such endpoint")),
+        }
+    }
+
+    fn room(&self, code: &str) -> Result<Response, Response> {
+        let room = self.lobby.room(code).map_err(lobby_error)?;
+        Ok(Response::new(200, room_json(&room)))
+    }
+
+    fn create_room(&self, body: &Json) -> Result<Response, Response> {
+        let name = name(body)?;
+        let seats = match body.get("seats") {
+            None => 2,
+            Some(seats) => seats.as_u64().ok_or_else(|| Response::error(400, "seats must be a whole number"))? as usize,
+        };
+        let rules = rules(body, seats)?;
+
+        self.lobby.close_expired(self.idle_ttl, self.finished_ttl);
+        self.forget_players();
+        let code = self.lobby.create(&name, seats, rules).map_err(lobby_error)?;
+        let token = new_token();
+        self.players.lock().unwrap().insert(token.clone(), (code.clone(), name));
+        Ok(Response::new(201, Json::object(vec![("room", Json::from(code)), ("token", Json::from(token))])))
+    }
+
+    /// Seats a new player, or moves the one whose token the request carries.
+    fn claim(&self, code: &str, request: &Request, body: &Json) -> Result<Response, Response> {
+        let seat = match body.get("seat") {
+            None => None,
+            Some(seat) => Some(seat.as_u64().ok_or_else(|| Response::error(400, "no such seat"))? as usize),
+        };
+        let (name, token) = match token(request) {
+            Some(_) => (self.player(code, request)?, None),
+            None => {
+                let name = name(body)?;
+                //a name is only ever claimed once, or its seat could be taken from whoever has it
+                let taken = self.lobby.room(code).map_err(lobby_error)?.seats.iter().any(|occupant| matches!(occupant, Occupant::Human { name: seated, .. } if *seated == name));
+                if taken {
+                    return Err(Response::error(409, format!("{} already has a seat", name)));
+                }
+                (name, Some(new_token()))
+            },
+        };
+
+        let seat = self.lobby.claim(code, &name, seat).map_err(lobby_error)?;
+        let mut fields = vec![("seat", Json::from(seat))];
+        if let Some(token) = token {
+            self.players.lock().unwrap().insert(token.clone(), (code.trim().to_uppercase(), name));
+            fields.push(("token", Json::from(token)));
+        }
+        Ok(Response::new(200, Json::object(fields)))
+    }
+
+    /// The name of the player in room `code` whose token the request carries.
+    fn player(&self, code: &str, request: &Request) -> Result<String, Response> {
+        let token = token(request).ok_or_else(|| Response::error(401, "send your seat's token"))?;
+        match self.players.lock().unwrap().get(token) {
+            Some((room, name)) if room.eq_ignore_ascii_case(code.trim()) => Ok(name.clone()),
+            _ => Err(Response::error(403, "that token is not for this room")),
+        }
+    }
+
+    /// Drops the tokens for rooms that have closed.
+    fn forget_players(&self) {
+        let open: Vec<String> = self.lobby.rooms().into_iter().map(|room| room.code).collect();
+        self.players.lock().unwrap().retain(|_, (code, _)| open.contains(code));
+    }
+}
+
+/// The body's `name`, trimmed and not empty.
+fn name(body: &Json) -> Result<String, Response> {
+    match body.get("name").and_then(Json::as_str).map(str::trim) {
+        Some(name) if !name.is_empty() => Ok(name.to_string()),
+        _ => Err(Response::error(400, "send a name to sit under")),
+    }
+}
+
+fn lobby_error(error: LobbyError) -> Response {
+    let status = match error {
+        LobbyError::NoSuchRoom(_) => 404,
+        LobbyError::NotHost(_) | LobbyError::NotSeated(_) => 403,
+        LobbyError::NoSuchSeat(_) | LobbyError::UnknownBot(_) | LobbyError::Rules(_) => 400,
+        _ => 409,
+    };
+    Response::error(status, error)
+}
+
+fn room_json(room: &RoomInfo) -> Json {
+    let seats = room.seats.iter().map(|occupant| match occupant {
+        Occupant::Open => Json::object(vec![("type", Json::from("open"))]),
+        Occupant::Human { name, ready } => Json::object(vec![("type", Json::from("human")), ("name", Json::from(name.clone())), ("ready", Json::from(*ready))]),
+        Occupant::Bot(bot) => Json::object(vec![("type", Json::from("bot")), ("name", Json::from(bot.clone()))]),
+    });
+    let (status, winner) = match room.status {
+        RoomStatus::Waiting => ("waiting", None),
+        RoomStatus::Playing => ("playing", None),
+        RoomStatus::Finished { winner } => ("finished", Some(winner)),
+    };
+    Json::object(vec![
+        ("room", Json::from(room.code.clone())),
+        ("host", Json::from(room.host.clone())),
+        ("seats", Json::Array(seats.collect())),
+        ("rules", Json::from(room.rules.to_string())),
+        ("status", Json::from(status)),
+        ("winner", Json::from(winner)),
+    ])
+}
@@ -0,0 +1,170 @@
+use crate::rules::Rules;
+use crate::sim::GameRecord;
+
+use std::fmt::{self, Display};
+
+/// How a rule set played out over a batch of simulated games.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RuleReport {
+    pub name: String,
+    pub rules: Rules,
+    pub games: usize,
+    pub unfinished: usize,
+    /// The share of games won from each seat. Seat 0 plays first.
+    pub seat_win_rates: Vec<f64>,
+    /// Game lengths in turns, shortest first, finished games only.
+    pub lengths: Vec<usize>,
+    /// The share of games in which the draw pile ran out.
+    pub reshuffled: f64,
+    pub reshuffles_per_game: f64,
+    pub penalties_per_game: f64,
+    /// Cards per penalty, UNO penalties included.
+    pub average_penalty: f64,
+    pub skips_per_game: f64,
+    pub reverses_per_game: f64,
+    /// The first seat's win rate in the games where the fewest and the most
+    /// turns were a skip or reverse, split at the median. Counted per turn so
+    /// long games do not fall in the upper half just for being long.
+    pub first_seat_by_action_rate: (f64, f64),
+}
+
+impl RuleReport {
+    pub fn new<S: ToString>(name: S, rules: Rules, records: &[GameRecord]) -> RuleReport {
+        let games = records.len();
+        let per_game = |total: usize| total as f64 / games.max(1) as f64;
+        let seats = records.iter().map(|record| record.bots.len()).max().unwrap_or(0);
+
+        let mut seat_wins = vec![0; seats];
+        records.iter().filter_map(|record| record.winner).for_each(|seat| seat_wins[seat] += 1);
+
+        let mut lengths: Vec<usize> = records.iter().filter(|record| record.winner.is_some()).map(|record| record.turns).collect();
+        lengths.sort_unstable();
+
+        let penalties: usize = records.iter().map(|record| record.penalties).sum();
+        let penalty_cards: usize = records.iter().map(|record| record.penalty_cards).sum();
+
+        let mut by_action: Vec<&GameRecord> = records.iter().collect();
+        let action_rate = |record: &GameRecord| (record.skips + record.reverses) as f64 / record.turns.max(1) as f64;
+        by_action.sort_by(|a, b| action_rate(a).total_cmp(&action_rate(b)));
+        let (few, many) = by_action.split_at(games / 2);
+        let first_seat = |records: &[&GameRecord]| {
+            records.iter().filter(|record| record.winner == Some(0)).count() as f64 / records.len().max(1) as f64
+        };
+
+        RuleReport {
+            name: name.to_string(),
+            rules,
+            games,
+            unfinished: games - lengths.len(),
+            seat_win_rates: seat_wins.iter().map(|&wins| per_game(wins)).collect(),
+            reshuffled: per_game(records.iter().filter(|record| record.reshuffles > 0).count()),
+            reshuffles_per_game: per_game(records.iter().map(|record| record.reshuffles).sum()),
+            penalties_per_game: per_game(penalties),
+            average_penalty: penalty_cards as f64 / penalties.max(1) as f64,
+            skips_per_game: per_game(records.iter().map(|record| record.skips).sum()),
+            reverses_per_game: per_game(records.iter().map(|record| record.reverses).sum()),
+            first_seat_by_action_rate: (first_seat(few), first_seat(many)),
+            lengths,
+        }
+    }
+
+    /// How much more often the first seat wins than an even share would give it.
+    pub fn first_player_advantage(&self) -> f64 {
+        self.seat_win_rates.first().copied().unwrap_or(0.0) - 1.0 / self.seat_win_rates.len().max(1) as f64
+    }
+
+    /// Half the width of the 95% confidence interval of the first seat's win rate.
+    pub fn first_player_margin(&self) -> f64 {
+        let p = self.seat_win_rates.first().copied().unwrap_or(0.0);
+        1.96 * (p * (1.0 - p) / self.games.max(1) as f64).sqrt()
+    }
+
+    /// The game length `fraction` of the way through the finished games.
+    pub fn length_percentile(&self, fraction: f64) -> usize {
+        match self.lengths.len() {
+            0 => 0,
+            len => self.lengths[((len - 1) as f64 * fraction).round() as usize],
+        }
+    }
+
+    pub fn mean_length(&self) -> f64 {
+        self.lengths.iter().sum::<usize>() as f64 / self.lengths.len().max(1) as f64
+    }
+}
+
+/// Several rule sets side by side, one column each.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Comparison(pub Vec<RuleReport>);
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = |value: f64| format!("{:.1}%", 100.0 * value);
+        let row = |label: &'static str, cell: &dyn Fn(&RuleReport) -> String| (label, self.0.iter().map(cell).collect::<Vec<_>>());
+        let rows = vec![
+            row("games", &|r| format!("{} ({} unfinished)", r.games, r.unfinished)),
+            row("first seat wins", &|r| format!("{} ±{:.1}", percent(r.seat_win_rates[0]), 100.0 * r.first_player_margin())),
+            row("first seat advantage", &|r| format!("{:+.1} pts", 100.0 * r.first_player_advantage())),
+            row("wins by seat", &|r| r.seat_win_rates.iter().map(|rate| format!("{:.0}", 100.0 * rate)).collect::<Vec<_>>().join("/")),
+            row("length, mean", &|r| format!("{:.1}", r.mean_length())),
+            row("length, median", &|r| r.length_percentile(0.5).to_string()),
+            row("length, 10%-90%", &|r| format!("{}-{}", r.length_percentile(0.1), r.length_percentile(0.9))),
+            row("length, longest", &|r| r.length_percentile(1.0).to_string()),
+            row("draw pile ran out", &|r| percent(r.reshuffled)),
+            row("reshuffles a game", &|r| format!("{:.2}", r.reshuffles_per_game)),
+            row("penalties a game", &|r| format!("{:.1}", r.penalties_per_game)),
+            row("cards a penalty", &|r| format!("{:.2}", r.average_penalty)),
+            row("skips a game", &|r| format!("{:.1}", r.skips_per_game)),
+            row("reverses a game", &|r| format!("{:.1}", r.reverses_per_game)),
+            row("first seat, few skip/rev a turn", &|r| percent(r.first_seat_by_action_rate.0)),
+            row("first seat, many skip/rev a turn", &|r| percent(r.first_seat_by_action_rate.1)),
+        ];
+
+        let label = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = self.0.iter().enumerate()
+            .map(|(i, report)| rows.iter().map(|(_, row)| row[i].len()).chain(Some(report.name.len())).max().unwrap())
+            .collect();
+
+        write!(f, "{:<width$}", "", width = label)?;
+        for (report, width) in self.0.iter().zip(widths.iter()) {
+            write!(f, "  {:>width$}", report.name, width = width)?;
+        }
+        writeln!(f)?;
+
+        for (name, row) in rows.iter() {
+            write!(f, "{:<width$}", name, width = label)?;
+            for (cell, width) in row.iter().zip(widths.iter()) {
+                write!(f, "  {:>width$}", cell, width = width)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::Batch;
+
+    #[test]
+    fn compares_rule_sets() {
+        let report = |name: &str, rules: Rules| {
+            let mut batch = Batch::new(vec!["greedy".into(); 3], rules.clone());
+            batch.games = 200;
+            RuleReport::new(name, rules, &batch.run())
+        };
+        let official = report("official", Rules::official());
+        let stacking = report("stacking", Rules::stacking());
+
+        assert_eq!(200, official.games);
+        assert!((official.seat_win_rates.iter().sum::<f64>() - 1.0).abs() < 0.05);
+        assert!(official.length_percentile(0.1) <= official.length_percentile(0.9));
+        //stacked penalties are bigger
+        assert!(stacking.average_penalty > official.average_penalty);
+
+        let table = Comparison(vec![official, stacking]).to_string();
+        assert!(table.lines().next().unwrap().ends_with("stacking"));
+        assert!(table.contains("draw pile ran out"));
+        assert!(table.contains("many skip/rev a turn"));
+    }
+}
@@ -0,0 +1,376 @@
+use crate::card::{color::Color, face::Face, Card};
+use crate::deck::Deck;
+use crate::direction::GameDirection;
+use crate::env::card_index;
+use crate::event::Event;
+use crate::view::PlayerView;
+
+/// Card kinds: the 52 colored cards as `13 * color + face`, indexed like the
+/// action space, then 52 for the Wild Card and 53 for the Draw Four.
+pub const KINDS: usize = 54;
+
+/// How much less likely a kind becomes once a player seems not to hold it.
+/// Players may draw while holding something playable, so it is never ruled out.
+const UNLIKELY: f64 = 0.1;
+/// The same, for what a failed challenge proved a player did not hold.
+const RULED_OUT: f64 = 0.001;
+/// Groups of cards tracked separately in one hand before the oldest are merged.
+const MAX_GROUPS: usize = 6;
+/// Rounds of balancing the hands against the unseen cards.
+const ROUNDS: usize = 40;
+
+/// The kind of `card`. Wilds are one kind whatever color they carry.
+pub fn kind(card: Card) -> usize {
+    match card.face {
+        Face::ColorCard => 52,
+        Face::DrawFour => 53,
+        _ => card_index(card),
+    }
+}
+
+/// The kinds of colored card in `color`.
+fn colored(color: Color) -> std::ops::Range<usize> {
+    13 * color as usize..13 * color as usize + 13
+}
+
+/// How many of each kind a standard deck holds.
+fn deck_counts() -> [f64; KINDS] {
+    let mut counts = [0.0; KINDS];
+    Deck::standard().iter().for_each(|&card| counts[kind(card)] += 1.0);
+    counts
+}
+
+/// Cards that joined a hand together, and how likely each kind is among them
+/// compared to the unseen cards as a whole.
+#[derive(Clone, PartialEq, Debug)]
+struct Group {
+    count: f64,
+    weights: [f64; KINDS],
+}
+
+/// What is believed about one seat's hand.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct Hand {
+    groups: Vec<Group>,
+}
+
+impl Hand {
+    fn dealt(count: usize) -> Hand {
+        let mut hand = Hand::default();
+        hand.draw(count as f64);
+        hand
+    }
+
+    fn count(&self) -> f64 {
+        self.groups.iter().map(|group| group.count).sum()
+    }
+
+    /// Adds cards nothing is known about.
+    fn draw(&mut self, count: f64) {
+        match self.groups.last_mut() {
+            Some(last) if last.weights.iter().all(|&w| w == 1.0) => last.count += count,
+            _ => self.groups.push(Group { count, weights: [1.0; KINDS] }),
+        }
+        if self.groups.len() > MAX_GROUPS {
+            let (a, b) = (self.groups.remove(0), &mut self.groups[0]);
+            let total = a.count + b.count;
+            for (w, &old) in b.weights.iter_mut().zip(a.weights.iter()) {
+                *w = (*w * b.count + old * a.count) / total.max(f64::MIN_POSITIVE);
+            }
+            b.count = total;
+        }
+    }
+
+    /// Takes out a card of `kind`, from whichever groups it most likely came from.
+    fn play(&mut self, kind: usize) {
+        let total: f64 = self.groups.iter().map(|group| group.count * group.weights[kind]).sum();
+        let count = self.count();
+        for group in self.groups.iter_mut() {
+            let share = match total > 0.0 {
+                true => group.count * group.weights[kind] / total,
+                false => group.count / count.max(f64::MIN_POSITIVE),
+            };
+            group.count = (group.count - share).max(0.0);
+        }
+        self.groups.retain(|group| group.count > 1e-9);
+    }
+
+    /// Makes `kinds` less likely everywhere in the hand.
+    fn lacks<I: Iterator<Item = usize>>(&mut self, kinds: I, factor: f64) {
+        for kind in kinds {
+            self.groups.iter_mut().for_each(|group| group.weights[kind] *= factor);
+        }
+    }
+}
+
+/// Card counting for one player: what the other hands and the draw pile
+/// probably hold, worked out from the public events.
+///
+/// Every unseen card is equally likely to be anywhere until the events say
+/// otherwise. Drawing instead of playing suggests a player had nothing that
+/// matched the top card, and losing a Draw Four challenge proves they had
+/// nothing of the color it was played on. Cards drawn afterwards are unknown
+/// again, and hands that change seats take what was known with them.
+///
+/// Pass it every event with `observe`, then ask for an `estimate` with the
+/// player's current view. It may be created partway through a game, as long
+/// as it is shown the events from the start.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Beliefs {
+    //empty until the first estimate, which tells how big the table is
+    hands: Vec<Hand>,
+    direction: GameDirection,
+    //the top of the discard pile and the card under it, once known
+    top: Option<Card>,
+    under: Option<Card>,
+    //events seen before the first estimate
+    waiting: Vec<Event>,
+}
+
+impl Beliefs {
+    pub fn new() -> Beliefs {
+        Beliefs::default()
+    }
+
+    /// Takes in the next public event of the game.
+    pub fn observe(&mut self, event: &Event) {
+        if self.hands.is_empty() {
+            self.waiting.push(*event);
+            return;
+        }
+
+        match *event {
+            Event::Played { seat, card } => {
+                self.hands[seat].play(kind(card));
+                if card.face == Face::Reverse {
+                    self.direction = !self.direction;
+                }
+                self.under = self.top.replace(card);
+            },
+            Event::Drew { seat, count, forced } => {
+                //drawing by choice usually means nothing matched
+                if let (false, Some(top)) = (forced, self.top) {
+                    let face = match top.face.is_wild() {
+                        true => None,
+                        false => Some(kind(top) % 13),
+                    };
+                    let matches = (0..52).filter(|k| k / 13 == top.color as usize || Some(k % 13) == face).chain(52..KINDS);
+                    self.hands[seat].lacks(matches, UNLIKELY);
+                }
+                self.hands[seat].draw(count as f64);
+            },
+            Event::Challenged { offender, bluffed: false, .. } => {
+                //a fair Draw Four proves the player had nothing of the color it went on
+                if let Some(under) = self.under {
+                    self.hands[offender].lacks(colored(under.color), RULED_OUT);
+                }
+            },
+            Event::Swapped { seat, with } => self.hands.swap(seat, with),
+            Event::Rotated => match self.direction {
+                GameDirection::Clockwise => self.hands.rotate_right(1),
+                GameDirection::CounterClockwise => self.hands.rotate_left(1),
+            },
+            _ => {},
+        }
+    }
+
+    /// What everyone else probably holds, as `view.seat` sees it now.
+    pub fn estimate(&mut self, view: &PlayerView) -> Estimate {
+        if self.hands.is_empty() {
+            self.hands = vec![Hand::dealt(view.rules.hand_size); view.hand_sizes.len()];
+            //the card the game started on is at the bottom of the discard, unless it has been reshuffled since
+            if !self.waiting.contains(&Event::Reshuffled) {
+                self.top = view.discard.last().copied();
+            }
+            std::mem::take(&mut self.waiting).iter().for_each(|event| self.observe(event));
+        }
+
+        let mut unseen = deck_counts();
+        view.hand.iter().chain(view.discard.iter()).for_each(|&card| unseen[kind(card)] -= 1.0);
+        unseen.iter_mut().for_each(|count| *count = f64::max(*count, 0.0));
+
+        //a reshuffle with an empty discard pile brings in a fresh deck
+        let hidden = view.hand_sizes.iter().sum::<usize>() - view.hand.len() + view.draw_pile;
+        let mut total: f64 = unseen.iter().sum();
+        while total < hidden as f64 {
+            unseen.iter_mut().zip(deck_counts().iter()).for_each(|(count, &more)| *count += more);
+            total += 108.0;
+        }
+        let columns: Vec<f64> = unseen.iter().map(|&count| count * hidden as f64 / total.max(1.0)).collect();
+
+        //one row for each group of cards in another hand, then one for the draw pile
+        let mut rows: Vec<(usize, f64, [f64; KINDS])> = Vec::new();
+        for (seat, hand) in self.hands.iter().enumerate().filter(|&(seat, _)| seat != view.seat) {
+            let size = view.hand_sizes[seat] as f64;
+            let count = hand.count();
+            match count > 0.0 {
+                true => hand.groups.iter().for_each(|group| rows.push((seat, group.count * size / count, group.weights))),
+                false => rows.push((seat, size, [1.0; KINDS])),
+            }
+        }
+        rows.push((view.seat, view.draw_pile as f64, [1.0; KINDS]));
+
+        let mut cells: Vec<[f64; KINDS]> = rows.iter()
+            .map(|(_, _, weights)| {
+                let mut row = [0.0; KINDS];
+                row.iter_mut().enumerate().for_each(|(k, cell)| *cell = weights[k] * columns[k]);
+                row
+            })
+            .collect();
+        for _ in 0..ROUNDS {
+            for (row, &(_, target, _)) in cells.iter_mut().zip(rows.iter()) {
+                let sum: f64 = row.iter().sum();
+                if sum > 0.0 {
+                    row.iter_mut().for_each(|cell| *cell *= target / sum);
+                }
+            }
+            for (k, &target) in columns.iter().enumerate() {
+                let sum: f64 = cells.iter().map(|row| row[k]).sum();
+                if sum > 0.0 {
+                    cells.iter_mut().for_each(|row| row[k] *= target / sum);
+                }
+            }
+        }
+
+        let mut hands = vec![[0.0; KINDS]; view.hand_sizes.len()];
+        view.hand.iter().for_each(|&card| hands[view.seat][kind(card)] += 1.0);
+        let draw_pile = cells.pop().unwrap();
+        for (row, &(seat, _, _)) in cells.iter().zip(rows.iter()) {
+            hands[seat].iter_mut().zip(row.iter()).for_each(|(sum, &cell)| *sum += cell);
+        }
+
+        Estimate { hand_sizes: view.hand_sizes.clone(), hands, draw_pile, unseen }
+    }
+}
+
+/// How many of each card kind every hand and the draw pile are expected to
+/// hold. Indexed by `kind`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Estimate {
+    pub hand_sizes: Vec<usize>,
+    /// Expected cards of each kind, by seat. Exact for the player's own hand.
+    pub hands: Vec<[f64; KINDS]>,
+    pub draw_pile: [f64; KINDS],
+    /// Cards of each kind not in the player's hand or the discard pile.
+    pub unseen: [f64; KINDS],
+}
+
+impl Estimate {
+    /// The chance `seat` holds at least one card of one of `kinds`.
+    pub fn holds_any<I: IntoIterator<Item = usize>>(&self, seat: usize, kinds: I) -> f64 {
+        let size = self.hand_sizes[seat] as f64;
+        if size == 0.0 {
+            return 0.0;
+        }
+        let expected: f64 = kinds.into_iter().map(|k| self.hands[seat][k]).sum();
+        //as if each card were dealt independently
+        1.0 - (1.0 - (expected / size).min(1.0)).powf(size)
+    }
+
+    /// The chance `seat` holds a card of `color`, wilds not counted.
+    pub fn holds_color(&self, seat: usize, color: Color) -> f64 {
+        self.holds_any(seat, colored(color))
+    }
+
+    /// The chance `seat` holds a Wild Card or a Draw Four.
+    pub fn holds_wild(&self, seat: usize) -> f64 {
+        self.holds_any(seat, 52..KINDS)
+    }
+
+    /// How many cards of `color` the draw pile is expected to hold, wilds not counted.
+    pub fn draw_pile_color(&self, color: Color) -> f64 {
+        colored(color).map(|k| self.draw_pile[k]).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::uno::{Action, TurnResult, Uno};
+
+    #[test]
+    fn counts_add_up() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into(), "C".into()], Rules::official(), 5);
+        uno.apply(Action::Draw);
+        let mut beliefs = Beliefs::new();
+        uno.events().iter().for_each(|event| beliefs.observe(event));
+        let estimate = beliefs.estimate(&uno.view(1));
+
+        for (seat, hand) in estimate.hands.iter().enumerate() {
+            assert!((hand.iter().sum::<f64>() - uno.players()[seat].get_hand().len() as f64).abs() < 1e-6);
+        }
+        assert!((estimate.draw_pile.iter().sum::<f64>() - uno.deck().len() as f64).abs() < 1e-6);
+        for k in 0..KINDS {
+            let placed = estimate.draw_pile[k] + (0..3).filter(|&seat| seat != 1).map(|seat| estimate.hands[seat][k]).sum::<f64>();
+            assert!((placed - estimate.unseen[k]).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn drawing_suggests_a_void() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 2);
+        let top = uno.top_card();
+        let mut beliefs = Beliefs::new();
+        let before = beliefs.estimate(&uno.view(1));
+
+        uno.apply(Action::Draw);
+        uno.events().iter().for_each(|event| beliefs.observe(event));
+        let after = beliefs.estimate(&uno.view(1));
+        assert!(after.holds_color(0, top.color) < before.holds_color(0, top.color) / 2.0);
+        //the cards it seems not to have are somewhere else
+        assert!(after.draw_pile_color(top.color) > before.draw_pile_color(top.color));
+    }
+
+    /// Gives the player to move `cards`, each a color letter and a face.
+    fn deal(uno: &mut Uno, cards: &[String]) {
+        *uno.current_player().get_hand_mut() = cards.iter().map(|card| card.parse().unwrap()).collect::<Vec<Card>>().into();
+    }
+
+    /// Has the player to move play a Draw Four holding nothing of the color on
+    /// top, and the next player challenge it. Returns the offender, the color
+    /// on top and the one declared.
+    fn fair_draw_four(uno: &mut Uno) -> (usize, Color, Color) {
+        let top = uno.top_card().color;
+        let other = [Color::Red, Color::Green, Color::Blue, Color::Yellow].iter().copied().find(|&color| color != top).unwrap();
+        let offender = uno.current_seat();
+        let letter = char::from(other);
+        deal(uno, &["D", "1", "2", "3", "4", "5", "6", "8"].iter().map(|face| format!("{}{}", letter, face)).collect::<Vec<_>>());
+        uno.apply(Action::Play(format!("{}D", letter).parse().unwrap()));
+        assert_eq!(TurnResult::Challenged { bluffed: false }, uno.apply(Action::Challenge));
+        (offender, top, other)
+    }
+
+    #[test]
+    fn a_fair_draw_four_rules_out_a_color() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 8);
+        let (offender, top, other) = fair_draw_four(&mut uno);
+        let mut beliefs = Beliefs::new();
+        uno.events().iter().for_each(|event| beliefs.observe(event));
+
+        let estimate = beliefs.estimate(&uno.view(1 - offender));
+        assert!(estimate.holds_color(offender, top) < 0.05);
+        let third = [Color::Red, Color::Green, Color::Blue, Color::Yellow].iter().copied().find(|&color| color != top && color != other).unwrap();
+        assert!(estimate.holds_color(offender, third) > 0.5);
+    }
+
+    #[test]
+    fn knowledge_moves_with_the_hand() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into(), "C".into()], Rules::seven_o(), 8);
+        let (offender, top, other) = fair_draw_four(&mut uno);
+
+        //the next player to move swaps hands with the offender
+        let swapper = uno.current_seat();
+        let letter = char::from(other);
+        deal(&mut uno, &["7", "9", "1", "2", "3", "4", "5", "6"].iter().map(|face| format!("{}{}", letter, face)).collect::<Vec<_>>());
+        uno.apply(Action::Play(format!("{}7", letter).parse().unwrap()));
+        uno.apply(Action::Swap(offender));
+        assert!(uno.events().contains(&Event::Swapped { seat: swapper, with: offender }));
+
+        let mut beliefs = Beliefs::new();
+        uno.events().iter().for_each(|event| beliefs.observe(event));
+        let estimate = beliefs.estimate(&uno.view(3 - offender - swapper));
+        assert!(estimate.holds_color(swapper, top) < 0.05);
+        assert!(estimate.holds_color(offender, top) > 0.5);
+    }
+}
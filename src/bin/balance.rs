@@ -0,0 +1,147 @@
+use uno::{strategy, Batch, Comparison, RuleReport, Rules};
+
+use std::env;
+use std::process;
+
+const USAGE: &str = "\
+usage: balance [options] <RULES> <RULES> [RULES...]
+
+Plays the same seeded games under each rule set and compares them.
+RULES is a preset, optionally followed by changes: stacking, official+uno-penalty=4,
+seven-o+hand-size=5+challenge=off
+
+options:
+  -g, --games <N>         games for each rule set (default 5000)
+  -n, --players <N>       players at the table (default 4, or one per bot)
+  -b, --bots <A,B,...>    the strategy in each seat, or one for every seat (default greedy)
+  -s, --seed <N>          seed of the first game, the rest count up (default 0)
+      --max-turns <N>     give up on games longer than this (default 10000)
+  -j, --threads <N>       games to play at once (default 1)
+  -h, --help              show this help";
+
+struct Config {
+    /// Each rule set with the name it was given on the command line.
+    rule_sets: Vec<(String, Rules)>,
+    batch: Batch,
+}
+
+fn usage() -> String {
+    format!("{}\n\n{}\n\nbots: {}", USAGE, Rules::usage(), strategy::NAMES.join(", "))
+}
+
+/// A preset followed by `+option=value` changes.
+fn parse_rules(spec: &str) -> Result<Rules, String> {
+    let mut parts = spec.split('+');
+    let mut rules = Rules::preset(parts.next().unwrap()).map_err(|e| e.to_string())?;
+    for change in parts {
+        rules.apply(change).map_err(|e| e.to_string())?;
+    }
+    Ok(rules)
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut batch = Batch::new(Vec::new(), Rules::default());
+    batch.games = 5000;
+    let mut players = None;
+    let mut bots = vec!["greedy".to_string()];
+    let mut rule_sets = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        let number = |value: String| value.parse::<usize>().map_err(|_| format!("{} must be a number", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-g" | "--games" => batch.games = number(value()?)?,
+            "-n" | "--players" => players = Some(number(value()?)?),
+            "-b" | "--bots" => bots = value()?.split(',').map(|name| name.trim().to_lowercase()).collect(),
+            "-s" | "--seed" => batch.seed = value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?,
+            "--max-turns" => batch.max_turns = number(value()?)?,
+            "-j" | "--threads" => batch.threads = number(value()?)?,
+            spec if spec.starts_with('-') => return Err(format!("unknown argument \"{}\"", spec)),
+            spec => rule_sets.push((spec.to_string(), parse_rules(spec)?)),
+        }
+    }
+
+    if rule_sets.len() < 2 {
+        return Err("give at least two rule sets to compare".into());
+    }
+    batch.bots = match (bots.len(), players) {
+        (1, players) => vec![bots[0].clone(); players.unwrap_or(4)],
+        (count, Some(players)) if count != players => {
+            return Err(format!("--players is {} but {} bots were given", players, count));
+        },
+        _ => bots,
+    };
+    for (_, rules) in rule_sets.iter() {
+        batch.rules = rules.clone();
+        batch.check().map_err(|e| e.to_string())?;
+    }
+
+    Ok(Some(Config { rule_sets, batch }))
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("balance: {}\n\n{}", e, usage());
+            process::exit(2);
+        },
+    };
+
+    let mut batch = config.batch;
+    println!("{} games of each rule set between {}, seeds {} and up\n", batch.games, batch.bots.join(", "), batch.seed);
+
+    let reports = config.rule_sets.into_iter().map(|(name, rules)| {
+        batch.rules = rules.clone();
+        RuleReport::new(name, rules, &batch.run())
+    }).collect();
+    print!("{}", Comparison(reports));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn rule_specs() {
+        assert_eq!(Rules::stacking(), parse_rules("stacking").unwrap());
+        let rules = parse_rules("seven-o+hand-size=5+challenge=off").unwrap();
+        assert!(rules.seven_o);
+        assert_eq!(5, rules.hand_size);
+        assert!(!rules.challenge_draw_four);
+        assert!(parse_rules("house").is_err());
+        assert!(parse_rules("official+stacking").is_err());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("-g 100 -n 3 -b expert -s 9 -j 2 official stacking+uno-penalty=4").unwrap().unwrap();
+        assert_eq!(2, config.rule_sets.len());
+        assert_eq!("stacking+uno-penalty=4", config.rule_sets[1].0);
+        assert_eq!(4, config.rule_sets[1].1.uno_penalty);
+        assert_eq!(vec!["expert"; 3], config.batch.bots);
+        assert_eq!(100, config.batch.games);
+        assert_eq!(9, config.batch.seed);
+        assert!(parse("-h").unwrap().is_none());
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse("official").is_err());
+        assert!(parse("official stacking -b nobody").is_err());
+        assert_eq!(Err("--players is 2 but 3 bots were given".to_string()), parse("official stacking -b basic,basic,basic -n 2").map(|_| ()));
+        assert!(parse("official stacking -b basic,greedy,basic -n 3").is_ok());
+        assert!(parse("official stacking+hand-size=13 -n 9").is_err());
+        assert!(parse("--bogus official stacking").is_err());
+    }
+}
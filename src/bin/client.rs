@@ -0,0 +1,297 @@
+use uno::{decide_from_view, parse_command, strategy, Action, ClientLine, Command, Locale, Message, PlayerView, RenderMode, Renderer, ServerLine, Strategy};
+
+use std::env;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How many times in a row to try getting a lost connection back.
+const RECONNECT_ATTEMPTS: usize = 10;
+const RECONNECT_WAIT: Duration = Duration::from_secs(1);
+/// How often to tell the server we are still here, well inside its timeout.
+const KEEPALIVE: Duration = Duration::from_secs(10);
+
+const USAGE: &str = "\
+usage: client [options]
+
+Joins a game hosted by the server binary, and comes back to it if the
+connection is lost.
+
+options:
+  -a, --address <ADDR>    server to connect to (default 127.0.0.1:7777)
+      --name <NAME>       name to play under (default: $USER, or Player)
+      --bot <NAME>        let a computer strategy play for you
+      --locale <CODE>     language for messages: en or es (or set UNO_LOCALE)
+  -h, --help              show this help";
+
+struct Config {
+    address: String,
+    name: String,
+    bot: Option<String>,
+    locale: Locale,
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut address = "127.0.0.1:7777".to_string();
+    let mut name = env::var("USER").unwrap_or_else(|_| "Player".to_string());
+    let mut bot = None;
+    let mut locale = env::var("UNO_LOCALE").ok();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-a" | "--address" => address = value()?,
+            "--name" => name = value()?.trim().to_string(),
+            "--bot" => bot = Some(value()?),
+            "--locale" => locale = Some(value()?),
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    if name.is_empty() || name.contains(',') {
+        return Err(format!("names cannot be empty or contain commas: \"{}\"", name));
+    }
+    if let Some(bot) = &bot {
+        if strategy::by_name(bot, 0).is_none() {
+            return Err(format!("unknown bot \"{}\" (expected one of {})", bot, strategy::NAMES.join(", ")));
+        }
+    }
+    let locale = match locale {
+        Some(code) => code.parse().map_err(|e: &str| e.to_string())?,
+        None => Locale::default(),
+    };
+
+    Ok(Some(Config { address, name, bot, locale }))
+}
+
+/// The writing half of the connection, shared with the thread keeping it alive
+/// so that their lines never interleave.
+#[derive(Clone)]
+struct Link(Arc<Mutex<TcpStream>>);
+
+impl Link {
+    fn send(&self, line: &ClientLine) -> io::Result<()> {
+        writeln!(self.0.lock().unwrap(), "{}", line)
+    }
+
+    /// Sends PING every `KEEPALIVE` while a person thinks or the table waits
+    /// on someone else, so the server doesn't give the seat away. Stops once
+    /// the session is over or the connection is gone.
+    fn keep_alive(&self) {
+        let link = Arc::downgrade(&self.0);
+        thread::spawn(move || loop {
+            thread::sleep(KEEPALIVE);
+            let alive = link.upgrade().map(|server| Link(server).send(&ClientLine::Ping).is_ok());
+            if alive != Some(true) {
+                return;
+            }
+        });
+    }
+}
+
+/// Asks the person at the keyboard for a move until they give one, sending
+/// any UNO call straight away. `None` means they left.
+fn ask<R: BufRead>(stdin: &mut R, server: &Link, view: &PlayerView, names: &[String], renderer: &Renderer, locale: Locale) -> io::Result<Option<Action>> {
+    renderer.table(view, names).iter().for_each(|line| println!("{}", line));
+    renderer.prompt(view, names).iter().for_each(|line| println!("{}", line));
+    loop {
+        println!("{}", locale.message(&Message::YourMove));
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        match parse_command(&line, view, names) {
+            Ok(Some(Command::Act(action))) => return Ok(Some(action)),
+            Ok(Some(Command::CallUno)) => {
+                server.send(&ClientLine::Uno)?;
+                println!("{}", locale.message(&Message::UnoCalled));
+            },
+            Ok(Some(Command::Exit)) => return Ok(None),
+            //the client never sees the whole game, so it has nothing to save
+            Ok(Some(Command::Save(_))) | Ok(Some(Command::Load(_))) | Ok(None) => {},
+            Err(e) => println!("{}", locale.message(&Message::CouldNotParse { input: line.trim(), error: &e })),
+        }
+    }
+}
+
+/// What the client knows of the game, kept when the connection is lost.
+struct Game {
+    bot: Option<Box<dyn Strategy>>,
+    seat: usize,
+    names: Vec<String>,
+    view: Option<PlayerView>,
+    /// Takes the seat back after losing the connection.
+    token: Option<String>,
+    /// Events heard so far, so a resumed session hears only the ones missed.
+    events: usize,
+}
+
+fn play(config: &Config) -> io::Result<()> {
+    let mut game = Game { bot: config.bot.as_ref().map(|name| strategy::by_name(name, 0).unwrap()), seat: 0, names: Vec::new(), view: None, token: None, events: 0 };
+    let stdin = io::stdin();
+    let mut stdin = BufReader::new(stdin.lock());
+    let mut attempt = 0;
+    loop {
+        let hello = match &game.token {
+            Some(token) => ClientLine::Resume { token: token.clone(), seen: Some(game.events) },
+            None => ClientLine::Join(config.name.clone()),
+        };
+        let result = TcpStream::connect(&config.address).and_then(|server| session(config, server, hello, &mut game, &mut stdin, &mut attempt));
+        match result {
+            Ok(true) => return Ok(()),
+            //lost the connection without being told the game is over
+            Ok(false) | Err(_) if game.token.is_some() && attempt < RECONNECT_ATTEMPTS => {
+                attempt += 1;
+                println!("{}", config.locale.message(&Message::Reconnecting(attempt)));
+                thread::sleep(RECONNECT_WAIT);
+            },
+            Ok(false) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Plays over one connection, opened with `hello`, starting the count of
+/// reconnection attempts again once it is welcomed. Returns whether the game
+/// is over for this client, rather than the connection lost.
+fn session<R: BufRead>(config: &Config, server: TcpStream, hello: ClientLine, game: &mut Game, stdin: &mut R, attempt: &mut usize) -> io::Result<bool> {
+    let locale = config.locale;
+    let renderer = Renderer::new(RenderMode::detect(), locale);
+    server.set_nodelay(true)?;
+    let lines = BufReader::new(server.try_clone()?).lines();
+    let server = Link(Arc::new(Mutex::new(server)));
+    server.send(&hello)?;
+    server.keep_alive();
+
+    let mut welcomed = false;
+    for line in lines {
+        let line = line?;
+        let line = match line.parse::<ServerLine>() {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("error: {}", e);
+                continue;
+            },
+        };
+        match line {
+            ServerLine::Welcome { seat, names } => {
+                let message = match game.token {
+                    Some(_) => Message::Rejoined { name: &names[seat], seat: seat + 1 },
+                    None => Message::Seated { name: &names[seat], seat: seat + 1 },
+                };
+                println!("{}", locale.message(&message));
+                welcomed = true;
+                *attempt = 0;
+                game.seat = seat;
+                game.names = names;
+            },
+            ServerLine::Session(token) => game.token = Some(token),
+            ServerLine::View(latest) => game.view = Some(latest),
+            ServerLine::Event(event) => {
+                game.events += 1;
+                if let Some(bot) = &mut game.bot {
+                    bot.observe(&event);
+                }
+                //our own moves are reported by their results
+                if !event.is_move_by(game.seat) {
+                    println!("{}", locale.event(&event, &game.names));
+                }
+            },
+            ServerLine::YourMove(legal) => {
+                let view = match &game.view {
+                    Some(view) => view,
+                    None => continue,
+                };
+                let action = match &mut game.bot {
+                    Some(bot) => {
+                        let (action, call_uno) = decide_from_view(bot.as_mut(), view, legal);
+                        if call_uno {
+                            server.send(&ClientLine::Uno)?;
+                        }
+                        action
+                    },
+                    None => match ask(stdin, &server, view, &game.names, &renderer, locale)? {
+                        Some(action) => action,
+                        None => {
+                            server.send(&ClientLine::Quit)?;
+                            return Ok(true);
+                        },
+                    },
+                };
+                server.send(&ClientLine::Act(action))?;
+            },
+            ServerLine::Result(result) => {
+                println!();
+                println!("{}", locale.result(&result, game.seat, &game.names));
+            },
+            ServerLine::Error(message) => {
+                eprintln!("error: {}", message);
+                //turned away before getting a seat, e.g. for a name already taken
+                if !welcomed {
+                    return Ok(true);
+                }
+            },
+            ServerLine::End => return Ok(true),
+        }
+    }
+    Ok(false)
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\nrun with --help for usage", e);
+            process::exit(2);
+        },
+    };
+
+    if let Err(e) = play(&config) {
+        eprintln!("error: lost the connection to {}: {}", config.address, e);
+        process::exit(1);
+    }
+    println!("\n{}", config.locale.message(&Message::Goodbye));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("--name Ann").unwrap().unwrap();
+        assert_eq!("127.0.0.1:7777", config.address);
+        assert_eq!("Ann", config.name);
+        assert_eq!(None, config.bot);
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("-a 10.0.0.2:9000 --name Bo --bot expert --locale es").unwrap().unwrap();
+        assert_eq!("10.0.0.2:9000", config.address);
+        assert_eq!(Some("expert".to_string()), config.bot);
+        assert_eq!(Locale::Spanish, config.locale);
+    }
+
+    #[test]
+    fn bad_combinations() {
+        assert!(parse("--name A,B").is_err());
+        assert!(parse("--bot nobody").is_err());
+        assert!(parse("--locale xx").is_err());
+        assert!(parse("--frobnicate").is_err());
+    }
+}
@@ -1,76 +1,386 @@
-use uno::{Card, TurnResult, Uno};
+use uno::belief::Beliefs;
+use uno::{parse_command, parse_seconds, strategy, Color, Command, Driver, Locale, Message, Player, RenderMode, Renderer, Rollouts, RuleArgs, Rules, Seat, SystemClock, TimeControl, Timers, TurnResult, Uno};
 
-use std::io::{self, BufRead, BufReader};
+use std::env;
+use std::io::{self, BufRead};
+use std::process;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+
+const USAGE: &str = "\
+usage: game [options]
+
+options:
+  -n, --players <N>       number of players, 2-8 (default 2, or one per name)
+      --names <A,B,...>   player names, comma separated
+  -s, --seed <N>          seed for shuffling (default: from the clock)
+  -c, --computer <SEATS>  seats played by the computer, e.g. 2,3 or 2:basic,3
+      --bot <NAME>        strategy for computer seats without one (default basic)
+      --locale <CODE>     language for messages: en or es (or set UNO_LOCALE)
+      --hot-seat          hide each hand until its owner has the device
+      --assist            show what the other players probably hold and who is likely to win
+      --turn-time <S>     seconds each person has for a turn
+      --time-bank <S>     seconds each person has for the whole game
+      --increment <S>     seconds added to a person's time bank after every turn
+      --on-timeout <WHAT> what is done for someone out of time: draw (draw and
+                          pass) or auto (the basic bot moves) (default draw)
+  -h, --help              show this help";
+
+struct Config {
+    names: Vec<String>,
+    seed: Option<u64>,
+    rules: Rules,
+    /// The strategy playing each seat, or `None` for a person.
+    computer: Vec<Option<String>>,
+    locale: Locale,
+    hot_seat: bool,
+    assist: bool,
+    time: TimeControl,
+}
+
+fn usage() -> String {
+    format!("{}\n\n{}", USAGE, RuleArgs::usage())
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut count = None;
+    let mut names: Option<Vec<String>> = None;
+    let mut seed = None;
+    let mut rule_args = RuleArgs::default();
+    let mut seats = Vec::new();
+    let mut bot = "basic".to_string();
+    let mut locale = env::var("UNO_LOCALE").ok();
+    let mut hot_seat = false;
+    let mut assist = false;
+    let mut time = TimeControl::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-n" | "--players" => count = Some(value()?.parse::<usize>().map_err(|_| "--players must be a number".to_string())?),
+            "--names" => names = Some(value()?.split(',').map(|name| name.trim().to_string()).collect()),
+            "-s" | "--seed" => seed = Some(value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?),
+            flag if RuleArgs::takes(flag) => rule_args.add(flag, value()?),
+            "-c" | "--computer" => {
+                for seat in value()?.split(',') {
+                    let mut split = seat.splitn(2, ':');
+                    let number = split.next().unwrap().trim();
+                    let number = number.parse::<usize>().map_err(|_| format!("\"{}\" is not a seat number", number))?;
+                    seats.push((number, split.next().map(|name| name.trim().to_string())));
+                }
+            },
+            "--bot" => bot = value()?,
+            "--locale" => locale = Some(value()?),
+            "--hot-seat" => hot_seat = true,
+            "--assist" => assist = true,
+            "--turn-time" => time.per_turn = Some(parse_seconds(&value()?).map_err(|e| e.to_string())?),
+            "--time-bank" => time.bank = Some(parse_seconds(&value()?).map_err(|e| e.to_string())?),
+            "--increment" => time.increment = parse_seconds(&value()?).map_err(|e| e.to_string())?,
+            "--on-timeout" => time.on_timeout = value()?.parse().map_err(|e: uno::TimeError| e.to_string())?,
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    let names = match (count, names) {
+        (Some(count), Some(names)) if count != names.len() => {
+            return Err(format!("--players is {} but {} names were given", count, names.len()));
+        },
+        (_, Some(names)) => names,
+        (count, None) => {
+            let count = count.unwrap_or(2);
+            if count > Player::NAMES.len() {
+                return Err(format!("a game needs 2 to 8 players, not {}", count));
+            }
+            Player::NAMES[..count].iter().map(|&name| name.to_string()).collect()
+        },
+    };
+
+    if let Some(name) = names.iter().find(|name| name.is_empty()) {
+        return Err(format!("player names cannot be empty: \"{}\"", name));
+    }
+    if let Some(name) = names.iter().enumerate().find(|(i, name)| names[..*i].contains(name)).map(|(_, name)| name) {
+        return Err(format!("two players are called {}", name));
+    }
+
+    let rules = rule_args.rules().map_err(|e| e.to_string())?;
+    rules.check(names.len()).map_err(|e| e.to_string())?;
+
+    let mut computer = vec![None; names.len()];
+    for (seat, name) in seats {
+        if seat == 0 || seat > names.len() {
+            return Err(format!("seat {} does not exist, seats are numbered 1 to {}", seat, names.len()));
+        }
+        let name = name.unwrap_or_else(|| bot.clone());
+        if strategy::by_name(&name, 0).is_none() {
+            return Err(format!("unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")));
+        }
+        computer[seat - 1] = Some(name);
+    }
+
+    let locale = match locale {
+        Some(code) => code.parse().map_err(|e: &str| e.to_string())?,
+        None => Locale::default(),
+    };
+
+    Ok(Some(Config { names, seed, rules, computer, locale, hot_seat, assist, time }))
+}
+
+/// Reads standard input on a thread of its own, so that waiting for a
+/// line can give up when a player's time runs out.
+fn read_lines() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Waits for Enter. Returns false once input has run out.
+fn wait_for_enter(lines: &Receiver<String>) -> bool {
+    lines.recv().is_ok()
+}
 
 fn main() {
-    let players = vec![
-        "Ali".into(),
-        "Bob".into(),
-        // "Cam".into(),
-        // "Dan".into(),
-        // "Edd".into(),
-        // "Fil".into(),
-        // "Gem".into(),
-        // "Hal".into(),
-    ];
-
-    let mut uno = Uno::create_game(players);
-
-    let stdin = io::stdin();
-    let mut stdin = BufReader::new(stdin.lock());
-    let mut line = String::new();
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\nrun with --help for usage", e);
+            process::exit(2);
+        },
+    };
+
+    let locale = config.locale;
+    let renderer = Renderer::new(RenderMode::detect(), locale);
+    let players: Vec<Player> = config.names.iter().map(|name| name.as_str().into()).collect();
+    let seed = config.seed.unwrap_or_else(|| shuffle::Xorshift::from_time().next_u64());
+    let uno = Uno::create_game_with(players, config.rules.clone(), seed);
+    //seats the command line gave to the computer stay with it in loaded games
+    let start = |uno: Uno| {
+        let seats = (0..uno.players().len()).map(|i| match config.computer.get(i).cloned().flatten() {
+            Some(name) => Seat::Computer(strategy::by_name(&name, seed.wrapping_add(i as u64)).unwrap()),
+            None => Seat::Human,
+        }).collect();
+        let players = uno.players().len();
+        let mut driver = Driver::new(uno, seats);
+        if config.time.is_timed() {
+            driver.set_timers(Timers::new(config.time, players, Box::new(SystemClock)));
+        }
+        driver
+    };
+    let mut driver = start(uno);
+    let mut names = config.names.clone();
+
+    //hiding hands only matters when more than one person shares the screen
+    let hot_seat = config.hot_seat && config.computer.iter().filter(|bot| bot.is_none()).count() > 1;
+    //how far into the event log each seat had got when their last turn ended
+    let mut seen = vec![0; names.len()];
+    let mut last_human = None;
+
+    let lines = read_lines();
     loop {
-        line.clear();
-        println!("\nTurn {}", uno.current_turn());
-        println!("Cards in draw pile: {}", (*uno.deck()).len());
-        println!("Cards in discard pile: {}", (*uno.discard()).len());
-        println!("Last card played: {}", uno.discard().peek_top_card().unwrap().display_name());
-        println!("Color of last card: {:?}", uno.discard().peek_top_card().unwrap().color);
-        println!("Game direction: {:?}", uno.direction());
-        println!("Player {}'s turn", uno.current_player());
-        println!("\nYour cards: {}", uno.current_player().get_hand());
-        println!("Your move?");
-
-        let _ = stdin.read_line(&mut line);
-        line = line.trim().to_string();
-        match line.trim().to_uppercase().as_ref() {
-            "D" => {
-                let card = uno.draw_card();
-                println!("You drew a card: {}", card.display_name());
-                *uno.current_player().get_hand_mut() += card;
-                continue;
+        let from = driver.uno().events().len();
+        let next = driver.run();
+        if !hot_seat {
+            driver.uno().events()[from..].iter().for_each(|event| println!("{}", locale.event(event, &names)));
+        }
+        let seat = match (next, driver.uno().winner()) {
+            (Some(seat), _) => seat,
+            (None, Some(winner)) => {
+                if hot_seat {
+                    println!("{}", locale.message(&Message::PlayerWon(&names[winner])));
+                }
+                break;
+            },
+            (None, None) => break,
+        };
+
+        let uno = driver.uno();
+        let name = uno.players()[seat].name().to_string();
+
+        if hot_seat && last_human != Some(seat) {
+            print!("{}", renderer.clear_screen());
+            println!("{}", locale.message(&Message::PassDevice(&name)));
+            if !wait_for_enter(&lines) {
+                break;
             }
-            "EXIT" => break,
-            _ => {}
+            println!("{}", locale.message(&Message::SinceYourLastTurn));
+            uno.events()[seen[seat]..].iter().for_each(|event| println!("  {}", locale.event(event, &names)));
         }
+        last_human = Some(seat);
 
-        match line.parse::<Card>() {
-            Ok(card) => {
-                println!();
-                match uno.play_card(card) {
-                    TurnResult::Success(c) => {
-                        println!("You played a {}!", c.display_name());
-                    }
-                    TurnResult::InvalidMove(discard, played) => {
-                        println!(
-                            "Invalid move! You cannot play a {} on a {}!",
-                            played.display_name(),
-                            discard.display_name()
-                        );
-                    }
-                    TurnResult::NotHoldingCard(c) => {
-                        println!("You don't have a {}!", c.display_name());
-                    }
-                    TurnResult::GameOver => {
-                        println!("Game over! You won!");
-                        break;
-                    }
+        let view = uno.view(seat);
+        renderer.table(&view, &names).iter().for_each(|line| println!("{}", line));
+        if config.assist {
+            let mut beliefs = Beliefs::new();
+            uno.events().iter().for_each(|event| beliefs.observe(event));
+            let estimate = beliefs.estimate(&view);
+            for other in view.opponents() {
+                let colors = [Color::Red, Color::Green, Color::Blue, Color::Yellow].map(|color| estimate.holds_color(other, color));
+                println!("{}", locale.message(&Message::HoldsOdds { name: uno.players()[other].name(), colors, wild: estimate.holds_wild(other) }));
+            }
+            let colors = [Color::Red, Color::Green, Color::Blue, Color::Yellow].map(|color| estimate.draw_pile_color(color));
+            println!("{}", locale.message(&Message::DrawPileHolds(colors)));
+
+            let mut rollouts = Rollouts::new("greedy");
+            rollouts.games = 200;
+            rollouts.seed = seed;
+            let odds = rollouts.estimate(&view, uno.events());
+            for (other, player) in uno.players().iter().enumerate() {
+                let (low, high) = odds.bounds(other);
+                println!("{}", locale.message(&Message::WinChance { name: player.name(), chance: odds.chance(other), low, high }));
+            }
+        }
+        renderer.prompt(&view, &names).iter().for_each(|line| println!("{}", line));
+        if let Some(left) = driver.time_left() {
+            println!("{}", locale.message(&Message::TimeLeft(left)));
+        }
+        println!("{}", locale.message(&Message::YourMove));
+
+        let line = match driver.time_left().map(|left| lines.recv_timeout(left)) {
+            Some(Ok(line)) => line,
+            Some(Err(RecvTimeoutError::Timeout)) => {
+                println!("\n{}", locale.message(&Message::OutOfTime(&name)));
+                let from = driver.uno().events().len();
+                driver.time_out();
+                driver.uno().events()[from..].iter().for_each(|event| println!("{}", locale.event(event, &names)));
+                seen[seat] = driver.uno().events().len();
+                if hot_seat {
+                    print!("{}", renderer.clear_screen());
+                    last_human = None;
+                }
+                continue;
+            },
+            Some(Err(RecvTimeoutError::Disconnected)) => break,
+            None => match lines.recv() {
+                Ok(line) => line,
+                Err(_) => break,
+            },
+        };
+        let action = match parse_command(&line, &view, &names) {
+            Ok(Some(Command::Act(action))) => action,
+            Ok(Some(Command::CallUno)) => {
+                driver.call_uno();
+                println!("{}", locale.message(&Message::UnoCalled));
+                continue;
+            },
+            Ok(Some(Command::Exit)) => break,
+            Ok(Some(Command::Save(path))) => {
+                match driver.uno().save_to(&path) {
+                    Ok(()) => println!("{}", locale.message(&Message::Saved(&path))),
+                    Err(error) => println!("{}", locale.message(&Message::SaveFailed { path: &path, error: &error })),
+                }
+                continue;
+            },
+            Ok(Some(Command::Load(path))) => {
+                match Uno::load_from(&path) {
+                    Ok(uno) => {
+                        names = uno.players().iter().map(|player| player.name().to_string()).collect();
+                        driver = start(uno);
+                        seen = vec![driver.uno().events().len(); names.len()];
+                        last_human = None;
+                        println!("{}", locale.message(&Message::Loaded(&path)));
+                    },
+                    Err(error) => println!("{}", locale.message(&Message::SaveFailed { path: &path, error: &error })),
                 }
-                println!();
+                continue;
+            },
+            Ok(None) => continue,
+            Err(e) => {
+                println!("{}", locale.message(&Message::CouldNotParse { input: line.trim(), error: &e }));
+                continue;
+            },
+        };
+
+        println!();
+        let from = driver.uno().events().len();
+        let result = driver.apply(action);
+        println!("{}", locale.result(&result, seat, &names));
+        if result == TurnResult::GameOver {
+            break;
+        }
+
+        //the rest of what this move caused; the move itself was reported above
+        let uno = driver.uno();
+        uno.events()[from..].iter()
+            .filter(|event| !event.is_move_by(seat))
+            .for_each(|event| println!("{}", locale.event(event, &names)));
+
+        if uno.current_seat() != seat {
+            seen[seat] = uno.events().len();
+            if hot_seat {
+                println!("\n{}", locale.message(&Message::HideHand));
+                if !wait_for_enter(&lines) {
+                    break;
+                }
+                print!("{}", renderer.clear_screen());
+                last_human = None;
             }
-            Err(e) => println!("Error parsing card from {}: {}", line, e),
         }
     }
 
-    println!("\nThanks for playing! Good bye.");
+    println!("\n{}", locale.message(&Message::Goodbye));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use uno::OnTimeout;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("").unwrap().unwrap();
+        assert_eq!(vec!["Ali", "Bob"], config.names);
+        assert!(!config.time.is_timed());
+        assert_eq!(Rules::classic(), config.rules);
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("-n 3 --names A,B,C -s 9 -r stacking -o uno-penalty=0 --hand-size 5 -c 2,3:basic --assist --turn-time 20 --time-bank 300 --on-timeout auto").unwrap().unwrap();
+        assert_eq!(3, config.names.len());
+        assert_eq!(Some(9), config.seed);
+        assert!(config.rules.stacking);
+        assert_eq!(0, config.rules.uno_penalty);
+        assert_eq!(5, config.rules.hand_size);
+        assert_eq!(vec![None, Some("basic".to_string()), Some("basic".to_string())], config.computer);
+        assert!(config.assist);
+        assert_eq!(Some(Duration::from_secs(20)), config.time.per_turn);
+        assert_eq!(Some(Duration::from_secs(300)), config.time.bank);
+        assert_eq!(OnTimeout::AutoPlay, config.time.on_timeout);
+    }
+
+    #[test]
+    fn bad_combinations() {
+        assert!(parse("-n 3 --names A,B").is_err());
+        assert!(parse("--names A,A").is_err());
+        assert!(parse("-n 9").is_err());
+        assert!(parse("-n 2 -c 3").is_err());
+        assert!(parse("-n 2 -c 2:nobody").is_err());
+        assert!(parse("-n 8 --hand-size 14").is_err());
+        assert!(parse("-n 8 --hand-size 13").is_err());
+        assert!(parse("--hand-size 5 -o hand_size=6").is_err());
+        assert!(parse("-r house").is_err());
+        assert!(parse("-o stacking").is_err());
+        assert!(parse("--seed").is_err());
+        assert!(parse("--turn-time soon").is_err());
+        assert!(parse("--on-timeout wait").is_err());
+        assert!(parse("--frobnicate").is_err());
+    }
 }
@@ -0,0 +1,93 @@
+use uno::Api;
+
+use std::env;
+use std::net::TcpListener;
+use std::process;
+use std::sync::Arc;
+
+const USAGE: &str = "\
+usage: http_server [options]
+
+Serves games over HTTP with JSON bodies, for web and mobile front ends. The
+endpoints are described by Api. Nothing outside the listening address is
+ever contacted.
+
+options:
+  -a, --address <ADDR>    address to listen on (default 127.0.0.1:8080, port 0 picks one)
+  -h, --help              show this help";
+
+struct Config {
+    address: String,
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut address = "127.0.0.1:8080".to_string();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-a" | "--address" => address = value()?,
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    Ok(Some(Config { address }))
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\nrun with --help for usage", e);
+            process::exit(2);
+        },
+    };
+
+    let listener = match TcpListener::bind(&config.address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: could not listen on {}: {}", config.address, e);
+            process::exit(1);
+        },
+    };
+    println!("listening on {}", listener.local_addr().unwrap());
+
+    if let Err(e) = Arc::new(Api::new()).serve(listener) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("").unwrap().unwrap();
+        assert_eq!("127.0.0.1:8080", config.address);
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("--address 0.0.0.0:80").unwrap().unwrap();
+        assert_eq!("0.0.0.0:80", config.address);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse("-a").is_err());
+        assert!(parse("--port 80").is_err());
+    }
+}
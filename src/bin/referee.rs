@@ -0,0 +1,193 @@
+use uno::{strategy, Engine, Entrant, RuleArgs, Rules, Tournament};
+
+use std::env;
+use std::process;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+usage: referee [options] <PLAYER> <PLAYER> [PLAYER...]
+
+Plays engines in other processes against each other and the built-in bots.
+A player is the name of a built-in bot, or the command that runs an engine,
+split on spaces and optionally named with NAME=, e.g. \"mine=./engine --fast\".
+Engines speak the protocol described by RefereeLine and EngineLine, and a
+fresh copy is run for every game.
+
+options:
+  -t, --table <N>         players at each table (default 2)
+  -g, --games <N>         games at each table, rotating the seats (default 100)
+  -s, --seed <N>          seed of the first game, the rest count up (default 0)
+      --movetime <MS>     time an engine has for each move (default 1000)
+      --max-turns <N>     give up on games longer than this (default 10000)
+  -j, --threads <N>       games to play at once (default 1)
+  -h, --help              show this help";
+
+/// Someone taking part, before any engine has been run.
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Player {
+    Builtin(String),
+    Engine { name: Option<String>, command: String },
+}
+
+struct Config {
+    players: Vec<Player>,
+    movetime: Duration,
+    /// Everything but the entrants, which need the engines running to be named.
+    tournament: Tournament,
+}
+
+fn usage() -> String {
+    format!("{}\n\n{}\n\nbots: {}", USAGE, RuleArgs::usage(), strategy::NAMES.join(", "))
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut tournament = Tournament::new(Vec::new(), Rules::default());
+    let mut players = Vec::new();
+    let mut movetime = Duration::from_millis(1000);
+    let mut rule_args = RuleArgs::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        let number = |value: String| value.parse::<usize>().map_err(|_| format!("{} must be a number", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-t" | "--table" => tournament.table_size = number(value()?)?,
+            "-g" | "--games" => tournament.games = number(value()?)?,
+            "-s" | "--seed" => tournament.seed = value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?,
+            flag if RuleArgs::takes(flag) => rule_args.add(flag, value()?),
+            "--movetime" => movetime = Duration::from_millis(number(value()?)? as u64),
+            "--max-turns" => tournament.max_turns = number(value()?)?,
+            "-j" | "--threads" => tournament.threads = number(value()?)?,
+            name if name.starts_with('-') => return Err(format!("unknown argument \"{}\"", name)),
+            name if strategy::by_name(name, 0).is_some() => players.push(Player::Builtin(name.trim().to_lowercase())),
+            command => {
+                let (name, command) = match command.split_once('=') {
+                    Some((name, command)) if !name.contains(char::is_whitespace) => (Some(name.to_string()), command),
+                    _ => (None, command),
+                };
+                if command.trim().is_empty() {
+                    return Err(format!("\"{}\" has no command to run", arg));
+                }
+                players.push(Player::Engine { name, command: command.trim().to_string() });
+            },
+        }
+    }
+
+    tournament.rules = rule_args.rules().map_err(|e| e.to_string())?;
+    if players.len() < tournament.table_size {
+        return Err(format!("{} players cannot fill a table of {}", players.len(), tournament.table_size));
+    }
+    tournament.rules.check(tournament.table_size).map_err(|e| e.to_string())?;
+
+    Ok(Some(Config { players, movetime, tournament }))
+}
+
+/// Turns a player into an entrant, running an engine once to learn its name
+/// and to find out before play begins if it doesn't start. That engine plays
+/// the first game; an engine that stops starting later ends the tournament
+/// with the error, since its games could no longer be scored.
+fn entrant(player: &Player, movetime: Duration) -> Result<Entrant, String> {
+    match player {
+        Player::Builtin(name) => Ok(Entrant::builtin(name).unwrap()),
+        Player::Engine { name, command } => {
+            let engine = Engine::start(command, movetime).map_err(|e| e.to_string())?;
+            let name = name.clone().unwrap_or_else(|| engine.name().to_string());
+            let command = command.clone();
+            let first = Mutex::new(Some(engine));
+            Ok(Entrant::new(name, move |_| {
+                if let Some(engine) = first.lock().unwrap().take() {
+                    return Box::new(engine);
+                }
+                match Engine::start(&command, movetime) {
+                    Ok(engine) => Box::new(engine),
+                    Err(e) => {
+                        eprintln!("referee: {}", e);
+                        process::exit(1);
+                    },
+                }
+            }))
+        },
+    }
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("referee: {}\n\n{}", e, usage());
+            process::exit(2);
+        },
+    };
+
+    let mut tournament = config.tournament;
+    for player in &config.players {
+        match entrant(player, config.movetime) {
+            Ok(entrant) => tournament.entrants.push(entrant),
+            Err(e) => {
+                eprintln!("referee: {}", e);
+                process::exit(1);
+            },
+        }
+    }
+    if let Err(e) = tournament.check() {
+        eprintln!("referee: {}", e);
+        process::exit(2);
+    }
+
+    println!("round robin for {} players, {} a table, {} games a table\n", tournament.entrants.len(), tournament.table_size, tournament.games);
+
+    let start = Instant::now();
+    let standings = tournament.run();
+    print!("{}", standings);
+    println!("\n{} games in {:.2?}", standings.records.len(), start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Config>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(&["basic", "./engine"]).unwrap().unwrap();
+        assert_eq!(vec![Player::Builtin("basic".into()), Player::Engine { name: None, command: "./engine".into() }], config.players);
+        assert_eq!(Duration::from_millis(1000), config.movetime);
+        assert_eq!(100, config.tournament.games);
+        assert!(parse(&["--help"]).unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse(&["-t", "3", "-g", "20", "-s", "4", "-r", "stacking", "--movetime", "50", "Greedy", "fast=./engine --fast", "./engine -x=1"]).unwrap().unwrap();
+        assert_eq!(3, config.tournament.table_size);
+        assert_eq!(20, config.tournament.games);
+        assert!(config.tournament.rules.stacking);
+        assert_eq!(Duration::from_millis(50), config.movetime);
+        assert_eq!(
+            vec![
+                Player::Builtin("greedy".into()),
+                Player::Engine { name: Some("fast".into()), command: "./engine --fast".into() },
+                Player::Engine { name: None, command: "./engine -x=1".into() },
+            ],
+            config.players
+        );
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse(&["basic"]).is_err());
+        assert!(parse(&["-t", "3", "basic", "greedy"]).is_err());
+        assert!(parse(&["basic", "name="]).is_err());
+        assert!(parse(&["--movetime", "soon", "basic", "greedy"]).is_err());
+        assert!(parse(&["--bogus", "basic", "greedy"]).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use uno::{decide_from_view, strategy, EngineLine, PlayerView, RefereeLine, Strategy};
+
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "\
+usage: sample_engine [options]
+
+A minimal engine for the referee, to copy from when writing one in another
+language. It reads the lines described by RefereeLine on standard input and
+answers with those of EngineLine on standard output, letting a built-in
+strategy choose the moves.
+
+options:
+      --bot <NAME>        strategy that picks the moves (default basic)
+      --name <NAME>       name to give in the handshake (default sample-<bot>)
+  -s, --seed <N>          seed for randomised strategies (default 0)
+      --think <MS>        wait this long before every move, to try out time limits
+  -h, --help              show this help";
+
+struct Config {
+    bot: String,
+    name: String,
+    seed: u64,
+    think: Duration,
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut bot = "basic".to_string();
+    let mut name = None;
+    let mut seed = 0;
+    let mut think = Duration::from_millis(0);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "--bot" => bot = value()?,
+            "--name" => name = Some(value()?),
+            "-s" | "--seed" => seed = value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?,
+            "--think" => think = Duration::from_millis(value()?.parse::<u64>().map_err(|_| "--think must be a whole number".to_string())?),
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    if strategy::by_name(&bot, 0).is_none() {
+        return Err(format!("unknown bot \"{}\" (expected one of {})", bot, strategy::NAMES.join(", ")));
+    }
+    let name = name.unwrap_or_else(|| format!("sample-{}", bot));
+
+    Ok(Some(Config { bot, name, seed, think }))
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\nrun with --help for usage", e);
+            process::exit(2);
+        },
+    };
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let mut send = |line: EngineLine| {
+        //the referee has gone if this fails, and there is nobody left to play for
+        if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+            process::exit(0);
+        }
+    };
+
+    let mut bot: Box<dyn Strategy> = strategy::by_name(&config.bot, config.seed).unwrap();
+    let mut view: Option<PlayerView> = None;
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        match line.parse::<RefereeLine>() {
+            Ok(RefereeLine::Uno) => {
+                send(EngineLine::Id { key: "name".into(), value: config.name.clone() });
+                send(EngineLine::Id { key: "author".into(), value: "the uno-rs authors".into() });
+                send(EngineLine::UnoOk);
+            },
+            Ok(RefereeLine::IsReady) => send(EngineLine::ReadyOk),
+            Ok(RefereeLine::NewGame) => {
+                bot = strategy::by_name(&config.bot, config.seed).unwrap();
+                view = None;
+            },
+            Ok(RefereeLine::Event(event)) => bot.observe(&event),
+            Ok(RefereeLine::View(latest)) => view = Some(latest),
+            Ok(RefereeLine::Go { actions, .. }) => {
+                thread::sleep(config.think);
+                let (action, uno) = match &view {
+                    Some(view) => decide_from_view(bot.as_mut(), view, actions),
+                    //a referee should always send the view first, but any move beats none
+                    None => (actions[actions.len() - 1], false),
+                };
+                send(EngineLine::BestMove { action, uno });
+            },
+            Ok(RefereeLine::GameOver { .. }) => {},
+            Ok(RefereeLine::Quit) => break,
+            Err(e) => send(EngineLine::Info(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("").unwrap().unwrap();
+        assert_eq!("basic", config.bot);
+        assert_eq!("sample-basic", config.name);
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("--bot greedy --name mine -s 4 --think 20").unwrap().unwrap();
+        assert_eq!("greedy", config.bot);
+        assert_eq!("mine", config.name);
+        assert_eq!(4, config.seed);
+        assert_eq!(Duration::from_millis(20), config.think);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse("--bot nobody").is_err());
+        assert!(parse("--think soon").is_err());
+        assert!(parse("--frobnicate").is_err());
+    }
+}
@@ -0,0 +1,512 @@
+use uno::{new_token, parse_seconds, strategy, ClientLine, Driver, Player, RuleArgs, Rules, Seat, ServerLine, SystemClock, TimeControl, TimeError, Timers, Uno};
+
+use std::collections::HashSet;
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::process;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a new connection has to say JOIN or RESUME before it is dropped.
+const HANDSHAKE: Duration = Duration::from_secs(5);
+
+const USAGE: &str = "\
+usage: server [options]
+
+Hosts a game over TCP. Every seat not played by the computer waits for a
+client to connect and send JOIN <name>; the game starts once all are in.
+A client that loses its connection, or sends nothing for longer than the
+timeout, can take its seat back by sending RESUME with the token it was
+given; if it is away for longer than the grace period, a bot plays for it
+until it does.
+
+options:
+  -a, --address <ADDR>    address to listen on (default 127.0.0.1:7777, port 0 picks one)
+  -n, --players <N>       number of players, 2-8 (default 2)
+  -s, --seed <N>          seed for shuffling (default: from the clock)
+  -c, --computer <SEATS>  seats played by the computer, e.g. 2,3 or 2:basic,3
+      --bot <NAME>        strategy for computer seats without one, and for
+                          players who leave (default basic)
+      --grace <MS>        how long to wait for a lost client before a bot
+                          plays for it (default 30000)
+      --timeout <S>       seconds a client may send nothing before it is taken
+                          to be gone (default 30)
+      --turn-time <S>     seconds each client has for a turn
+      --time-bank <S>     seconds each client has for the whole game
+      --increment <S>     seconds added to a client's time bank after every turn
+      --on-timeout <WHAT> what is done for a client out of time: draw (draw
+                          and pass) or auto (the basic bot moves) (default draw)
+  -h, --help              show this help";
+
+struct Config {
+    address: String,
+    players: usize,
+    seed: Option<u64>,
+    rules: Rules,
+    /// The strategy playing each seat, or `None` for a client.
+    computer: Vec<Option<String>>,
+    bot: String,
+    grace: Duration,
+    /// How long a client can go without sending anything.
+    timeout: Duration,
+    time: TimeControl,
+}
+
+fn usage() -> String {
+    format!("{}\n\n{}", USAGE, RuleArgs::usage())
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut address = "127.0.0.1:7777".to_string();
+    let mut players = 2;
+    let mut seed = None;
+    let mut rule_args = RuleArgs::default();
+    let mut seats = Vec::new();
+    let mut bot = "basic".to_string();
+    let mut grace = Duration::from_millis(30000);
+    let mut timeout = Duration::from_secs(30);
+    let mut time = TimeControl::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-a" | "--address" => address = value()?,
+            "-n" | "--players" => players = value()?.parse::<usize>().map_err(|_| "--players must be a number".to_string())?,
+            "-s" | "--seed" => seed = Some(value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?),
+            flag if RuleArgs::takes(flag) => rule_args.add(flag, value()?),
+            "-c" | "--computer" => {
+                for seat in value()?.split(',') {
+                    let mut split = seat.splitn(2, ':');
+                    let number = split.next().unwrap().trim();
+                    let number = number.parse::<usize>().map_err(|_| format!("\"{}\" is not a seat number", number))?;
+                    seats.push((number, split.next().map(|name| name.trim().to_string())));
+                }
+            },
+            "--bot" => bot = value()?,
+            "--grace" => grace = Duration::from_millis(value()?.parse::<u64>().map_err(|_| "--grace must be a whole number".to_string())?),
+            "--timeout" => timeout = parse_seconds(&value()?).map_err(|e| e.to_string())?,
+            "--turn-time" => time.per_turn = Some(parse_seconds(&value()?).map_err(|e| e.to_string())?),
+            "--time-bank" => time.bank = Some(parse_seconds(&value()?).map_err(|e| e.to_string())?),
+            "--increment" => time.increment = parse_seconds(&value()?).map_err(|e| e.to_string())?,
+            "--on-timeout" => time.on_timeout = value()?.parse().map_err(|e: TimeError| e.to_string())?,
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    if players < 2 || players > Player::NAMES.len() {
+        return Err(format!("a game needs 2 to 8 players, not {}", players));
+    }
+
+    let rules = rule_args.rules().map_err(|e| e.to_string())?;
+    rules.check(players).map_err(|e| e.to_string())?;
+
+    if strategy::by_name(&bot, 0).is_none() {
+        return Err(format!("unknown bot \"{}\" (expected one of {})", bot, strategy::NAMES.join(", ")));
+    }
+    let mut computer = vec![None; players];
+    for (seat, name) in seats {
+        if seat == 0 || seat > players {
+            return Err(format!("seat {} does not exist, seats are numbered 1 to {}", seat, players));
+        }
+        let name = name.unwrap_or_else(|| bot.clone());
+        if strategy::by_name(&name, 0).is_none() {
+            return Err(format!("unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")));
+        }
+        computer[seat - 1] = Some(name);
+    }
+
+    //a read timeout of zero means none at all
+    if timeout.is_zero() {
+        return Err("--timeout must be more than 0".into());
+    }
+
+    Ok(Some(Config { address, players, seed, rules, computer, bot, grace, timeout, time }))
+}
+
+/// The connections to the clients, by seat. A seat whose client has gone is `None`.
+struct Clients(Vec<Option<TcpStream>>);
+
+impl Clients {
+    /// Sends a line to one seat, forgetting the client if it can't be reached.
+    fn send(&mut self, seat: usize, line: &ServerLine) {
+        if let Some(stream) = &mut self.0[seat] {
+            if writeln!(stream, "{}", line).is_err() {
+                self.0[seat] = None;
+            }
+        }
+    }
+
+    fn broadcast(&mut self, line: &ServerLine) {
+        (0..self.0.len()).for_each(|seat| self.send(seat, line));
+    }
+}
+
+/// What the game loop hears about.
+enum Message {
+    /// A line from a seat's client, `None` once its connection closes.
+    /// `connection` tells a client that came back from its old connection.
+    Line { seat: usize, connection: usize, line: Option<String> },
+    /// Someone connected once the game was under way and sent `RESUME`.
+    Resume { token: String, seen: Option<usize>, stream: TcpStream },
+}
+
+/// A seat played by a client, and how to get it back after losing the connection.
+struct Session {
+    token: String,
+    connection: usize,
+    /// When the client's connection closed, while waiting for it to come back.
+    away: Option<Instant>,
+    /// Set once a bot plays the seat, until the client comes back.
+    stand_in: bool,
+    /// How many events had been sent when the connection was lost.
+    seen: usize,
+    quit: bool,
+}
+
+/// Passes the lines a client sends to the game loop, on a thread of its own.
+fn forward(reader: BufReader<TcpStream>, seat: usize, connection: usize, messages: Sender<Message>) {
+    thread::spawn(move || {
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    if messages.send(Message::Line { seat, connection, line: Some(line) }).is_err() {
+                        return;
+                    }
+                },
+                Err(_) => break,
+            }
+        }
+        let _ = messages.send(Message::Line { seat, connection, line: None });
+    });
+}
+
+/// Takes the next connection, split into its reading and writing halves.
+/// Reading fails once the client has sent nothing for `HANDSHAKE`, and for
+/// the client timeout once it has said who it is, which is how a peer that
+/// vanished without closing the connection is noticed.
+fn accept(listener: &TcpListener) -> Option<(BufReader<TcpStream>, TcpStream)> {
+    let stream = match listener.accept() {
+        //lines are small and each one matters now, so don't wait to batch them
+        Ok((stream, _)) => stream.set_nodelay(true).and_then(|_| stream.set_read_timeout(Some(HANDSHAKE))).ok().map(|_| stream)?,
+        Err(e) => {
+            eprintln!("error: could not accept a connection: {}", e);
+            return None;
+        },
+    };
+    let writer = stream.try_clone().ok()?;
+    Some((BufReader::new(stream), writer))
+}
+
+/// Waits for a client to connect and send a `JOIN` with a name nobody has
+/// taken, then hands what it sends afterwards to `messages`.
+fn seat_client(listener: &TcpListener, timeout: Duration, seat: usize, taken: &HashSet<String>, messages: Sender<Message>) -> (String, TcpStream) {
+    loop {
+        let (mut reader, mut writer) = match accept(listener) {
+            Some(connection) => connection,
+            None => continue,
+        };
+
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {
+            let error = match line.parse::<ClientLine>() {
+                Ok(ClientLine::Join(name)) if !taken.contains(&name) => {
+                    if reader.get_ref().set_read_timeout(Some(timeout)).is_err() {
+                        break;
+                    }
+                    forward(reader, seat, 0, messages);
+                    return (name, writer);
+                },
+                Ok(ClientLine::Join(name)) => format!("{} is already taken", name),
+                Ok(ClientLine::Resume { .. }) => "the game has not started yet".to_string(),
+                Ok(_) => "send JOIN <name> first".to_string(),
+                Err(e) => e.to_string(),
+            };
+            if writeln!(writer, "{}", ServerLine::Error(error)).is_err() {
+                break;
+            }
+            line.clear();
+        }
+    }
+}
+
+/// Once the game is under way, passes on every client that connects and
+/// sends `RESUME`, and turns away the rest.
+fn await_returns(listener: TcpListener, timeout: Duration, messages: Sender<Message>) {
+    thread::spawn(move || loop {
+        let (mut reader, mut writer) = match accept(&listener) {
+            Some(connection) => connection,
+            None => continue,
+        };
+        let messages = messages.clone();
+        //a client that says nothing mustn't keep the others waiting
+        thread::spawn(move || {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+            match line.parse::<ClientLine>() {
+                Ok(ClientLine::Resume { token, seen }) if reader.get_ref().set_read_timeout(Some(timeout)).is_ok() => {
+                    let _ = messages.send(Message::Resume { token, seen, stream: reader.into_inner() });
+                },
+                Ok(ClientLine::Resume { .. }) => {},
+                _ => {
+                    let _ = writeln!(writer, "{}", ServerLine::Error("the game has started, send RESUME <token> to come back".into()));
+                },
+            }
+        });
+    });
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\nrun with --help for usage", e);
+            process::exit(2);
+        },
+    };
+
+    let listener = match TcpListener::bind(&config.address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("error: could not listen on {}: {}", config.address, e);
+            process::exit(1);
+        },
+    };
+    println!("listening on {}", listener.local_addr().unwrap());
+
+    let seed = config.seed.unwrap_or_else(|| shuffle::Xorshift::from_time().next_u64());
+    let (sender, receiver) = mpsc::channel();
+    let mut names: Vec<String> = Player::NAMES[..config.players].iter().map(|&name| name.to_string()).collect();
+    let mut clients = Clients((0..config.players).map(|_| None).collect());
+    let mut sessions: Vec<Option<Session>> = (0..config.players).map(|_| None).collect();
+    let mut taken: HashSet<String> = config.computer.iter().zip(&names).filter(|(bot, _)| bot.is_some()).map(|(_, name)| name.clone()).collect();
+    for seat in (0..config.players).filter(|&seat| config.computer[seat].is_none()) {
+        let (name, stream) = seat_client(&listener, config.timeout, seat, &taken, sender.clone());
+        println!("{} joined in seat {}", name, seat + 1);
+        taken.insert(name.clone());
+        names[seat] = name;
+        clients.0[seat] = Some(stream);
+        sessions[seat] = Some(Session { token: new_token(), connection: 0, away: None, stand_in: false, seen: 0, quit: false });
+    }
+    await_returns(listener, config.timeout, sender.clone());
+
+    let players: Vec<Player> = names.iter().map(|name| name.as_str().into()).collect();
+    let uno = Uno::create_game_with(players, config.rules.clone(), seed);
+    let seats = config.computer.iter().enumerate().map(|(i, bot)| match bot {
+        Some(name) => Seat::Computer(strategy::by_name(name, seed.wrapping_add(i as u64)).unwrap()),
+        None => Seat::Human,
+    }).collect();
+    let mut driver = Driver::new(uno, seats);
+    if config.time.is_timed() {
+        driver.set_timers(Timers::new(config.time, config.players, Box::new(SystemClock)));
+    }
+    let stand_in = |seat: usize| Seat::Computer(strategy::by_name(&config.bot, seed.wrapping_add(seat as u64)).unwrap());
+
+    for (seat, session) in sessions.iter().enumerate() {
+        clients.send(seat, &ServerLine::Welcome { seat, names: names.clone() });
+        if let Some(session) = session {
+            clients.send(seat, &ServerLine::Session(session.token.clone()));
+        }
+    }
+
+    //events already sent to the clients
+    let mut sent = 0;
+    let mut changed = true;
+    loop {
+        if changed {
+            let next = driver.run();
+            let uno = driver.uno();
+            uno.events()[sent..].iter().for_each(|&event| clients.broadcast(&ServerLine::Event(event)));
+            sent = uno.events().len();
+            for seat in 0..config.players {
+                clients.send(seat, &ServerLine::View(uno.view(seat)));
+            }
+            match next {
+                Some(seat) => clients.send(seat, &ServerLine::YourMove(uno.legal_actions())),
+                None => break,
+            }
+        }
+
+        //the first seat whose client has been away too long, or whose time runs out
+        let away = sessions.iter().flatten().filter(|session| !session.stand_in).filter_map(|session| session.away).min().map(|away| away + config.grace);
+        let deadline = away.into_iter().chain(driver.time_left().map(|left| Instant::now() + left)).min();
+        let message = match deadline {
+            Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())).ok(),
+            None => receiver.recv().ok(),
+        };
+
+        let (seat, connection, line) = match message {
+            //the grace period of one or more clients is up, or the current one's time
+            None => {
+                let current = driver.uno().current_seat();
+                let results = driver.time_out();
+                if !results.is_empty() {
+                    println!("{} ran out of time", names[current]);
+                    results.into_iter().for_each(|result| clients.send(current, &ServerLine::Result(result)));
+                }
+                for (seat, session) in sessions.iter_mut().enumerate() {
+                    if let Some(session) = session.as_mut().filter(|session| !session.stand_in && session.away.is_some_and(|away| away + config.grace <= Instant::now())) {
+                        println!("{} is still away, {} plays for them", names[seat], config.bot);
+                        session.stand_in = true;
+                        driver.set_seat(seat, stand_in(seat));
+                    }
+                }
+                changed = true;
+                continue;
+            },
+            Some(Message::Resume { token, seen, stream }) => {
+                let seat = sessions.iter().position(|session| session.as_ref().is_some_and(|session| !session.quit && session.token == token));
+                let (seat, reader) = match (seat, stream.try_clone()) {
+                    (Some(seat), Ok(reader)) => (seat, reader),
+                    (_, _) => {
+                        let _ = writeln!(&stream, "{}", ServerLine::Error("there is no seat to come back to for that token".into()));
+                        changed = false;
+                        continue;
+                    },
+                };
+                let session = sessions[seat].as_mut().unwrap();
+                session.connection += 1;
+                session.away = None;
+                if session.stand_in {
+                    session.stand_in = false;
+                    driver.set_seat(seat, Seat::Human);
+                }
+                //the old connection may not know it is dead yet
+                if let Some(old) = clients.0[seat].replace(stream) {
+                    let _ = old.shutdown(Shutdown::Both);
+                }
+                forward(BufReader::new(reader), seat, session.connection, sender.clone());
+                println!("{} is back in seat {}", names[seat], seat + 1);
+
+                //only the client that came back hears about it, so nobody else is asked to move twice
+                let uno = driver.uno();
+                let missed = seen.unwrap_or(session.seen).min(uno.events().len());
+                clients.send(seat, &ServerLine::Welcome { seat, names: names.clone() });
+                clients.send(seat, &ServerLine::Session(token));
+                uno.events()[missed..].iter().for_each(|&event| clients.send(seat, &ServerLine::Event(event)));
+                clients.send(seat, &ServerLine::View(uno.view(seat)));
+                if uno.current_seat() == seat {
+                    clients.send(seat, &ServerLine::YourMove(uno.legal_actions()));
+                }
+                changed = false;
+                continue;
+            },
+            Some(Message::Line { seat, connection, line }) => (seat, connection, line),
+        };
+        let session = match sessions[seat].as_mut() {
+            Some(session) if session.connection == connection => session,
+            //left over from a connection that has since been replaced
+            _ => {
+                changed = false;
+                continue;
+            },
+        };
+
+        let current = driver.uno().current_seat();
+        changed = match line.as_deref().map(str::parse::<ClientLine>) {
+            Some(Ok(ClientLine::Act(action))) if seat == current && driver.is_human(seat) => {
+                let result = driver.apply(action);
+                clients.send(seat, &ServerLine::Result(result));
+                true
+            },
+            Some(Ok(ClientLine::Uno)) if seat == current && driver.is_human(seat) => {
+                driver.call_uno();
+                false
+            },
+            Some(Ok(ClientLine::Act(_))) | Some(Ok(ClientLine::Uno)) => {
+                clients.send(seat, &ServerLine::Error("it is not your turn".into()));
+                false
+            },
+            Some(Ok(ClientLine::Ping)) => false,
+            Some(Ok(ClientLine::Join(_))) | Some(Ok(ClientLine::Resume { .. })) => {
+                clients.send(seat, &ServerLine::Error("you have already joined".into()));
+                false
+            },
+            Some(Err(e)) => {
+                clients.send(seat, &ServerLine::Error(e.to_string()));
+                false
+            },
+            Some(Ok(ClientLine::Quit)) if !session.quit => {
+                println!("{} left, {} plays for them", names[seat], config.bot);
+                clients.send(seat, &ServerLine::End);
+                clients.0[seat] = None;
+                session.quit = true;
+                session.away = None;
+                if !session.stand_in {
+                    session.stand_in = true;
+                    driver.set_seat(seat, stand_in(seat));
+                }
+                true
+            },
+            None if !session.quit && session.away.is_none() => {
+                println!("{} lost the connection, waiting {:?} for them to come back", names[seat], config.grace);
+                clients.0[seat] = None;
+                session.away = Some(Instant::now());
+                session.seen = sent;
+                false
+            },
+            Some(Ok(ClientLine::Quit)) | None => false,
+        };
+    }
+
+    match driver.uno().winner() {
+        Some(winner) => println!("{} won", names[winner]),
+        None => println!("the game ended without a winner"),
+    }
+    clients.broadcast(&ServerLine::End);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("").unwrap().unwrap();
+        assert_eq!("127.0.0.1:7777", config.address);
+        assert_eq!(vec![None, None], config.computer);
+        assert_eq!(Duration::from_secs(30), config.grace);
+        assert_eq!(Duration::from_secs(30), config.timeout);
+        assert!(!config.time.is_timed());
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("-a 0.0.0.0:9000 -n 4 -s 9 -r stacking --hand-size 5 -c 2,3:greedy --bot expert --grace 500 --timeout 5 --turn-time 0.5 --on-timeout auto").unwrap().unwrap();
+        assert_eq!("0.0.0.0:9000", config.address);
+        assert_eq!(Some(9), config.seed);
+        assert!(config.rules.stacking);
+        assert_eq!(5, config.rules.hand_size);
+        assert_eq!(vec![None, Some("expert".to_string()), Some("greedy".to_string()), None], config.computer);
+        assert_eq!(Duration::from_millis(500), config.grace);
+        assert_eq!(Duration::from_secs(5), config.timeout);
+        assert_eq!(Some(Duration::from_millis(500)), config.time.per_turn);
+    }
+
+    #[test]
+    fn bad_combinations() {
+        assert!(parse("-n 1").is_err());
+        assert!(parse("-n 9").is_err());
+        assert!(parse("-n 2 -c 3").is_err());
+        assert!(parse("--bot nobody").is_err());
+        assert!(parse("-n 8 --hand-size 14").is_err());
+        assert!(parse("--address").is_err());
+        assert!(parse("--grace soon").is_err());
+        assert!(parse("--timeout 0").is_err());
+        assert!(parse("--time-bank -3").is_err());
+    }
+}
@@ -0,0 +1,132 @@
+use uno::{strategy, Batch, GameRecord, RuleArgs, Rules, Summary};
+
+use std::env;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage: simulate [options]
+
+options:
+  -g, --games <N>         number of games to play (default 1000)
+  -b, --bots <A,B,...>    the strategy in each seat (default basic,basic)
+  -s, --seed <N>          seed of the first game, the rest count up (default 0)
+      --rotate            move the bots one seat along after every game
+      --max-turns <N>     give up on games longer than this (default 10000)
+  -j, --threads <N>       games to play at once (default 1)
+  -v, --verbose           print a line for every game
+  -h, --help              show this help";
+
+struct Config {
+    batch: Batch,
+    verbose: bool,
+}
+
+fn usage() -> String {
+    format!("{}\n\n{}\n\nbots: {}", USAGE, RuleArgs::usage(), strategy::NAMES.join(", "))
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Config>, String> {
+    let mut batch = Batch::new(vec!["basic".into(), "basic".into()], Rules::default());
+    let mut rule_args = RuleArgs::default();
+    let mut verbose = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        let number = |value: String| value.parse::<usize>().map_err(|_| format!("{} must be a number", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-g" | "--games" => batch.games = number(value()?)?,
+            "-b" | "--bots" => batch.bots = value()?.split(',').map(|name| name.trim().to_lowercase()).collect(),
+            "-s" | "--seed" => batch.seed = value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?,
+            flag if RuleArgs::takes(flag) => rule_args.add(flag, value()?),
+            "--rotate" => batch.rotate = true,
+            "--max-turns" => batch.max_turns = number(value()?)?,
+            "-j" | "--threads" => batch.threads = number(value()?)?,
+            "-v" | "--verbose" => verbose = true,
+            _ => return Err(format!("unknown argument \"{}\"", arg)),
+        }
+    }
+
+    batch.rules = rule_args.rules().map_err(|e| e.to_string())?;
+    batch.check().map_err(|e| e.to_string())?;
+
+    Ok(Some(Config { batch, verbose }))
+}
+
+fn describe(record: &GameRecord) -> String {
+    let winner = match record.winner {
+        Some(seat) => format!("seat {} ({})", seat + 1, record.bots[seat]),
+        None => "nobody".into(),
+    };
+    format!("seed {:<8} {:>5} turns  {:>3} drawn  {} reshuffles  won by {}", record.seed, record.turns, record.drawn.iter().sum::<usize>(), record.reshuffles, winner)
+}
+
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("simulate: {}\n\n{}", e, usage());
+            process::exit(2);
+        },
+    };
+
+    let batch = &config.batch;
+    let seating = if batch.rotate { ", rotating seats" } else { "" };
+    println!("{} games between {}, seeds {} and up{}\n", batch.games, batch.bots.join(", "), batch.seed, seating);
+
+    let start = Instant::now();
+    let records = batch.run();
+    if config.verbose {
+        records.iter().for_each(|record| println!("{}", describe(record)));
+        println!();
+    }
+
+    print!("{}", Summary::new(&records));
+    println!("\nfinished in {:.2?}", start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Config>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse("").unwrap().unwrap();
+        assert_eq!(1000, config.batch.games);
+        assert_eq!(vec!["basic", "basic"], config.batch.bots);
+        assert!(parse("-h").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let config = parse("-g 50 -b expert,Greedy,random -s 7 -r seven-o -o uno-penalty=4 --rotate -j 3 -v").unwrap().unwrap();
+        assert_eq!(50, config.batch.games);
+        assert_eq!(vec!["expert", "greedy", "random"], config.batch.bots);
+        assert_eq!(7, config.batch.seed);
+        assert!(config.batch.rules.seven_o);
+        assert_eq!(4, config.batch.rules.uno_penalty);
+        assert!(config.batch.rotate);
+        assert_eq!(3, config.batch.threads);
+        assert!(config.verbose);
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse("-b basic").is_err());
+        assert!(parse("-b basic,nobody").is_err());
+        assert!(parse("-g lots").is_err());
+        assert!(parse("-r house").is_err());
+        assert!(parse("--hand-size 60 -b basic,basic,basic").is_err());
+        assert!(parse("--bogus").is_err());
+    }
+}
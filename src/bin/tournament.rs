@@ -0,0 +1,128 @@
+use uno::{strategy, Entrant, Format, RuleArgs, Rules, Tournament};
+
+use std::env;
+use std::process;
+use std::time::Instant;
+
+const USAGE: &str = "\
+usage: tournament [options] <BOT> <BOT> [BOT...]
+
+options:
+  -f, --format <FORMAT>   round-robin or swiss (default round-robin)
+      --rounds <N>        rounds of a Swiss tournament (default 5)
+  -t, --table <N>         players at each table (default 2)
+  -g, --games <N>         games at each table, rotating the seats (default 100)
+  -s, --seed <N>          seed of the first game, the rest count up (default 0)
+      --max-turns <N>     give up on games longer than this (default 10000)
+  -j, --threads <N>       games to play at once (default 1)
+  -h, --help              show this help";
+
+fn usage() -> String {
+    format!("{}\n\n{}\n\nbots: {}", USAGE, RuleArgs::usage(), strategy::NAMES.join(", "))
+}
+
+/// Reads the command line. `Ok(None)` means help was asked for.
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Tournament>, String> {
+    let mut tournament = Tournament::new(Vec::new(), Rules::default());
+    let mut swiss = false;
+    let mut rounds = 5;
+    let mut rule_args = RuleArgs::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        let number = |value: String| value.parse::<usize>().map_err(|_| format!("{} must be a number", arg));
+        match arg.as_ref() {
+            "-h" | "--help" => return Ok(None),
+            "-f" | "--format" => swiss = match value()?.to_lowercase().as_ref() {
+                "round-robin" | "roundrobin" | "rr" => false,
+                "swiss" => true,
+                format => return Err(format!("unknown format \"{}\" (expected round-robin or swiss)", format)),
+            },
+            "--rounds" => rounds = number(value()?)?,
+            "-t" | "--table" => tournament.table_size = number(value()?)?,
+            "-g" | "--games" => tournament.games = number(value()?)?,
+            "-s" | "--seed" => tournament.seed = value()?.parse::<u64>().map_err(|_| "--seed must be a whole number".to_string())?,
+            flag if RuleArgs::takes(flag) => rule_args.add(flag, value()?),
+            "--max-turns" => tournament.max_turns = number(value()?)?,
+            "-j" | "--threads" => tournament.threads = number(value()?)?,
+            name if name.starts_with('-') => return Err(format!("unknown argument \"{}\"", name)),
+            name => {
+                let entrant = Entrant::builtin(name)
+                    .ok_or_else(|| format!("unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")))?;
+                tournament.entrants.push(entrant);
+            },
+        }
+    }
+
+    if swiss {
+        tournament.format = Format::Swiss { rounds };
+    }
+    tournament.rules = rule_args.rules().map_err(|e| e.to_string())?;
+    tournament.check().map_err(|e| e.to_string())?;
+
+    Ok(Some(tournament))
+}
+
+fn main() {
+    let tournament = match parse_args(env::args().skip(1)) {
+        Ok(Some(tournament)) => tournament,
+        Ok(None) => {
+            println!("{}", usage());
+            return;
+        },
+        Err(e) => {
+            eprintln!("tournament: {}\n\n{}", e, usage());
+            process::exit(2);
+        },
+    };
+
+    let format = match tournament.format {
+        Format::RoundRobin => "round robin".to_string(),
+        Format::Swiss { rounds } => format!("{} round Swiss", rounds),
+    };
+    println!("{} for {} entrants, {} players a table, {} games a table\n", format, tournament.entrants.len(), tournament.table_size, tournament.games);
+
+    let start = Instant::now();
+    let standings = tournament.run();
+    print!("{}", standings);
+    println!("\n{} games in {:.2?}", standings.records.len(), start.elapsed());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Tournament>, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn defaults() {
+        let tournament = parse("basic greedy").unwrap().unwrap();
+        assert_eq!(Format::RoundRobin, tournament.format);
+        assert_eq!(2, tournament.table_size);
+        assert_eq!(100, tournament.games);
+        assert!(parse("--help").unwrap().is_none());
+    }
+
+    #[test]
+    fn full_configuration() {
+        let tournament = parse("-f swiss --rounds 3 -t 3 -g 20 -s 4 -r stacking -j 2 basic greedy random expert").unwrap().unwrap();
+        assert_eq!(Format::Swiss { rounds: 3 }, tournament.format);
+        assert_eq!(3, tournament.table_size);
+        assert_eq!(20, tournament.games);
+        assert_eq!(4, tournament.seed);
+        assert!(tournament.rules.stacking);
+        assert_eq!(4, tournament.entrants.len());
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert!(parse("basic").is_err());
+        assert!(parse("basic basic").is_err());
+        assert!(parse("basic nobody").is_err());
+        assert!(parse("-t 3 basic greedy").is_err());
+        assert!(parse("-f knockout basic greedy").is_err());
+        assert!(parse("--bogus basic greedy").is_err());
+    }
+}
@@ -1,11 +1,16 @@
 pub mod color;
 pub mod face;
+mod parse;
 
 use color::*;
 use face::Face;
+use crate::locale::Locale;
+pub use parse::{ParseCardError, ParseCardErrorKind};
 
-use std::fmt::{self, Display};
-use std::str::FromStr;
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{self, Display};
+use core::str::FromStr;
 
 #[derive(Copy, Clone, Eq, Debug)]
 pub struct Card {
@@ -27,14 +32,17 @@ impl Card {
         Card { color, face }
     }
 
+    /// The English name of this card. See `Locale::card_name` for other languages.
     pub fn display_name(&self) -> String {
+        Locale::English.card_name(*self)
+    }
+
+    /// The canonical long form of this card, e.g. "red seven" or
+    /// "wild draw four green". Parsing the result gives back the same card.
+    pub fn long_name(&self) -> String {
         match self.face {
-            Face::DrawFour => "Draw Four".into(),
-            Face::ColorCard => "Wild Card".into(),
-            Face::DrawTwo => format!("{:?} Draw Two", self.color),
-            Face::Skip => format!("{:?} Skip", self.color),
-            Face::Reverse => format!("{:?} Reverse", self.color),
-            _ => format!("{:?} {:?}", self.color, self.face),
+            Face::DrawFour | Face::ColorCard => format!("{} {}", self.face.long_name(), self.color.long_name()),
+            _ => format!("{} {}", self.color.long_name(), self.face.long_name()),
         }
     }
 
@@ -48,20 +56,12 @@ impl Card {
 }
 
 impl FromStr for Card {
-    type Err = &'static str;
+    type Err = ParseCardError;
 
+    /// Reads a card in short ("Y0", "RC") or long ("red seven",
+    /// "wild draw four green", "+2 yellow") form, ignoring case.
     fn from_str(s: &str) -> Result<Card, Self::Err> {
-        let color = s.chars().nth(0)
-            .map(|x| x.to_string())
-            .and_then(|col| col.parse().ok())
-            .ok_or("Could not parse color.")?;
-
-        let face = s.chars().nth(1)
-            .map(Face::from)
-            .flatten()
-            .ok_or("Invalid face identifier")?;
-
-        Ok(Card::new(color, face))
+        parse::parse_card(s)
     }
 }
 
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use core::str::FromStr;
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum Color {
@@ -12,19 +12,25 @@ impl FromStr for Color {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Color, Self::Err> {
-        s.chars().nth(0).and_then(|ch| match ch {
-                'y' | 'Y' => Some(Color::Yellow),
-                'r' | 'R' => Some(Color::Red),
-                'b' | 'B' => Some(Color::Blue),
-                'g' | 'G' => Some(Color::Green),
-                _ => None,
-        }).ok_or("Colors must be one of Y, R, G, or B.")
+        super::parse::color_word(&s.trim().to_lowercase())
+            .ok_or("Colors must be one of Y, R, G, or B.")
     }
 }
 
-impl Into<char> for Color {
-    fn into(self) -> char {
+impl Color {
+    pub fn long_name(self) -> &'static str {
         match self {
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Blue => "blue",
+            Color::Yellow => "yellow",
+        }
+    }
+}
+
+impl From<Color> for char {
+    fn from(color: Color) -> char {
+        match color {
             Color::Red => 'R',
             Color::Green => 'G',
             Color::Blue => 'B',
@@ -18,6 +18,20 @@ pub enum Face {
 }
 
 impl Face {
+    /// Wild cards can be played on anything and take a declared color.
+    pub fn is_wild(self) -> bool {
+        self == Face::ColorCard || self == Face::DrawFour
+    }
+
+    /// The points this card scores for the winner when left in a hand.
+    pub fn points(self) -> u32 {
+        match self {
+            Face::DrawFour | Face::ColorCard => 50,
+            Face::DrawTwo | Face::Skip | Face::Reverse => 20,
+            face => char::from(face).to_digit(10).unwrap(),
+        }
+    }
+
     pub fn from(ch: char) -> Option<Face> {
         match ch {
             '0' => Some(Face::Zero),
@@ -40,9 +54,31 @@ impl Face {
     }
 }
 
-impl Into<char> for Face {
-    fn into(self) -> char {
+impl Face {
+    pub fn long_name(self) -> &'static str {
         match self {
+            Face::DrawFour => "wild draw four",
+            Face::ColorCard => "wild",
+            Face::DrawTwo => "draw two",
+            Face::Skip => "skip",
+            Face::Reverse => "reverse",
+            Face::Zero => "zero",
+            Face::One => "one",
+            Face::Two => "two",
+            Face::Three => "three",
+            Face::Four => "four",
+            Face::Five => "five",
+            Face::Six => "six",
+            Face::Seven => "seven",
+            Face::Eight => "eight",
+            Face::Nine => "nine",
+        }
+    }
+}
+
+impl From<Face> for char {
+    fn from(face: Face) -> char {
+        match face {
             Face::DrawFour => 'D',
             Face::ColorCard => 'C',
             Face::Reverse => 'R',
@@ -0,0 +1,312 @@
+use super::color::Color;
+use super::face::Face;
+use super::Card;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+use core::ops::Range;
+
+/// What went wrong while reading a card from text.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseCardErrorKind {
+    /// The input had no words in it.
+    Empty,
+    /// A word that is neither a color nor a face.
+    UnknownWord(String),
+    /// "draw" was not followed by "two" or "four".
+    ExpectedDrawAmount,
+    /// A color was given more than once.
+    DuplicateColor,
+    /// A face was given more than once.
+    DuplicateFace,
+    /// No color was given. Wild cards need the color being declared.
+    MissingColor,
+    /// No face was given.
+    MissingFace,
+}
+
+/// Error returned by `Card::from_str`.
+///
+/// `span` is the byte range of the input the error refers to. For missing
+/// pieces it is the empty range at the end of the input.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseCardError {
+    kind: ParseCardErrorKind,
+    span: Range<usize>,
+}
+
+impl ParseCardError {
+    fn new(kind: ParseCardErrorKind, span: Range<usize>) -> Self {
+        ParseCardError { kind, span }
+    }
+
+    pub fn kind(&self) -> &ParseCardErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let column = self.span.start + 1;
+        match &self.kind {
+            ParseCardErrorKind::Empty => write!(f, "no card given"),
+            ParseCardErrorKind::UnknownWord(word) => write!(f, "unknown word \"{}\" at column {}", word, column),
+            ParseCardErrorKind::ExpectedDrawAmount => write!(f, "expected \"two\" or \"four\" after \"draw\" at column {}", column),
+            ParseCardErrorKind::DuplicateColor => write!(f, "second color at column {}", column),
+            ParseCardErrorKind::DuplicateFace => write!(f, "second face at column {}", column),
+            ParseCardErrorKind::MissingColor => write!(f, "missing a color"),
+            ParseCardErrorKind::MissingFace => write!(f, "missing a face"),
+        }
+    }
+}
+
+impl Error for ParseCardError {}
+
+/// A single recognised word.
+#[derive(Copy, Clone, Debug)]
+enum Word {
+    Color(Color),
+    Face(Face),
+    /// "r" on its own is either red or reverse, depending on what is missing.
+    RedOrReverse,
+    /// "draw", which needs an amount after it.
+    Draw,
+    /// Filler such as "card" in "wild card".
+    Noise,
+}
+
+pub(crate) fn color_word(word: &str) -> Option<Color> {
+    match word {
+        "r" | "red" => Some(Color::Red),
+        "g" | "grn" | "green" => Some(Color::Green),
+        "b" | "blu" | "blue" => Some(Color::Blue),
+        "y" | "yel" | "ylw" | "yellow" => Some(Color::Yellow),
+        _ => None,
+    }
+}
+
+fn face_word(word: &str) -> Option<Face> {
+    let face = match word {
+        "0" | "zero" => Face::Zero,
+        "1" | "one" => Face::One,
+        "2" | "two" => Face::Two,
+        "3" | "three" => Face::Three,
+        "4" | "four" => Face::Four,
+        "5" | "five" => Face::Five,
+        "6" | "six" => Face::Six,
+        "7" | "seven" => Face::Seven,
+        "8" | "eight" => Face::Eight,
+        "9" | "nine" => Face::Nine,
+        "s" | "sk" | "skip" => Face::Skip,
+        "rev" | "rv" | "reverse" => Face::Reverse,
+        "t" | "dt" | "d2" | "+2" | "draw2" | "drawtwo" => Face::DrawTwo,
+        "d" | "d4" | "+4" | "wd4" | "draw4" | "drawfour" => Face::DrawFour,
+        "c" | "w" | "wc" | "wild" | "color" | "colour" => Face::ColorCard,
+        _ => return None,
+    };
+    Some(face)
+}
+
+fn classify(word: &str) -> Option<Word> {
+    match word {
+        "r" => Some(Word::RedOrReverse),
+        "draw" => Some(Word::Draw),
+        "card" => Some(Word::Noise),
+        _ => color_word(word)
+            .map(Word::Color)
+            .or_else(|| face_word(word).map(Word::Face)),
+    }
+}
+
+/// Splits a glued word such as "Y0", "rskip" or "blue+2" into a color and a face.
+fn split_compact(word: &str) -> Option<(Color, Face)> {
+    word.char_indices()
+        .skip(1)
+        .find_map(|(i, _)| Some((color_word(&word[..i])?, face_word(&word[i..]).or_else(|| legacy_face(&word[i..]))?)))
+}
+
+/// The single letters used by `Display for Card`, so "RR" reads as red reverse.
+fn legacy_face(word: &str) -> Option<Face> {
+    let mut chars = word.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Face::from(ch.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+fn tokenize(s: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, ch) in s.char_indices().chain(core::iter::once((s.len(), ' '))) {
+        let separator = ch.is_whitespace() || ch == ',' || ch == '-' || ch == '_';
+        match (start, separator) {
+            (None, false) => start = Some(i),
+            (Some(from), true) => {
+                tokens.push((s[from..i].to_lowercase(), from..i));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+    tokens
+}
+
+#[derive(Default)]
+struct Slots {
+    color: Option<Color>,
+    face: Option<Face>,
+    red_or_reverse: Option<Range<usize>>,
+}
+
+impl Slots {
+    fn set_color(&mut self, color: Color, span: Range<usize>) -> Result<(), ParseCardError> {
+        if self.color.is_some() {
+            return Err(ParseCardError::new(ParseCardErrorKind::DuplicateColor, span));
+        }
+        self.color = Some(color);
+        Ok(())
+    }
+
+    fn set_face(&mut self, face: Face, span: Range<usize>) -> Result<(), ParseCardError> {
+        self.face = match (self.face, face) {
+            (None, face) => Some(face),
+            //"wild draw four" and "+4 wild" both name the same card
+            (Some(Face::ColorCard), Face::DrawFour) | (Some(Face::DrawFour), Face::ColorCard) => Some(Face::DrawFour),
+            _ => return Err(ParseCardError::new(ParseCardErrorKind::DuplicateFace, span)),
+        };
+        Ok(())
+    }
+}
+
+pub(crate) fn parse_card(s: &str) -> Result<Card, ParseCardError> {
+    let tokens = tokenize(s);
+    if tokens.is_empty() {
+        return Err(ParseCardError::new(ParseCardErrorKind::Empty, s.len()..s.len()));
+    }
+
+    let mut slots = Slots::default();
+    let mut iter = tokens.into_iter().peekable();
+    while let Some((word, span)) = iter.next() {
+        match classify(&word) {
+            Some(Word::Color(color)) => slots.set_color(color, span)?,
+            Some(Word::Face(face)) => slots.set_face(face, span)?,
+            Some(Word::RedOrReverse) if slots.red_or_reverse.is_none() => slots.red_or_reverse = Some(span),
+            Some(Word::RedOrReverse) => slots.set_face(Face::Reverse, span)?,
+            Some(Word::Draw) => {
+                let face = match iter.peek().map(|(word, _)| word.as_str()) {
+                    Some("two") | Some("2") => Face::DrawTwo,
+                    Some("four") | Some("4") => Face::DrawFour,
+                    _ => {
+                        let at = iter.peek().map(|(_, span)| span.clone()).unwrap_or(s.len()..s.len());
+                        return Err(ParseCardError::new(ParseCardErrorKind::ExpectedDrawAmount, at));
+                    },
+                };
+                let (_, amount) = iter.next().unwrap();
+                slots.set_face(face, span.start..amount.end)?;
+            },
+            Some(Word::Noise) => {},
+            None => match split_compact(&word) {
+                Some((color, face)) => {
+                    slots.set_color(color, span.clone())?;
+                    slots.set_face(face, span)?;
+                },
+                None => return Err(ParseCardError::new(ParseCardErrorKind::UnknownWord(s[span.clone()].to_string()), span)),
+            },
+        }
+    }
+
+    //A lone "r" fills whichever of color or face is still empty, preferring color.
+    if let Some(span) = slots.red_or_reverse.take() {
+        if slots.color.is_none() {
+            slots.color = Some(Color::Red);
+        } else {
+            slots.set_face(Face::Reverse, span)?;
+        }
+    }
+
+    let end = s.trim_end().len();
+    match slots {
+        Slots { color: Some(color), face: Some(face), .. } => Ok(Card::new(color, face)),
+        Slots { face: None, .. } => Err(ParseCardError::new(ParseCardErrorKind::MissingFace, end..end)),
+        Slots { color: None, .. } => Err(ParseCardError::new(ParseCardErrorKind::MissingColor, end..end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(s: &str) -> Card {
+        s.parse().unwrap()
+    }
+
+    fn kind(s: &str) -> ParseCardErrorKind {
+        s.parse::<Card>().unwrap_err().kind().clone()
+    }
+
+    #[test]
+    fn long_forms() {
+        assert_eq!(Card::new(Color::Red, Face::Seven), card("red seven"));
+        assert_eq!(Card::new(Color::Red, Face::Seven), card("red 7"));
+        assert_eq!(Card::new(Color::Blue, Face::Skip), card("blue skip"));
+        assert_eq!(Card::new(Color::Yellow, Face::DrawTwo), card("+2 yellow"));
+        assert_eq!(Card::new(Color::Green, Face::DrawTwo), card("Green Draw Two"));
+
+        let wild = card("wild draw four green");
+        assert_eq!(Face::DrawFour, wild.face);
+        assert_eq!(Color::Green, wild.color);
+
+        let wild = card("wild card blue");
+        assert_eq!(Face::ColorCard, wild.face);
+        assert_eq!(Color::Blue, wild.color);
+    }
+
+    #[test]
+    fn abbreviations_and_case() {
+        assert_eq!(Card::new(Color::Yellow, Face::Zero), card("y0"));
+        assert_eq!(Card::new(Color::Red, Face::Reverse), card("RR"));
+        assert_eq!(Card::new(Color::Red, Face::Reverse), card("r r"));
+        assert_eq!(Card::new(Color::Red, Face::Reverse), card("REV r"));
+        assert_eq!(Card::new(Color::Blue, Face::DrawTwo), card("b+2"));
+        assert_eq!(Card::new(Color::Green, Face::Skip), card("GSkip"));
+        assert_eq!(Card::new(Color::Yellow, Face::Nine), card("YEL-9"));
+        assert_eq!(Face::DrawFour, card("+4 g").face);
+        assert_eq!(Face::DrawFour, card("wd4 b").face);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let err = "red purple".parse::<Card>().unwrap_err();
+        assert_eq!(&ParseCardErrorKind::UnknownWord("purple".into()), err.kind());
+        assert_eq!(4..10, err.span());
+
+        let err = "red blue 7".parse::<Card>().unwrap_err();
+        assert_eq!(&ParseCardErrorKind::DuplicateColor, err.kind());
+        assert_eq!(4..8, err.span());
+
+        assert_eq!(ParseCardErrorKind::ExpectedDrawAmount, kind("red draw"));
+        assert_eq!(ParseCardErrorKind::DuplicateFace, kind("red 7 skip"));
+        assert_eq!(ParseCardErrorKind::MissingColor, kind("wild"));
+        assert_eq!(ParseCardErrorKind::MissingFace, kind("blue"));
+        assert_eq!(ParseCardErrorKind::Empty, kind("   "));
+    }
+
+    #[test]
+    fn long_name_round_trips() {
+        let deck = crate::deck::Deck::default();
+        for &color in &[Color::Red, Color::Green, Color::Blue, Color::Yellow] {
+            for card in deck.iter() {
+                let card = Card::new(color, card.face);
+                let parsed = card.long_name().parse::<Card>().unwrap();
+                assert_eq!(card, parsed);
+                assert_eq!(card.color, parsed.color);
+            }
+        }
+    }
+}
@@ -1,9 +1,15 @@
 use crate::card::{color::*, face::*, *};
+use crate::locale::Locale;
 
-use std::char;
-use std::fmt::{self, Display};
-use std::ops::{AddAssign, Deref, DerefMut, SubAssign};
+use shuffle::Xorshift;
 
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::char;
+use core::fmt::{self, Display};
+use core::ops::{AddAssign, Deref, DerefMut, SubAssign};
+
+#[derive(Clone)]
 pub struct Deck {
     cards: Vec<Card>,
 }
@@ -13,10 +19,15 @@ impl Deck {
         Deck { cards }
     }
 
+    #[cfg(feature = "std")]
     pub fn shuffle(&mut self) {
         shuffle::shuffle(&mut self.cards);
     }
 
+    pub fn shuffle_with(&mut self, rng: &mut Xorshift) {
+        rng.shuffle(&mut self.cards);
+    }
+
     pub fn draw(&mut self) -> Option<Card> {
         if self.cards.is_empty() {
             return None;
@@ -30,7 +41,7 @@ impl Deck {
             return None;
         }
 
-        self.cards.get(0)
+        self.cards.first()
     }
 
     pub fn reclaim(&mut self, other: &mut Deck) {
@@ -38,34 +49,49 @@ impl Deck {
     }
 
     pub fn has_card(&self, rhs: Card) -> bool {
-        self.cards.iter().any(|&card| card == rhs)
+        self.cards.contains(&rhs)
+    }
+
+    /// The names of the cards in this deck in `locale`, comma separated.
+    pub fn names(&self, locale: Locale) -> String {
+        let names: Vec<_> = self.cards.iter().map(|&card| locale.card_name(card)).collect();
+        names.join(", ")
     }
 }
 
-impl Default for Deck {
-    fn default() -> Deck {
+impl Deck {
+    /// The 108 cards of a standard deck, in a fixed order.
+    pub fn standard() -> Deck {
         let mut cards: Vec<Card> = Vec::with_capacity(108);
 
         [Color::Red, Color::Green, Color::Blue, Color::Yellow].iter().for_each(|&color| {
             cards.push(Card::new(color, Face::Zero));
-            (0..2).into_iter().for_each(|_| {
+            (0..2).for_each(|_| {
                 cards.push(Card::new(color, Face::DrawTwo));
                 cards.push(Card::new(color, Face::Reverse));
                 cards.push(Card::new(color, Face::Skip));
 
-                (1..=9).into_iter()
+                (1..=9)
                     .filter_map(|x| char::from_digit(x, 10))
                     .filter_map(Face::from)
                     .for_each(|face| cards.push(Card::new(color, face)));
             });
         });
 
-        (0..4).into_iter().for_each(|_| {
+        (0..4).for_each(|_| {
             cards.push(Card::new(Color::Red, Face::DrawFour));
             cards.push(Card::new(Color::Red, Face::ColorCard));
         });
 
-        let mut deck = Deck::new(cards);
+        Deck::new(cards)
+    }
+}
+
+/// A shuffled standard deck.
+#[cfg(feature = "std")]
+impl Default for Deck {
+    fn default() -> Deck {
+        let mut deck = Deck::standard();
         deck.shuffle();
         deck
     }
@@ -87,8 +113,7 @@ impl DerefMut for Deck {
 
 impl Display for Deck {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let joined: Vec<_> = self.cards.iter().map(Card::display_name).collect();
-        write!(f, "{:?}", joined)
+        write!(f, "{}", self.names(Locale::English))
     }
 }
 
@@ -125,7 +150,7 @@ mod tests {
         let top = deck.draw().unwrap();
         assert_eq!(107, (*deck).len());
 
-        deck += top.clone();
+        deck += top;
         assert_eq!(deck.peek_top_card(), Some(&top));
         assert_eq!(108, (*deck).len());
     }
@@ -133,7 +158,7 @@ mod tests {
     #[test]
     fn test_sub_assign() {
         let mut deck = Deck::default();
-        let top = deck.peek_top_card().unwrap().clone();
+        let top = *deck.peek_top_card().unwrap();
         assert_eq!(108, (*deck).len());
 
         deck -= top;
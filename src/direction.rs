@@ -1,7 +1,9 @@
-use std::ops::{Add, Not};
+use core::ops::{Add, Not};
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+/// Games start clockwise.
+#[derive(PartialEq, Eq, Copy, Clone, Debug, Default)]
 pub enum GameDirection {
+    #[default]
     Clockwise,
     CounterClockwise,
 }
@@ -0,0 +1,288 @@
+use crate::card::Card;
+use crate::strategy::{self, Strategy};
+use crate::timer::{OnTimeout, Timers};
+use crate::uno::{Action, Phase, TurnResult, Uno};
+use crate::view::PlayerView;
+
+use std::time::Duration;
+
+/// Who makes the decisions for a seat.
+pub enum Seat {
+    /// A person, whose moves are passed to `Driver::apply`.
+    Human,
+    Computer(Box<dyn Strategy>),
+}
+
+/// Runs a game where any mix of seats is human or computer.
+///
+/// Computer seats are played by `step` and `run`. When it is a human's turn
+/// the driver stops and waits for their move through `apply`.
+pub struct Driver {
+    uno: Uno,
+    seats: Vec<Seat>,
+    //events already passed to every strategy's `observe`
+    delivered: usize,
+    timers: Option<Timers>,
+}
+
+impl Driver {
+    pub fn new(uno: Uno, seats: Vec<Seat>) -> Driver {
+        if seats.len() != uno.players().len() {
+            panic!("Need one seat for each of the {} players!", uno.players().len());
+        }
+
+        let mut driver = Driver { uno, seats, delivered: 0, timers: None };
+        driver.deliver();
+        driver
+    }
+
+    pub fn uno(&self) -> &Uno {
+        &self.uno
+    }
+
+    pub fn seats(&self) -> &[Seat] {
+        &self.seats
+    }
+
+    pub fn is_human(&self, seat: usize) -> bool {
+        matches!(self.seats[seat], Seat::Human)
+    }
+
+    /// Hands a seat over to someone else, e.g. a bot standing in for a player who left.
+    ///
+    /// A computer taking over is shown the whole game so far, so it knows
+    /// as much as if it had played from the start.
+    pub fn set_seat(&mut self, seat: usize, mut controller: Seat) -> Seat {
+        if let Seat::Computer(strategy) = &mut controller {
+            self.uno.events().iter().for_each(|event| strategy.observe(event));
+        }
+        let previous = std::mem::replace(&mut self.seats[seat], controller);
+        self.delivered = self.uno.events().len();
+        self.track();
+        previous
+    }
+
+    /// Starts timing people's turns. Their clocks only run while they are
+    /// to move, from now on.
+    pub fn set_timers(&mut self, timers: Timers) {
+        self.timers = Some(timers);
+        self.track();
+    }
+
+    pub fn timers(&self) -> Option<&Timers> {
+        self.timers.as_ref()
+    }
+
+    /// How long the current player has left to move, if they are timed.
+    pub fn time_left(&self) -> Option<Duration> {
+        self.timers.as_ref()?.remaining()
+    }
+
+    /// Makes the current player's move for them if their time has run out,
+    /// as their `TimeControl` says, and returns what came of each part of it,
+    /// e.g. drawing and then passing. Returns nothing while there is time left.
+    pub fn time_out(&mut self) -> Vec<TurnResult> {
+        let on_timeout = match &self.timers {
+            Some(timers) if timers.expired() => timers.control.on_timeout,
+            _ => return Vec::new(),
+        };
+
+        let (seat, turn) = (self.uno.current_seat(), self.uno.current_turn());
+        let mut bot = strategy::by_name("basic", turn as u64).unwrap();
+        let mut results = Vec::new();
+        while self.uno.winner().is_none() && self.uno.current_seat() == seat && self.uno.current_turn() == turn {
+            let action = match on_timeout {
+                OnTimeout::DrawAndPass => {
+                    let legal = self.uno.legal_actions();
+                    [Action::Draw, Action::Pass, Action::Accept].iter().copied().find(|action| legal.contains(action)).unwrap_or(legal[0])
+                },
+                OnTimeout::AutoPlay => decide(bot.as_mut(), &mut self.uno),
+            };
+            results.push(self.apply(action));
+        }
+        results
+    }
+
+    /// Declares UNO for the current player.
+    pub fn call_uno(&mut self) {
+        self.uno.call_uno();
+    }
+
+    /// Makes a move for the current player, whoever controls the seat.
+    pub fn apply(&mut self, action: Action) -> TurnResult {
+        let result = self.uno.apply(action);
+        self.deliver();
+        self.track();
+        result
+    }
+
+    /// Plays one decision for the current seat if a computer controls it.
+    /// Returns `None` on a human's turn or once the game is over.
+    pub fn step(&mut self) -> Option<TurnResult> {
+        if self.uno.winner().is_some() {
+            return None;
+        }
+
+        let seat = self.uno.current_seat();
+        let strategy = match &mut self.seats[seat] {
+            Seat::Human => return None,
+            Seat::Computer(strategy) => strategy,
+        };
+
+        let action = decide(strategy.as_mut(), &mut self.uno);
+        let result = self.uno.apply(action);
+        if let TurnResult::InvalidMove(..) | TurnResult::NotHoldingCard(_) | TurnResult::IllegalAction(_) = result {
+            panic!("Strategy {} made an illegal move: {:?}", strategy.name(), result);
+        }
+
+        self.deliver();
+        self.track();
+        Some(result)
+    }
+
+    /// Plays computer seats until a human has to move, returning their seat.
+    /// Returns `None` once the game is over.
+    pub fn run(&mut self) -> Option<usize> {
+        while self.step().is_some() {}
+        match self.uno.winner() {
+            Some(_) => None,
+            None => Some(self.uno.current_seat()),
+        }
+    }
+
+    /// Tells the timers whose turn it is now.
+    fn track(&mut self) {
+        let seat = self.uno.current_seat();
+        let timed = self.uno.winner().is_none() && self.is_human(seat);
+        let turn = self.uno.current_turn();
+        if let Some(timers) = &mut self.timers {
+            timers.track(Some(seat).filter(|_| timed), turn);
+        }
+    }
+
+    fn deliver(&mut self) {
+        let events = &self.uno.events()[self.delivered..];
+        for seat in self.seats.iter_mut() {
+            if let Seat::Computer(strategy) = seat {
+                events.iter().for_each(|event| strategy.observe(event));
+            }
+        }
+        self.delivered = self.uno.events().len();
+    }
+}
+
+/// Asks `strategy` what the current player of `uno` should do, including
+/// the color of a wild and whether to call UNO.
+pub fn decide(strategy: &mut dyn Strategy, uno: &mut Uno) -> Action {
+    let view = uno.view(uno.current_seat());
+    let (action, call_uno) = decide_from_view(strategy, &view, uno.legal_actions());
+    if call_uno {
+        uno.call_uno();
+    }
+    action
+}
+
+/// Like `decide`, for a player who only has their view of the game and the
+/// actions they may take. Also returns whether to call UNO first.
+pub fn decide_from_view(strategy: &mut dyn Strategy, view: &PlayerView, mut legal: Vec<Action>) -> (Action, bool) {
+    if let Phase::Challenge { .. } = view.phase {
+        if strategy.challenge(view) {
+            return (Action::Challenge, false);
+        }
+        legal.retain(|&action| action != Action::Challenge);
+    }
+
+    let action = match strategy.choose_action(view, &legal) {
+        Action::Play(card) if card.face.is_wild() => Action::Play(Card::new(strategy.choose_color(view), card.face)),
+        action => action,
+    };
+
+    let call_uno = matches!(action, Action::Play(_)) && view.hand.len() == 2 && strategy.call_uno(view);
+    (action, call_uno)
+}
+
+/// Plays a game between computer players only. Returns the winning seat, or
+/// `None` if nobody had won after `max_turns` turns.
+pub fn play_out(uno: Uno, strategies: Vec<Box<dyn Strategy>>, max_turns: usize) -> (Option<usize>, Uno) {
+    let mut driver = Driver::new(uno, strategies.into_iter().map(Seat::Computer).collect());
+    while driver.uno.current_turn() <= max_turns && driver.step().is_some() {}
+    let winner = driver.uno.winner();
+    (winner, driver.uno)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Rules;
+    use crate::belief::Beliefs;
+    use crate::card::color::Color;
+    use crate::event::Event;
+    use crate::strategy::Basic;
+    use std::sync::{Arc, Mutex};
+
+    /// Plays like `Basic`, keeping beliefs where the test can see them.
+    struct Watcher(Arc<Mutex<Beliefs>>);
+
+    impl Strategy for Watcher {
+        fn name(&self) -> &str {
+            "watcher"
+        }
+
+        fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+            Basic.choose_action(view, legal)
+        }
+
+        fn choose_color(&mut self, view: &PlayerView) -> Color {
+            Basic.choose_color(view)
+        }
+
+        fn observe(&mut self, event: &Event) {
+            self.0.lock().unwrap().observe(event);
+        }
+    }
+
+    #[test]
+    fn computers_play_until_a_human_is_up() {
+        let uno = Uno::create_game_with(vec!["Ali".into(), "Bob".into(), "Cam".into()], Rules::official(), 3);
+        let mut driver = Driver::new(uno, vec![Seat::Computer(Box::new(Basic)), Seat::Human, Seat::Computer(Box::new(Basic))]);
+
+        while let Some(seat) = driver.run() {
+            assert_eq!(1, seat);
+            let action = driver.uno().legal_actions().last().copied().unwrap();
+            driver.apply(action);
+        }
+        assert!(driver.uno().winner().is_some());
+    }
+
+    #[test]
+    fn stand_ins_know_the_game_so_far() {
+        let uno = Uno::create_game_with(vec!["Ali".into(), "Bob".into()], Rules::official(), 5);
+        let (watched, stood_in) = (Arc::new(Mutex::new(Beliefs::new())), Arc::new(Mutex::new(Beliefs::new())));
+        let mut driver = Driver::new(uno, vec![Seat::Computer(Box::new(Watcher(watched.clone()))), Seat::Human]);
+
+        for _ in 0..3 {
+            driver.run();
+            let action = driver.uno().legal_actions().last().copied().unwrap();
+            driver.apply(action);
+        }
+        driver.set_seat(1, Seat::Computer(Box::new(Watcher(stood_in.clone()))));
+        assert_eq!(*watched.lock().unwrap(), *stood_in.lock().unwrap());
+
+        driver.run();
+        assert!(driver.uno().winner().is_some());
+        assert_eq!(*watched.lock().unwrap(), *stood_in.lock().unwrap());
+    }
+
+    #[test]
+    fn computer_only_games_finish() {
+        for seed in 0..50 {
+            for rules in [Rules::official(), Rules::stacking(), Rules::seven_o()].iter() {
+                let uno = Uno::create_game_with(vec!["Ali".into(), "Bob".into(), "Cam".into(), "Dan".into()], rules.clone(), seed);
+                let strategies: Vec<Box<dyn Strategy>> = (0..4).map(|_| Box::new(Basic) as Box<dyn Strategy>).collect();
+                let (winner, uno) = play_out(uno, strategies, 5000);
+                assert!(winner.is_some(), "seed {} did not finish", seed);
+                assert_eq!(0, uno.players()[winner.unwrap()].get_hand().len());
+            }
+        }
+    }
+}
@@ -0,0 +1,420 @@
+use crate::card::color::Color;
+use crate::event::Event;
+use crate::protocol::{action_text, event_text, parse_action, parse_event, parse_view, view_text, ProtocolError};
+use crate::strategy::{most_held_color, Strategy};
+use crate::uno::Action;
+use crate::view::PlayerView;
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long an engine may take to start up and answer `uno` or `isready`.
+const STARTUP: Duration = Duration::from_secs(10);
+/// How long an engine may take to exit after `quit` before it is killed.
+const SHUTDOWN: Duration = Duration::from_secs(1);
+/// How long past its movetime a `bestmove` is still taken, for the time it
+/// takes to reach the referee.
+const LATENCY: Duration = Duration::from_millis(50);
+
+/// A line the referee sends to an engine on its standard input.
+///
+/// | line                                 | meaning                                         |
+/// |--------------------------------------|-------------------------------------------------|
+/// | `uno`                                | the first line; answer with `id` lines, `unook` |
+/// | `isready`                            | answer `readyok` once caught up                 |
+/// | `newgame`                            | a game starts; forget the last one              |
+/// | `event <event>`                      | something everyone at the table saw happen      |
+/// | `view <key=value>...`                | what this engine can see, before every `go`     |
+/// | `go movetime <ms> actions <actions>` | pick one of the comma separated actions         |
+/// | `gameover <seat>` or `gameover none` | the game is over, with its winner if any        |
+/// | `quit`                               | exit                                            |
+///
+/// Events, views and actions are written as in `ServerLine` and `ClientLine`,
+/// e.g. `event PLAYED 1 R5`, `go movetime 1000 actions PLAY R5,PLAY GC,DRAW`.
+/// Seats count from 0 and the engine's own seat is the view's `seat`.
+#[derive(Clone, Debug)]
+pub enum RefereeLine {
+    Uno,
+    IsReady,
+    NewGame,
+    Event(Event),
+    View(PlayerView),
+    Go { movetime: Duration, actions: Vec<Action> },
+    GameOver { winner: Option<usize> },
+    Quit,
+}
+
+/// A line an engine writes to its standard output.
+///
+/// | line                        | meaning                                              |
+/// |-----------------------------|------------------------------------------------------|
+/// | `id name <name>`            | the engine's name, before `unook`                    |
+/// | `id author <author>`        | who wrote it, before `unook`                         |
+/// | `unook`                     | the handshake is done                                |
+/// | `readyok`                   | the answer to `isready`                              |
+/// | `bestmove <action> [uno]`   | the answer to `go`; `uno` calls UNO before the move  |
+/// | `info <text>`               | anything the engine wants logged; ignored            |
+///
+/// A wild card is played with the color it declares, e.g. `bestmove PLAY GC`.
+/// Lines that fit none of these are ignored.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EngineLine {
+    Id { key: String, value: String },
+    UnoOk,
+    ReadyOk,
+    BestMove { action: Action, uno: bool },
+    Info(String),
+}
+
+impl Display for RefereeLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RefereeLine::Uno => write!(f, "uno"),
+            RefereeLine::IsReady => write!(f, "isready"),
+            RefereeLine::NewGame => write!(f, "newgame"),
+            RefereeLine::Event(event) => write!(f, "event {}", event_text(event)),
+            RefereeLine::View(view) => write!(f, "view {}", view_text(view)),
+            RefereeLine::Go { movetime, actions } => {
+                let actions: Vec<_> = actions.iter().map(|&action| action_text(action)).collect();
+                write!(f, "go movetime {} actions {}", movetime.as_millis(), actions.join(","))
+            },
+            RefereeLine::GameOver { winner: Some(seat) } => write!(f, "gameover {}", seat),
+            RefereeLine::GameOver { winner: None } => write!(f, "gameover none"),
+            RefereeLine::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+impl FromStr for RefereeLine {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<RefereeLine, ProtocolError> {
+        let line = line.trim();
+        let error = || ProtocolError { line: line.into() };
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let parsed = match (command, words.as_slice()) {
+            ("uno", []) => Some(RefereeLine::Uno),
+            ("isready", []) => Some(RefereeLine::IsReady),
+            ("newgame", []) => Some(RefereeLine::NewGame),
+            ("event", words) => parse_event(words).map(RefereeLine::Event),
+            ("view", words) => parse_view(words).map(RefereeLine::View),
+            ("go", ["movetime", movetime, "actions", ..]) => {
+                let actions = rest.split_once("actions").ok_or_else(error)?.1;
+                let actions = actions.split(',').map(|action| parse_action(&action.split_whitespace().collect::<Vec<_>>())).collect::<Option<_>>();
+                match (movetime.parse().ok(), actions) {
+                    (Some(movetime), Some(actions)) => Some(RefereeLine::Go { movetime: Duration::from_millis(movetime), actions }),
+                    _ => None,
+                }
+            },
+            ("gameover", ["none"]) => Some(RefereeLine::GameOver { winner: None }),
+            ("gameover", [seat]) => seat.parse().ok().map(|seat| RefereeLine::GameOver { winner: Some(seat) }),
+            ("quit", []) => Some(RefereeLine::Quit),
+            _ => None,
+        };
+        parsed.ok_or_else(error)
+    }
+}
+
+impl Display for EngineLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineLine::Id { key, value } => write!(f, "id {} {}", key, value),
+            EngineLine::UnoOk => write!(f, "unook"),
+            EngineLine::ReadyOk => write!(f, "readyok"),
+            EngineLine::BestMove { action, uno: false } => write!(f, "bestmove {}", action_text(*action)),
+            EngineLine::BestMove { action, uno: true } => write!(f, "bestmove {} uno", action_text(*action)),
+            EngineLine::Info(text) => write!(f, "info {}", text),
+        }
+    }
+}
+
+impl FromStr for EngineLine {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<EngineLine, ProtocolError> {
+        let line = line.trim();
+        let error = || ProtocolError { line: line.into() };
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let parsed = match (command, words.as_slice()) {
+            ("id", [key, _, ..]) => Some(EngineLine::Id { key: key.to_string(), value: rest.trim_start()[key.len()..].trim().into() }),
+            ("unook", []) => Some(EngineLine::UnoOk),
+            ("readyok", []) => Some(EngineLine::ReadyOk),
+            ("bestmove", [action @ .., "uno"]) => parse_action(action).map(|action| EngineLine::BestMove { action, uno: true }),
+            ("bestmove", action) => parse_action(action).map(|action| EngineLine::BestMove { action, uno: false }),
+            ("info", _) => Some(EngineLine::Info(rest.into())),
+            _ => None,
+        };
+        parsed.ok_or_else(error)
+    }
+}
+
+/// Why an engine could not be used.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EngineError {
+    /// The command could not be run.
+    Launch { command: String, error: String },
+    /// The engine exited or closed its output.
+    Closed,
+    /// The engine did not send `waiting_for` in time.
+    TimedOut { waiting_for: &'static str },
+    /// The engine picked something it wasn't offered.
+    Illegal(Action),
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EngineError::Launch { command, error } => write!(f, "could not run \"{}\": {}", command, error),
+            EngineError::Closed => write!(f, "the engine has exited"),
+            EngineError::TimedOut { waiting_for } => write!(f, "the engine did not send {} in time", waiting_for),
+            EngineError::Illegal(action) => write!(f, "the engine chose {}, which it was not offered", action_text(*action)),
+        }
+    }
+}
+
+impl Error for EngineError {}
+
+/// A player in another process, spoken to through `RefereeLine`s and
+/// `EngineLine`s over its standard input and output.
+///
+/// As a `Strategy` it stands in for a built-in bot anywhere one can play.
+/// When the engine fails to answer properly in time its seat makes the last
+/// of the legal actions, usually a draw or pass, and a warning is printed.
+pub struct Engine {
+    name: String,
+    author: Option<String>,
+    child: Child,
+    input: ChildStdin,
+    output: Receiver<String>,
+    /// How long the engine may think about each move.
+    pub movetime: Duration,
+    faults: usize,
+    //answers to `go`s that timed out, which are skipped when they arrive late
+    owed: usize,
+    //what the engine chose while being asked about a challenge, with its color and UNO call
+    decided: Option<Action>,
+    color: Color,
+    uno: bool,
+    game_over: bool,
+}
+
+impl Engine {
+    /// Runs `command`, split on whitespace into the program and its arguments,
+    /// and shakes hands with it.
+    pub fn start(command: &str, movetime: Duration) -> Result<Engine, EngineError> {
+        let launch_error = |error: io::Error| EngineError::Launch { command: command.into(), error: error.to_string() };
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or_else(|| launch_error(io::Error::new(io::ErrorKind::InvalidInput, "no program given")))?;
+        let mut child = Command::new(program).args(words).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().map_err(launch_error)?;
+
+        let input = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Engine {
+            name: program.into(),
+            author: None,
+            child,
+            input,
+            output,
+            movetime,
+            faults: 0,
+            owed: 0,
+            decided: None,
+            color: Color::Red,
+            uno: false,
+            game_over: false,
+        };
+        engine.send(&RefereeLine::Uno)?;
+        loop {
+            match engine.receive(STARTUP, "unook")? {
+                EngineLine::Id { key, value } if key == "name" => engine.name = value,
+                EngineLine::Id { key, value } if key == "author" => engine.author = Some(value),
+                EngineLine::UnoOk => break,
+                _ => {},
+            }
+        }
+        engine.send(&RefereeLine::NewGame)?;
+        Ok(engine)
+    }
+
+    /// The name the engine gave, or its program if it gave none.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// How many times the engine has failed to give a legal move in time.
+    pub fn faults(&self) -> usize {
+        self.faults
+    }
+
+    pub fn send(&mut self, line: &RefereeLine) -> Result<(), EngineError> {
+        writeln!(self.input, "{}", line).map_err(|_| EngineError::Closed)
+    }
+
+    /// Waits up to `timeout` for the engine's next line, skipping any that
+    /// don't follow the protocol. `waiting_for` names what is expected, for errors.
+    pub fn receive(&mut self, timeout: Duration, waiting_for: &'static str) -> Result<EngineLine, EngineError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.output.recv_timeout(left) {
+                Ok(line) => {
+                    if let Ok(line) = line.parse() {
+                        return Ok(line);
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => return Err(EngineError::TimedOut { waiting_for }),
+                Err(RecvTimeoutError::Disconnected) => return Err(EngineError::Closed),
+            }
+        }
+    }
+
+    /// Asks whether the engine has caught up with everything sent so far.
+    pub fn ready(&mut self) -> Result<(), EngineError> {
+        self.send(&RefereeLine::IsReady)?;
+        while self.receive(STARTUP, "readyok")? != EngineLine::ReadyOk {}
+        Ok(())
+    }
+
+    /// Asks the engine for one of `legal`, and whether to call UNO first.
+    pub fn best_move(&mut self, view: &PlayerView, legal: &[Action]) -> Result<(Action, bool), EngineError> {
+        self.send(&RefereeLine::View(view.clone()))?;
+        self.send(&RefereeLine::Go { movetime: self.movetime, actions: legal.to_vec() })?;
+        let deadline = Instant::now() + self.movetime + LATENCY;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match self.receive(left, "bestmove") {
+                Ok(EngineLine::BestMove { .. }) if self.owed > 0 => self.owed -= 1,
+                Ok(EngineLine::BestMove { action, uno }) => {
+                    return match legal.contains(&action) {
+                        true => Ok((action, uno)),
+                        false => Err(EngineError::Illegal(action)),
+                    };
+                },
+                Ok(_) => {},
+                Err(e) => {
+                    if let EngineError::TimedOut { .. } = e {
+                        self.owed += 1;
+                    }
+                    return Err(e);
+                },
+            }
+        }
+    }
+
+    /// Like `best_move`, but falls back on the last legal action if the engine
+    /// fails, remembering the card's color and the UNO call for later.
+    fn decide(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        let (action, uno) = self.best_move(view, legal).unwrap_or_else(|e| {
+            self.faults += 1;
+            eprintln!("warning: {}: {}", self.name, e);
+            (*legal.last().unwrap(), false)
+        });
+        self.color = match action {
+            Action::Play(card) if card.face.is_wild() => card.color,
+            _ => most_held_color(&view.hand),
+        };
+        self.uno = uno;
+        action
+    }
+}
+
+impl Strategy for Engine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        match self.decided.take() {
+            Some(action) if legal.contains(&action) => action,
+            _ => self.decide(view, legal),
+        }
+    }
+
+    fn choose_color(&mut self, _view: &PlayerView) -> Color {
+        self.color
+    }
+
+    /// The engine is asked for its whole answer to a Draw Four here, since it
+    /// may rather stack another one than challenge or accept.
+    fn challenge(&mut self, view: &PlayerView) -> bool {
+        let legal = view.legal_actions();
+        let action = self.decide(view, &legal);
+        self.decided = Some(action);
+        action == Action::Challenge
+    }
+
+    fn call_uno(&mut self, _view: &PlayerView) -> bool {
+        self.uno
+    }
+
+    fn observe(&mut self, event: &Event) {
+        //an engine that has gone is found out at its next move
+        let _ = self.send(&RefereeLine::Event(*event));
+        if let Event::Won { seat } = *event {
+            let _ = self.send(&RefereeLine::GameOver { winner: Some(seat) });
+            self.game_over = true;
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        if !self.game_over {
+            let _ = self.send(&RefereeLine::GameOver { winner: None });
+        }
+        let _ = self.send(&RefereeLine::Quit);
+        let deadline = Instant::now() + SHUTDOWN;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_read_back() {
+        let referee = ["uno", "isready", "newgame", "event PLAYED 1 R5", "go movetime 250 actions PLAY R5,PLAY GC,DRAW", "gameover 2", "gameover none", "quit"];
+        for line in referee.iter() {
+            assert_eq!(*line, line.parse::<RefereeLine>().unwrap().to_string());
+        }
+        let engine = ["id name Deep Red", "unook", "readyok", "bestmove PLAY YC uno", "bestmove SWAP 1", "info thinking hard"];
+        for line in engine.iter() {
+            assert_eq!(*line, line.parse::<EngineLine>().unwrap().to_string());
+        }
+
+        assert_eq!(EngineLine::Id { key: "name".into(), value: "Deep Red".into() }, "id name Deep Red".parse().unwrap());
+        assert_eq!(EngineLine::BestMove { action: Action::Draw, uno: false }, "bestmove DRAW".parse().unwrap());
+        assert!("go movetime soon actions DRAW".parse::<RefereeLine>().is_err());
+        assert!("bestmove".parse::<EngineLine>().is_err());
+        assert!("bestmove PLAY X9".parse::<EngineLine>().is_err());
+    }
+}
@@ -0,0 +1,415 @@
+mod encode;
+
+pub use encode::Encoding;
+
+use crate::card::{color::Color, face::Face, Card};
+use crate::driver::{Driver, Seat};
+use crate::event::Event;
+use crate::player::Player;
+use crate::rules::{RuleError, Rules};
+use crate::sim::bot_seed;
+use crate::strategy;
+use crate::uno::{Action, TurnResult, Uno};
+use crate::view::PlayerView;
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+const COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+const FACES: [Face; 13] = [
+    Face::Zero,
+    Face::One,
+    Face::Two,
+    Face::Three,
+    Face::Four,
+    Face::Five,
+    Face::Six,
+    Face::Seven,
+    Face::Eight,
+    Face::Nine,
+    Face::Skip,
+    Face::Reverse,
+    Face::DrawTwo,
+];
+
+/// The size of the action space.
+///
+/// | index  | action                                                  |
+/// |--------|---------------------------------------------------------|
+/// | 0-51   | play a colored card: `13 * color + face`                |
+/// | 52-55  | play a Wild Card declaring red, green, blue, yellow     |
+/// | 56-59  | play a Draw Four declaring red, green, blue, yellow     |
+/// | 60     | draw, or take the pending penalty                       |
+/// | 61     | pass after drawing                                      |
+/// | 62     | challenge a Draw Four                                   |
+/// | 63     | accept a Draw Four                                      |
+/// | 64-70  | swap hands with the seat 1-7 places after this one      |
+///
+/// Colors count red, green, blue, yellow from 0 and faces count 0-9, skip,
+/// reverse, draw two from 0. Seats count in seat order, whatever the direction.
+pub const ACTIONS: usize = 71;
+
+const DRAW: usize = 60;
+const PASS: usize = 61;
+const CHALLENGE: usize = 62;
+const ACCEPT: usize = 63;
+const SWAP: usize = 64;
+
+/// Where `card` is in the action space, or in `0..60` for anything indexed by card.
+pub fn card_index(card: Card) -> usize {
+    let color = COLORS.iter().position(|&color| color == card.color).unwrap();
+    match card.face {
+        Face::ColorCard => 52 + color,
+        Face::DrawFour => 56 + color,
+        face => 13 * color + FACES.iter().position(|&f| f == face).unwrap(),
+    }
+}
+
+/// Where `action`, taken by `seat` at a table of `players`, is in the action space.
+pub fn action_index(action: Action, seat: usize, players: usize) -> usize {
+    match action {
+        Action::Play(card) => card_index(card),
+        Action::Draw => DRAW,
+        Action::Pass => PASS,
+        Action::Challenge => CHALLENGE,
+        Action::Accept => ACCEPT,
+        Action::Swap(with) => SWAP + (with + players - seat) % players - 1,
+    }
+}
+
+/// The action at `index` for `seat` at a table of `players`, if there is one.
+pub fn index_action(index: usize, seat: usize, players: usize) -> Option<Action> {
+    match index {
+        0..=51 => Some(Action::Play(Card::new(COLORS[index / 13], FACES[index % 13]))),
+        52..=55 => Some(Action::Play(Card::new(COLORS[index - 52], Face::ColorCard))),
+        56..=59 => Some(Action::Play(Card::new(COLORS[index - 56], Face::DrawFour))),
+        DRAW => Some(Action::Draw),
+        PASS => Some(Action::Pass),
+        CHALLENGE => Some(Action::Challenge),
+        ACCEPT => Some(Action::Accept),
+        _ if index >= SWAP && index < SWAP + players - 1 => Some(Action::Swap((seat + index - SWAP + 1) % players)),
+        _ => None,
+    }
+}
+
+/// Who plays each seat and by which rules.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnvConfig {
+    pub rules: Rules,
+    /// `None` for a seat the learning agent plays, or the name of a built-in strategy.
+    pub seats: Vec<Option<String>>,
+    /// Games still going after this many turns are cut short with no winner.
+    pub max_turns: usize,
+    /// Reward for winning. Everyone else gets `loss`.
+    pub win: f32,
+    pub loss: f32,
+    /// How observations are turned into numbers for `Step::encoded`.
+    pub encoding: Encoding,
+}
+
+impl EnvConfig {
+    /// One agent in the first seat against `opponents`.
+    pub fn against(opponents: &[&str], rules: Rules) -> EnvConfig {
+        let mut seats = vec![None];
+        seats.extend(opponents.iter().map(|name| Some(name.to_string())));
+        EnvConfig { rules, seats, max_turns: 2000, win: 1.0, loss: -1.0, encoding: Encoding::LATEST }
+    }
+}
+
+/// A training environment in the style of OpenAI Gym.
+///
+/// Seats without a strategy belong to agents. `reset` and `step` play the
+/// other seats until an agent has to move, and every observation is that
+/// agent's view. Agents always call UNO when they should.
+pub struct Env {
+    config: EnvConfig,
+    driver: Option<Driver>,
+    //events already reported in a step
+    reported: usize,
+}
+
+/// What comes back from `reset` and `step`.
+#[derive(Clone, Debug)]
+pub struct Step {
+    /// The view of the agent now to move, or of the last agent to move once the game is done.
+    pub observation: PlayerView,
+    /// `observation` and the recent plays, encoded by `EnvConfig::encoding`.
+    pub encoded: Vec<f32>,
+    /// The reward for the agent that took the step.
+    pub reward: f32,
+    /// The reward for every seat, agent or not.
+    pub rewards: Vec<f32>,
+    pub done: bool,
+    pub info: Info,
+}
+
+#[derive(Clone, Debug)]
+pub struct Info {
+    /// The seat `observation` belongs to.
+    pub seat: usize,
+    /// Which of the `ACTIONS` that seat may take. All false once the game is done.
+    pub mask: Vec<bool>,
+    /// What the stepped action did. `None` after `reset`.
+    pub result: Option<TurnResult>,
+    /// Everything that happened since the last step.
+    pub events: Vec<Event>,
+    pub winner: Option<usize>,
+    /// The game ran out of turns before anyone won.
+    pub truncated: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EnvError {
+    NoAgents,
+    UnknownBot(String),
+    Rules(RuleError),
+    /// `step` before `reset`.
+    NotStarted,
+    /// `step` after the game was done.
+    GameOver,
+    /// Not one of the `ACTIONS`, or not allowed right now.
+    IllegalAction(usize),
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvError::NoAgents => write!(f, "at least one seat has to be left for an agent"),
+            EnvError::UnknownBot(name) => write!(f, "unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")),
+            EnvError::Rules(e) => write!(f, "{}", e),
+            EnvError::NotStarted => write!(f, "the environment has to be reset before the first step"),
+            EnvError::GameOver => write!(f, "the game is over, reset the environment"),
+            EnvError::IllegalAction(index) => write!(f, "action {} is not allowed right now", index),
+        }
+    }
+}
+
+impl Error for EnvError {}
+
+impl Env {
+    pub fn new(config: EnvConfig) -> Result<Env, EnvError> {
+        if config.seats.iter().all(Option::is_some) {
+            return Err(EnvError::NoAgents);
+        }
+        if let Some(name) = config.seats.iter().flatten().find(|name| strategy::by_name(name, 0).is_none()) {
+            return Err(EnvError::UnknownBot(name.clone()));
+        }
+        config.rules.check(config.seats.len()).map_err(EnvError::Rules)?;
+        Ok(Env { config, driver: None, reported: 0 })
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// The game being played, once `reset` has been called.
+    pub fn uno(&self) -> Option<&Uno> {
+        self.driver.as_ref().map(Driver::uno)
+    }
+
+    /// Deals a new game with `seed` and plays until an agent has to move.
+    pub fn reset(&mut self, seed: u64) -> Step {
+        let players = (0..self.config.seats.len()).map(|seat| Player::new(seat + 1)).collect();
+        let uno = Uno::create_game_with(players, self.config.rules.clone(), seed);
+        let seats = self.config.seats.iter().enumerate()
+            .map(|(seat, name)| match name {
+                Some(name) => Seat::Computer(strategy::by_name(name, bot_seed(seed, seat)).unwrap()),
+                None => Seat::Human,
+            })
+            .collect();
+
+        self.driver = Some(Driver::new(uno, seats));
+        self.reported = 0;
+        let to_move = self.advance();
+        self.report(to_move, None, None)
+    }
+
+    /// Makes the move at `action` for the agent to move, then plays until an
+    /// agent has to move again or the game is done.
+    pub fn step(&mut self, action: usize) -> Result<Step, EnvError> {
+        if self.driver.is_none() {
+            return Err(EnvError::NotStarted);
+        }
+        if self.done() {
+            return Err(EnvError::GameOver);
+        }
+
+        let driver = self.driver.as_mut().unwrap();
+        let uno = driver.uno();
+        let seat = uno.current_seat();
+        if !mask(uno).get(action).copied().unwrap_or(false) {
+            return Err(EnvError::IllegalAction(action));
+        }
+
+        let action = index_action(action, seat, uno.players().len()).unwrap();
+        if let Action::Play(_) = action {
+            if uno.players()[seat].get_hand().len() == 2 {
+                driver.call_uno();
+            }
+        }
+        let result = driver.apply(action);
+        let to_move = self.advance();
+        Ok(self.report(to_move, Some(seat), Some(result)))
+    }
+
+    /// Which of the `ACTIONS` the agent to move may take.
+    pub fn mask(&self) -> Vec<bool> {
+        match self.uno() {
+            Some(uno) if !self.done() => mask(uno),
+            _ => vec![false; ACTIONS],
+        }
+    }
+
+    /// Whether the game has been won or cut short.
+    pub fn done(&self) -> bool {
+        match self.uno() {
+            Some(uno) => uno.winner().is_some() || uno.current_turn() > self.config.max_turns,
+            None => true,
+        }
+    }
+
+    /// Plays computer seats until an agent is up, returning their seat. `None` once the game is done.
+    fn advance(&mut self) -> Option<usize> {
+        let max_turns = self.config.max_turns;
+        let driver = self.driver.as_mut().unwrap();
+        while driver.uno().current_turn() <= max_turns && driver.step().is_some() {}
+        match driver.uno().winner() {
+            None if driver.uno().current_turn() <= max_turns => Some(driver.uno().current_seat()),
+            _ => None,
+        }
+    }
+
+    /// `to_move` is the agent now to move, and `acted` the one that took the step.
+    fn report(&mut self, to_move: Option<usize>, acted: Option<usize>, result: Option<TurnResult>) -> Step {
+        let done = self.done();
+        let uno = self.driver.as_ref().unwrap().uno();
+        let winner = uno.winner();
+
+        let first_agent = self.config.seats.iter().position(Option::is_none).unwrap();
+        let seat = to_move.or(acted).unwrap_or(first_agent);
+        let rewards: Vec<f32> = (0..uno.players().len())
+            .map(|s| match winner {
+                Some(w) if w == s => self.config.win,
+                Some(_) => self.config.loss,
+                None => 0.0,
+            })
+            .collect();
+
+        let events = uno.events()[self.reported..].to_vec();
+        self.reported = uno.events().len();
+
+        let observation = uno.view(seat);
+        Step {
+            encoded: self.config.encoding.encode(&observation, uno.events()),
+            observation,
+            reward: rewards[acted.unwrap_or(seat)],
+            done,
+            info: Info {
+                seat,
+                mask: self.mask(),
+                result,
+                events,
+                winner,
+                truncated: done && winner.is_none(),
+            },
+            rewards,
+        }
+    }
+}
+
+fn mask(uno: &Uno) -> Vec<bool> {
+    let seat = uno.current_seat();
+    let players = uno.players().len();
+    let mut mask = vec![false; ACTIONS];
+    for action in uno.legal_actions() {
+        match action {
+            Action::Play(card) if card.face.is_wild() => {
+                COLORS.iter().for_each(|&color| mask[card_index(Card::new(color, card.face))] = true);
+            },
+            action => mask[action_index(action, seat, players)] = true,
+        }
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shuffle::Xorshift;
+
+    /// Takes a random legal action, as an untrained agent would.
+    fn random_action(mask: &[bool], rng: &mut Xorshift) -> usize {
+        let legal: Vec<usize> = (0..ACTIONS).filter(|&i| mask[i]).collect();
+        legal[rng.below(legal.len())]
+    }
+
+    #[test]
+    fn indexes_round_trip() {
+        for players in 2..=8 {
+            for index in 0..ACTIONS {
+                match index_action(index, 1, players) {
+                    Some(action) => {
+                        assert_eq!(index, action_index(action, 1, players));
+                        if let Action::Play(card) = action {
+                            assert_eq!(index, card_index(card));
+                        }
+                    },
+                    None => assert!(index >= SWAP + players - 1),
+                }
+            }
+        }
+        assert_eq!(Some(Action::Swap(0)), index_action(SWAP + 1, 1, 3));
+        assert_eq!(None, index_action(SWAP + 2, 1, 3));
+        assert_eq!(58, card_index("BD".parse().unwrap()));
+    }
+
+    #[test]
+    fn agent_plays_against_bots() {
+        let mut env = Env::new(EnvConfig::against(&["greedy", "random"], Rules::seven_o())).unwrap();
+        let mut rng = Xorshift::new(3);
+        for seed in 0..20 {
+            let mut step = env.reset(seed);
+            while !step.done {
+                assert_eq!(0, step.info.seat);
+                assert_eq!(0, env.uno().unwrap().current_seat());
+                step = env.step(random_action(&step.info.mask, &mut rng)).unwrap();
+            }
+            assert_eq!(Err(EnvError::GameOver), env.step(DRAW).map(|_| ()));
+            assert!(step.info.mask.iter().all(|&legal| !legal));
+            match step.info.winner {
+                Some(0) => assert_eq!(1.0, step.reward),
+                Some(_) => assert_eq!(-1.0, step.reward),
+                None => assert!(step.info.truncated),
+            }
+        }
+    }
+
+    #[test]
+    fn agents_take_turns() {
+        let config = EnvConfig { seats: vec![None, None], ..EnvConfig::against(&[], Rules::official()) };
+        let mut env = Env::new(config).unwrap();
+        let mut rng = Xorshift::new(9);
+        let mut step = env.reset(4);
+        let mut seen = [false; 2];
+        while !step.done {
+            seen[step.info.seat] = true;
+            assert_eq!(step.info.seat, env.uno().unwrap().current_seat());
+            step = env.step(random_action(&step.info.mask, &mut rng)).unwrap();
+        }
+        assert_eq!([true, true], seen);
+        assert_eq!(0.0, step.rewards.iter().sum::<f32>());
+    }
+
+    #[test]
+    fn bad_steps() {
+        assert_eq!(Err(EnvError::NoAgents), Env::new(EnvConfig { seats: vec![Some("basic".into()); 2], ..EnvConfig::against(&[], Rules::official()) }).map(|_| ()));
+        assert!(Env::new(EnvConfig::against(&["nobody"], Rules::official())).is_err());
+
+        let mut env = Env::new(EnvConfig::against(&["basic"], Rules::official())).unwrap();
+        assert_eq!(Err(EnvError::NotStarted), env.step(DRAW).map(|_| ()));
+        let step = env.reset(1);
+        assert!(step.info.mask[DRAW]);
+        assert_eq!(Err(EnvError::IllegalAction(PASS)), env.step(PASS).map(|_| ()));
+        assert_eq!(Err(EnvError::IllegalAction(ACTIONS)), env.step(ACTIONS).map(|_| ()));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::belief::{kind, KINDS};
+use crate::direction::GameDirection;
+use crate::event::Event;
+use crate::uno::Phase;
+use crate::view::PlayerView;
+
+/// Opponent slots, enough for the largest table.
+const OPPONENTS: usize = 7;
+/// Plays remembered, most recent first.
+const HISTORY: usize = 8;
+/// One remembered play: its kind, its color, and who played it.
+const PLAY: usize = KINDS + 4 + 1 + OPPONENTS;
+
+/// A fixed layout for turning a `PlayerView` into numbers.
+///
+/// A layout never changes once released. Changes go into a new variant, so
+/// a model trained on one keeps working as long as it asks for that one.
+///
+/// `V1` is 666 numbers long:
+///
+/// | offset  | length | contents                                                    |
+/// |---------|--------|-------------------------------------------------------------|
+/// | 0       | 54     | cards held of each kind                                     |
+/// | 54      | 54     | the kind of the top discard, one-hot                        |
+/// | 108     | 4      | the color in play (declared for a wild), one-hot            |
+/// | 112     | 1      | 1 clockwise, -1 counterclockwise                            |
+/// | 113     | 7      | hand size of the seats 1-7 places after this one            |
+/// | 120     | 7      | 1 where those seats exist                                   |
+/// | 127     | 1      | own hand size                                               |
+/// | 128     | 1      | cards pending from draw cards                               |
+/// | 129     | 1      | cards left in the draw pile                                 |
+/// | 130     | 5      | phase, one-hot: play, drawn, challenge, swap, game over     |
+/// | 135     | 3      | rules: stacking, seven-o, challenge Draw Four               |
+/// | 138     | 8 x 66 | the last 8 plays, most recent first                         |
+///
+/// Card kinds are `13 * color + face` for colored cards, 52 for the Wild Card
+/// and 53 for the Draw Four, with colors and faces counted as in `ACTIONS`.
+/// Each remembered play is 54 for its kind, 4 for its color, then who played
+/// it: 1 for this player, otherwise the seat 1-7 places after this one. A
+/// play not made yet is all zeros. Counts are not scaled.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    V1,
+}
+
+impl Encoding {
+    pub const LATEST: Encoding = Encoding::V1;
+
+    pub fn version(self) -> u32 {
+        match self {
+            Encoding::V1 => 1,
+        }
+    }
+
+    /// How many numbers `encode` returns.
+    pub fn size(self) -> usize {
+        match self {
+            Encoding::V1 => 138 + HISTORY * PLAY,
+        }
+    }
+
+    /// Encodes `view`, taking recent plays from `events`, the game's public event log.
+    pub fn encode(self, view: &PlayerView, events: &[Event]) -> Vec<f32> {
+        match self {
+            Encoding::V1 => encode_v1(view, events),
+        }
+    }
+}
+
+/// How many places after `seat` `other` sits, in seat order.
+fn offset(seat: usize, other: usize, players: usize) -> usize {
+    (other + players - seat) % players
+}
+
+fn one_hot(out: &mut Vec<f32>, len: usize, at: usize) {
+    out.extend((0..len).map(|i| if i == at { 1.0 } else { 0.0 }));
+}
+
+fn encode_v1(view: &PlayerView, events: &[Event]) -> Vec<f32> {
+    let players = view.hand_sizes.len();
+    let mut out = Vec::with_capacity(Encoding::V1.size());
+    let mut hand = [0.0; KINDS];
+    view.hand.iter().for_each(|&card| hand[kind(card)] += 1.0);
+    out.extend_from_slice(&hand);
+    one_hot(&mut out, KINDS, kind(view.top));
+    one_hot(&mut out, 4, view.top.color as usize);
+    out.push(match view.direction {
+        GameDirection::Clockwise => 1.0,
+        GameDirection::CounterClockwise => -1.0,
+    });
+
+    let others = |step: usize| (step < players).then(|| (view.seat + step) % players);
+    out.extend((1..=OPPONENTS).map(|step| others(step).map(|seat| view.hand_sizes[seat] as f32).unwrap_or(0.0)));
+    out.extend((1..=OPPONENTS).map(|step| if others(step).is_some() { 1.0 } else { 0.0 }));
+
+    out.push(view.hand.len() as f32);
+    out.push(view.pending_draw as f32);
+    out.push(view.draw_pile as f32);
+    let phase = match view.phase {
+        Phase::Play => 0,
+        Phase::Drawn => 1,
+        Phase::Challenge { .. } => 2,
+        Phase::Swap => 3,
+        Phase::GameOver { .. } => 4,
+    };
+    one_hot(&mut out, 5, phase);
+    out.extend([view.rules.stacking, view.rules.seven_o, view.rules.challenge_draw_four].iter().map(|&on| if on { 1.0 } else { 0.0 }));
+
+    let plays = events.iter().rev().filter_map(|event| match *event {
+        Event::Played { seat, card } => Some((seat, card)),
+        _ => None,
+    });
+    let mut remembered = 0;
+    for (seat, card) in plays.take(HISTORY) {
+        one_hot(&mut out, KINDS, kind(card));
+        one_hot(&mut out, 4, card.color as usize);
+        one_hot(&mut out, 1 + OPPONENTS, offset(view.seat, seat, players));
+        remembered += 1;
+    }
+    out.resize(out.len() + (HISTORY - remembered) * PLAY, 0.0);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::Card;
+    use crate::rules::Rules;
+    use crate::uno::{Action, Uno};
+
+    #[test]
+    fn v1_layout() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into(), "C".into()], Rules::stacking(), 12);
+        uno.apply(Action::Draw);
+        let view = uno.view(1);
+        let encoded = Encoding::V1.encode(&view, uno.events());
+
+        assert_eq!(666, encoded.len());
+        assert_eq!(Encoding::V1.size(), encoded.len());
+        assert_eq!(view.hand.len() as f32, encoded[..54].iter().sum::<f32>());
+        assert_eq!(1.0, encoded[54 + kind(view.top)]);
+        assert_eq!(1.0, encoded[108 + view.top.color as usize]);
+        //seat 2 comes one place after seat 1, seat 0 two places
+        assert_eq!(&[view.hand_sizes[2] as f32, view.hand_sizes[0] as f32, 0.0], &encoded[113..116]);
+        assert_eq!(&[1.0, 1.0, 0.0], &encoded[120..123]);
+        //stacking, no challenges
+        assert_eq!(&[1.0, 0.0, 0.0], &encoded[135..138]);
+        //no plays yet, so no history
+        assert!(encoded[138..].iter().all(|&x| x == 0.0));
+    }
+
+    /// Pins every number of `V1` for one view, written out by hand from the
+    /// table above rather than worked out the way the encoder does.
+    #[test]
+    fn v1_golden() {
+        let card = |text: &str| text.parse::<Card>().unwrap();
+        let view = PlayerView {
+            seat: 1,
+            hand: vec![card("R5"), card("R5"), card("GC"), card("BD")],
+            top: card("Y7"),
+            discard: vec![card("Y7"), card("R3"), card("BS")],
+            direction: GameDirection::CounterClockwise,
+            current_seat: 1,
+            phase: Phase::Drawn,
+            drawn: Some(card("BD")),
+            pending_draw: 2,
+            hand_sizes: vec![3, 4, 6],
+            draw_pile: 40,
+            turn: 4,
+            rules: Rules { stacking: true, seven_o: false, challenge_draw_four: true, ..Rules::classic() },
+        };
+        let events = [
+            Event::Played { seat: 1, card: card("BS") },
+            Event::Played { seat: 0, card: card("R3") },
+            Event::Drew { seat: 2, count: 1, forced: false },
+            Event::Played { seat: 2, card: card("Y7") },
+        ];
+
+        let mut expected = vec![0.0; 666];
+        let set = [
+            //two red fives, a Wild Card and a Draw Four
+            (5, 2.0), (52, 1.0), (53, 1.0),
+            //yellow seven on top, yellow in play, counterclockwise
+            (100, 1.0), (111, 1.0), (112, -1.0),
+            //seat 2 holds 6 and seat 0 holds 3, and both exist
+            (113, 6.0), (114, 3.0), (120, 1.0), (121, 1.0),
+            //own hand, pending, draw pile, drawn phase, stacking and challenges
+            (127, 4.0), (128, 2.0), (129, 40.0), (131, 1.0), (135, 1.0), (137, 1.0),
+            //yellow seven by the next seat
+            (184, 1.0), (195, 1.0), (197, 1.0),
+            //red three by the seat after that
+            (207, 1.0), (258, 1.0), (264, 1.0),
+            //blue skip by this player
+            (306, 1.0), (326, 1.0), (328, 1.0),
+        ];
+        set.iter().for_each(|&(at, value)| expected[at] = value);
+        assert_eq!(expected, Encoding::V1.encode(&view, &events));
+    }
+
+    #[test]
+    fn remembers_recent_plays() {
+        let card: Card = "G7".parse().unwrap();
+        let events = [Event::Drew { seat: 0, count: 1, forced: false }, Event::Played { seat: 0, card }];
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 1);
+        let encoded = Encoding::V1.encode(&uno.view(1), &events);
+
+        let play = &encoded[138..138 + PLAY];
+        assert_eq!(1.0, play[kind(card)]);
+        assert_eq!(1.0, play[KINDS + 1]);
+        //seat 0 sits one place after seat 1
+        assert_eq!(1.0, play[KINDS + 4 + 1]);
+        assert!(encoded[138 + PLAY..].iter().all(|&x| x == 0.0));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::card::Card;
+
+/// Something that happened in a game, as seen by everyone at the table.
+///
+/// Events never reveal hidden cards: a draw says how many cards were drawn,
+/// not which.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Event {
+    Played { seat: usize, card: Card },
+    /// `forced` is set when the cards were a penalty rather than a choice.
+    Drew { seat: usize, count: usize, forced: bool },
+    Passed { seat: usize },
+    Skipped { seat: usize },
+    Challenged { seat: usize, offender: usize, bluffed: bool },
+    Swapped { seat: usize, with: usize },
+    /// Every hand moved one seat along in the direction of play.
+    Rotated,
+    UnoCalled { seat: usize },
+    /// The player got down to one card without calling UNO.
+    UnoPenalty { seat: usize },
+    /// The discard pile was shuffled back into the draw pile.
+    Reshuffled,
+    Won { seat: usize },
+}
+
+impl Event {
+    /// The seat that caused this event, if any.
+    pub fn seat(&self) -> Option<usize> {
+        match *self {
+            Event::Played { seat, .. }
+            | Event::Drew { seat, .. }
+            | Event::Passed { seat }
+            | Event::Skipped { seat }
+            | Event::Challenged { seat, .. }
+            | Event::Swapped { seat, .. }
+            | Event::UnoCalled { seat }
+            | Event::UnoPenalty { seat }
+            | Event::Won { seat } => Some(seat),
+            Event::Rotated | Event::Reshuffled => None,
+        }
+    }
+
+    /// Whether this reports a move `seat` chose themselves, which they already
+    /// learn about from its `TurnResult`.
+    pub fn is_move_by(&self, seat: usize) -> bool {
+        match *self {
+            Event::Played { .. } | Event::Passed { .. } | Event::Challenged { .. } | Event::Swapped { .. } => self.seat() == Some(seat),
+            Event::Drew { seat: drew, forced, .. } => drew == seat && !forced,
+            _ => false,
+        }
+    }
+}
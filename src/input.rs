@@ -0,0 +1,50 @@
+use crate::card::{Card, ParseCardError};
+use crate::uno::{Action, Phase};
+use crate::view::PlayerView;
+
+/// Something a person typed at the move prompt.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    Act(Action),
+    /// Call UNO before playing the next card.
+    CallUno,
+    /// Leave the game.
+    Exit,
+    /// Save the game to this file.
+    Save(String),
+    /// Replace the game with the one saved in this file.
+    Load(String),
+}
+
+/// Reads a move the way the terminal game accepts them: a card, `D`raw,
+/// `P`ass, `C`hallenge, `A`ccept, `UNO`, `EXIT`, `SAVE <file>` or
+/// `LOAD <file>`, and while swapping, a seat number from 1 or a name.
+/// Ignoring case throughout, except in file names.
+///
+/// `Ok(None)` means the line named nobody to swap with and can be asked again.
+pub fn parse_command(line: &str, view: &PlayerView, names: &[String]) -> Result<Option<Command>, ParseCardError> {
+    let line = line.trim();
+    if let Some((word, file)) = line.split_once(char::is_whitespace) {
+        match word.to_uppercase().as_ref() {
+            "SAVE" => return Ok(Some(Command::Save(file.trim().to_string()))),
+            "LOAD" => return Ok(Some(Command::Load(file.trim().to_string()))),
+            _ => {},
+        }
+    }
+    Ok(Some(match line.to_uppercase().as_ref() {
+        "EXIT" => Command::Exit,
+        "D" | "DRAW" => Command::Act(Action::Draw),
+        "P" | "PASS" => Command::Act(Action::Pass),
+        "C" | "CHALLENGE" => Command::Act(Action::Challenge),
+        "A" | "ACCEPT" => Command::Act(Action::Accept),
+        "UNO" => Command::CallUno,
+        _ if view.phase == Phase::Swap => match line.parse::<usize>() {
+            Ok(other) if other > 0 => Command::Act(Action::Swap(other - 1)),
+            _ => match names.iter().position(|name| name.eq_ignore_ascii_case(line)) {
+                Some(other) => Command::Act(Action::Swap(other)),
+                None => return Ok(None),
+            },
+        },
+        _ => Command::Act(Action::Play(line.parse::<Card>()?)),
+    }))
+}
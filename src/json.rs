@@ -0,0 +1,394 @@
+use std::error::Error;
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+/// A JSON value, enough to speak JSON over HTTP without other crates.
+///
+/// Objects keep their keys in the order they were written.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// An object from key and value pairs.
+    pub fn object<K: ToString>(fields: Vec<(K, Json)>) -> Json {
+        Json::Object(fields.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    /// The value of `key` if this is an object that has it.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The number, if it is a whole one that fits.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Json::Number(value) if value >= 0.0 && value.fract() == 0.0 && value < u64::MAX as f64 => Some(value as u64),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(value: bool) -> Json {
+        Json::Bool(value)
+    }
+}
+
+impl From<usize> for Json {
+    fn from(value: usize) -> Json {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<u64> for Json {
+    fn from(value: u64) -> Json {
+        Json::Number(value as f64)
+    }
+}
+
+impl From<f64> for Json {
+    fn from(value: f64) -> Json {
+        Json::Number(value)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(value: &str) -> Json {
+        Json::String(value.into())
+    }
+}
+
+impl From<String> for Json {
+    fn from(value: String) -> Json {
+        Json::String(value)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(items: Vec<T>) -> Json {
+        Json::Array(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(value: Option<T>) -> Json {
+        value.map_or(Json::Null, Into::into)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl Display for Json {
+    /// Compact JSON, without any spaces between tokens.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            //JSON has no infinities or NaN
+            Json::Number(value) if !value.is_finite() => f.write_str("null"),
+            Json::Number(value) => write!(f, "{}", value),
+            Json::String(text) => write_string(f, text),
+            Json::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            },
+            Json::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            },
+        }
+    }
+}
+
+/// Text that isn't valid JSON, with the byte offset where reading stopped.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct JsonError {
+    pub offset: usize,
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid JSON at byte {}", self.offset)
+    }
+}
+
+impl Error for JsonError {}
+
+/// Objects and arrays may nest this deep, so hostile input can't exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a [u8],
+    at: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> JsonError {
+        JsonError { offset: self.at }
+    }
+
+    fn skip_space(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.text.get(self.at) {
+            self.at += 1;
+        }
+    }
+
+    fn expect(&mut self, word: &str) -> Result<(), JsonError> {
+        match self.text[self.at..].starts_with(word.as_bytes()) {
+            true => {
+                self.at += word.len();
+                Ok(())
+            },
+            false => Err(self.error()),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.skip_space();
+        let value = match self.text.get(self.at) {
+            Some(b'n') => self.expect("null").map(|_| Json::Null)?,
+            Some(b't') => self.expect("true").map(|_| Json::Bool(true))?,
+            Some(b'f') => self.expect("false").map(|_| Json::Bool(false))?,
+            Some(b'"') => Json::String(self.string()?),
+            Some(b'[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                self.skip_space();
+                if self.text.get(self.at) == Some(&b']') {
+                    self.at += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    self.skip_space();
+                    match self.text.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b']') => break,
+                        _ => return Err(self.error()),
+                    }
+                }
+                self.at += 1;
+                Json::Array(items)
+            },
+            Some(b'{') => {
+                self.at += 1;
+                let mut fields = Vec::new();
+                self.skip_space();
+                if self.text.get(self.at) == Some(&b'}') {
+                    self.at += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_space();
+                    if self.text.get(self.at) != Some(&b'"') {
+                        return Err(self.error());
+                    }
+                    let key = self.string()?;
+                    self.skip_space();
+                    self.expect(":")?;
+                    fields.push((key, self.value(depth + 1)?));
+                    self.skip_space();
+                    match self.text.get(self.at) {
+                        Some(b',') => self.at += 1,
+                        Some(b'}') => break,
+                        _ => return Err(self.error()),
+                    }
+                }
+                self.at += 1;
+                Json::Object(fields)
+            },
+            Some(b'-' | b'0'..=b'9') => self.number()?,
+            _ => return Err(self.error()),
+        };
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.at;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.text.get(self.at) {
+            self.at += 1;
+        }
+        let text = std::str::from_utf8(&self.text[start..self.at]).unwrap();
+        text.parse().map(Json::Number).map_err(|_| JsonError { offset: start })
+    }
+
+    /// Four hex digits of a `\u` escape.
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let digits = self.text.get(self.at..self.at + 4).ok_or_else(|| self.error())?;
+        let value = std::str::from_utf8(digits).ok().and_then(|digits| u32::from_str_radix(digits, 16).ok()).ok_or_else(|| self.error())?;
+        self.at += 4;
+        Ok(value)
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.at += 1;
+        let mut text = String::new();
+        loop {
+            let start = self.at;
+            while let Some(&byte) = self.text.get(self.at) {
+                if byte == b'"' || byte == b'\\' || byte < 0x20 {
+                    break;
+                }
+                self.at += 1;
+            }
+            //the input came from a str and stops only at ASCII, so this is whole characters
+            text.push_str(std::str::from_utf8(&self.text[start..self.at]).unwrap());
+
+            match self.text.get(self.at) {
+                Some(b'"') => {
+                    self.at += 1;
+                    return Ok(text);
+                },
+                Some(b'\\') => {
+                    self.at += 1;
+                    let escape = *self.text.get(self.at).ok_or_else(|| self.error())?;
+                    self.at += 1;
+                    match escape {
+                        b'"' => text.push('"'),
+                        b'\\' => text.push('\\'),
+                        b'/' => text.push('/'),
+                        b'b' => text.push('\u{8}'),
+                        b'f' => text.push('\u{c}'),
+                        b'n' => text.push('\n'),
+                        b'r' => text.push('\r'),
+                        b't' => text.push('\t'),
+                        b'u' => {
+                            let mut code = self.hex()?;
+                            //a character outside the basic plane comes as a surrogate pair
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect("\\u")?;
+                                let low = self.hex()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error());
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            text.push(char::from_u32(code).ok_or_else(|| self.error())?);
+                        },
+                        _ => return Err(self.error()),
+                    }
+                },
+                _ => return Err(self.error()),
+            }
+        }
+    }
+}
+
+impl FromStr for Json {
+    type Err = JsonError;
+
+    fn from_str(text: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser { text: text.as_bytes(), at: 0 };
+        let value = parser.value(0)?;
+        parser.skip_space();
+        match parser.at == text.len() {
+            true => Ok(value),
+            false => Err(parser.error()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let value = Json::object(vec![
+            ("name", Json::from("Ann \"the\" \\ best\n\u{1}é😀")),
+            ("seat", Json::from(2usize)),
+            ("ratio", Json::from(-0.25)),
+            ("ready", Json::from(true)),
+            ("hand", Json::from(vec!["R5", "GC"])),
+            ("drawn", Json::from(None::<&str>)),
+            ("empty", Json::object(Vec::<(&str, Json)>::new())),
+        ]);
+        let text = value.to_string();
+        assert_eq!(value, text.parse().unwrap());
+        assert!(text.starts_with(r#"{"name":"Ann \"the\" \\ best\n\u0001é😀","seat":2,"#));
+
+        let value: Json = r#" { "a" : [ 1 , 2.5e1 , "\u00e9\ud83d\ude00\/" ] , "b" : null } "#.parse().unwrap();
+        assert_eq!(Some(25.0), value.get("a").and_then(|a| a.as_array()).and_then(|a| a[1].as_f64()));
+        assert_eq!(Some("é😀/"), value.get("a").unwrap().as_array().unwrap()[2].as_str());
+        assert_eq!(Some(&Json::Null), value.get("b"));
+        assert_eq!(Some(1), value.get("a").unwrap().as_array().unwrap()[0].as_u64());
+    }
+
+    #[test]
+    fn rejects_what_is_not_json() {
+        for text in ["", "{", "[1,]", "{\"a\" 1}", "{a:1}", "tru", "\"open", "\"\\x\"", "\"\\ud83d\"", "1 2", "[\"\u{1}\"]"].iter() {
+            assert!(text.parse::<Json>().is_err(), "{:?} parsed", text);
+        }
+        assert_eq!(Err(JsonError { offset: 5 }), "[1, 2".parse::<Json>());
+        let deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+        assert!(deep.parse::<Json>().is_err());
+    }
+}
@@ -1,11 +1,94 @@
+//! Everything but the cards, deck, rules and game (`Uno`, `PlayerView`,
+//! `Locale`) needs the default `std` feature. Without it the crate is
+//! `no_std` and needs only `alloc`; games are then always seeded by the
+//! caller, with `Uno::create_game_with`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std as alloc;
+
+#[cfg(feature = "std")]
+mod api;
+#[cfg(feature = "std")]
+mod balance;
+#[cfg(feature = "std")]
+pub mod belief;
 mod card;
 mod deck;
 mod direction;
+#[cfg(feature = "std")]
+mod driver;
+#[cfg(feature = "std")]
+mod engine;
+#[cfg(feature = "std")]
+pub mod env;
+mod event;
+#[cfg(feature = "std")]
+mod input;
+#[cfg(feature = "std")]
+mod json;
+#[cfg(feature = "std")]
+mod lobby;
+mod locale;
+#[cfg(feature = "std")]
+mod odds;
 mod player;
+#[cfg(feature = "std")]
+mod protocol;
+#[cfg(feature = "std")]
+mod render;
+mod rules;
+#[cfg(feature = "std")]
+mod sim;
+#[cfg(feature = "std")]
+pub mod strategy;
+#[cfg(feature = "std")]
+mod timer;
+#[cfg(feature = "std")]
+mod tournament;
 mod uno;
+mod view;
 
+#[cfg(feature = "std")]
+pub use crate::api::Api;
+#[cfg(feature = "std")]
+pub use crate::balance::{Comparison, RuleReport};
 pub use crate::card::*;
 pub use crate::color::*;
 pub use crate::deck::Deck;
+pub use crate::direction::GameDirection;
+#[cfg(feature = "std")]
+pub use crate::driver::{decide, decide_from_view, play_out, Driver, Seat};
+#[cfg(feature = "std")]
+pub use crate::engine::{Engine, EngineError, EngineLine, RefereeLine};
+pub use crate::event::Event;
+#[cfg(feature = "std")]
+pub use crate::input::{parse_command, Command};
+#[cfg(feature = "std")]
+pub use crate::json::{Json, JsonError};
+#[cfg(feature = "std")]
+pub use crate::lobby::{Lobby, LobbyError, Occupant, RoomInfo, RoomStatus};
+pub use crate::locale::{Locale, Message};
+#[cfg(feature = "std")]
+pub use crate::odds::{Rollouts, WinOdds};
 pub use crate::player::Player;
-pub use crate::uno::{TurnResult, Uno};
+#[cfg(feature = "std")]
+pub use crate::protocol::{new_token, ClientLine, ProtocolError, ServerLine};
+#[cfg(feature = "std")]
+pub use crate::render::{RenderMode, Renderer};
+pub use crate::rules::{RuleArgs, RuleError, Rules};
+#[cfg(feature = "std")]
+pub use crate::sim::{play_game, Batch, BatchError, BotTally, GameRecord, Summary};
+#[cfg(feature = "std")]
+pub use crate::strategy::Strategy;
+#[cfg(feature = "std")]
+pub use crate::timer::{parse_seconds, Clock, ManualClock, OnTimeout, SystemClock, TimeControl, TimeError, Timers};
+#[cfg(feature = "std")]
+pub use crate::tournament::{leaderboard, Entrant, Format, Standing, Standings, Tournament, TournamentError};
+pub use crate::uno::{Action, Phase, TurnResult, Uno};
+#[cfg(feature = "std")]
+pub use crate::uno::{SaveError, SAVE_VERSION};
+pub use crate::view::PlayerView;
@@ -0,0 +1,558 @@
+use crate::driver::{Driver, Seat};
+use crate::event::Event;
+use crate::player::Player;
+use crate::rules::{RuleError, Rules};
+use crate::strategy;
+use crate::uno::{Action, TurnResult, Uno};
+use crate::view::PlayerView;
+
+use shuffle::Xorshift;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Letters join codes are made of, leaving out I and O, which look like digits.
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 4;
+
+/// Who sits in a seat of a room.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Occupant {
+    Open,
+    Human { name: String, ready: bool },
+    /// A built-in strategy, by name.
+    Bot(String),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RoomStatus {
+    /// Seats are being claimed and the host can still change the rules.
+    Waiting,
+    Playing,
+    Finished { winner: usize },
+}
+
+/// A room as the lobby lists it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct RoomInfo {
+    pub code: String,
+    pub host: String,
+    pub seats: Vec<Occupant>,
+    pub rules: Rules,
+    pub status: RoomStatus,
+}
+
+struct Room {
+    host: String,
+    seats: Vec<Occupant>,
+    rules: Rules,
+    //set once the game starts
+    driver: Option<Driver>,
+    //when the room was last used, and when its game ended
+    touched: Instant,
+    finished: Option<Instant>,
+}
+
+impl Room {
+    fn status(&self) -> RoomStatus {
+        match self.driver.as_ref().map(|driver| driver.uno().winner()) {
+            None => RoomStatus::Waiting,
+            Some(None) => RoomStatus::Playing,
+            Some(Some(winner)) => RoomStatus::Finished { winner },
+        }
+    }
+
+    fn seat_of(&self, name: &str) -> Option<usize> {
+        self.seats.iter().position(|occupant| matches!(occupant, Occupant::Human { name: seated, .. } if seated == name))
+    }
+
+    fn seated(&self, name: &str) -> Result<usize, LobbyError> {
+        self.seat_of(name).ok_or_else(|| LobbyError::NotSeated(name.to_string()))
+    }
+
+    fn check_host(&self, name: &str) -> Result<(), LobbyError> {
+        match self.host == name {
+            true => Ok(()),
+            false => Err(LobbyError::NotHost(name.to_string())),
+        }
+    }
+
+    fn check_waiting(&self) -> Result<(), LobbyError> {
+        match self.driver {
+            None => Ok(()),
+            Some(_) => Err(LobbyError::Started),
+        }
+    }
+
+    fn driver(&mut self) -> Result<&mut Driver, LobbyError> {
+        self.driver.as_mut().ok_or(LobbyError::NotStarted)
+    }
+
+    /// Everyone has to agree again after anything they agreed to changes.
+    fn unready(&mut self) {
+        for occupant in self.seats.iter_mut() {
+            if let Occupant::Human { ready, .. } = occupant {
+                *ready = false;
+            }
+        }
+    }
+
+    fn check_seat(&self, seat: usize) -> Result<(), LobbyError> {
+        match seat < self.seats.len() {
+            true => Ok(()),
+            false => Err(LobbyError::NoSuchSeat(seat)),
+        }
+    }
+
+    /// Lets the bots play until someone has to move, noting when the game ends.
+    fn run(&mut self) {
+        if let Some(driver) = self.driver.as_mut() {
+            driver.run();
+            if driver.uno().winner().is_some() && self.finished.is_none() {
+                self.finished = Some(Instant::now());
+            }
+        }
+    }
+
+    fn expired(&self, now: Instant, idle_ttl: Duration, finished_ttl: Duration) -> bool {
+        let over = self.finished.is_some_and(|finished| now.saturating_duration_since(finished) >= finished_ttl);
+        over || now.saturating_duration_since(self.touched) >= idle_ttl
+    }
+}
+
+/// Rooms of players waiting for or playing games, found by a join code.
+///
+/// Every room has a lock of its own, and the lobby's own lock is only held
+/// long enough to find a room, so any number of games can be played at once
+/// from different threads without waiting on each other. Players are known
+/// by name within a room, and the one who created it is its host: only they
+/// can change the rules, put bots in seats, start the game or close it.
+pub struct Lobby {
+    rooms: Mutex<HashMap<String, Arc<Mutex<Room>>>>,
+    rng: Mutex<Xorshift>,
+}
+
+impl Lobby {
+    /// `seed` picks the join codes.
+    pub fn new(seed: u64) -> Lobby {
+        Lobby { rooms: Mutex::new(HashMap::new()), rng: Mutex::new(Xorshift::new(seed)) }
+    }
+
+    /// Opens a room with `seats` seats, the host sitting in the first, and
+    /// returns its join code.
+    pub fn create(&self, host: &str, seats: usize, rules: Rules) -> Result<String, LobbyError> {
+        rules.check(seats).map_err(LobbyError::Rules)?;
+        let mut occupants = vec![Occupant::Open; seats];
+        occupants[0] = Occupant::Human { name: host.to_string(), ready: false };
+        let room = Room { host: host.to_string(), seats: occupants, rules, driver: None, touched: Instant::now(), finished: None };
+
+        let mut rooms = self.rooms.lock().unwrap();
+        let mut rng = self.rng.lock().unwrap();
+        let code = loop {
+            let code: String = (0..CODE_LENGTH).map(|_| CODE_LETTERS[rng.below(CODE_LETTERS.len())] as char).collect();
+            if !rooms.contains_key(&code) {
+                break code;
+            }
+        };
+        rooms.insert(code.clone(), Arc::new(Mutex::new(room)));
+        Ok(code)
+    }
+
+    /// Every room, in order of their codes.
+    pub fn rooms(&self) -> Vec<RoomInfo> {
+        let rooms: Vec<(String, Arc<Mutex<Room>>)> = self.rooms.lock().unwrap().iter().map(|(code, room)| (code.clone(), Arc::clone(room))).collect();
+        let mut infos: Vec<RoomInfo> = rooms.iter().map(|(code, room)| info(code, &room.lock().unwrap())).collect();
+        infos.sort_by(|a, b| a.code.cmp(&b.code));
+        infos
+    }
+
+    pub fn room(&self, code: &str) -> Result<RoomInfo, LobbyError> {
+        let code = normalize(code);
+        self.with(&code, |room| Ok(info(&code, room)))
+    }
+
+    /// Sits `name` in `seat`, or in the first open one, and returns the seat.
+    /// Someone already seated moves.
+    pub fn claim(&self, code: &str, name: &str, seat: Option<usize>) -> Result<usize, LobbyError> {
+        self.with(code, |room| {
+            room.check_waiting()?;
+            let current = room.seat_of(name);
+            let seat = match seat {
+                Some(seat) => {
+                    room.check_seat(seat)?;
+                    match &room.seats[seat] {
+                        Occupant::Open => seat,
+                        _ if current == Some(seat) => return Ok(seat),
+                        _ => return Err(LobbyError::SeatTaken(seat)),
+                    }
+                },
+                None => match current {
+                    Some(seat) => return Ok(seat),
+                    None => room.seats.iter().position(|occupant| *occupant == Occupant::Open).ok_or(LobbyError::Full)?,
+                },
+            };
+            if let Some(current) = current {
+                room.seats[current] = Occupant::Open;
+            }
+            room.seats[seat] = Occupant::Human { name: name.to_string(), ready: false };
+            Ok(seat)
+        })
+    }
+
+    /// Gives up `name`'s seat before the game starts. If the host leaves, the
+    /// next person seated becomes host, and a room nobody is left in closes.
+    pub fn leave(&self, code: &str, name: &str) -> Result<(), LobbyError> {
+        let empty = self.with(code, |room| {
+            room.check_waiting()?;
+            let seat = room.seated(name)?;
+            room.seats[seat] = Occupant::Open;
+            if room.host == name {
+                match room.seats.iter().find_map(|occupant| match occupant {
+                    Occupant::Human { name, .. } => Some(name.clone()),
+                    _ => None,
+                }) {
+                    Some(next) => room.host = next,
+                    None => return Ok(true),
+                }
+            }
+            Ok(false)
+        })?;
+        if empty {
+            self.rooms.lock().unwrap().remove(&normalize(code));
+        }
+        Ok(())
+    }
+
+    pub fn ready(&self, code: &str, name: &str, ready: bool) -> Result<(), LobbyError> {
+        self.with(code, |room| {
+            room.check_waiting()?;
+            let seat = room.seated(name)?;
+            if let Occupant::Human { ready: seat_ready, .. } = &mut room.seats[seat] {
+                *seat_ready = ready;
+            }
+            Ok(())
+        })
+    }
+
+    /// Lets the host change the rules, which everyone then has to ready up for again.
+    pub fn set_rules(&self, code: &str, host: &str, rules: Rules) -> Result<(), LobbyError> {
+        self.with(code, |room| {
+            room.check_host(host)?;
+            room.check_waiting()?;
+            rules.check(room.seats.len()).map_err(LobbyError::Rules)?;
+            room.rules = rules;
+            room.unready();
+            Ok(())
+        })
+    }
+
+    /// Lets the host put a built-in bot in a seat, or empty it with `None`,
+    /// removing whoever sat there. The host can't remove themselves.
+    pub fn set_seat(&self, code: &str, host: &str, seat: usize, bot: Option<&str>) -> Result<(), LobbyError> {
+        self.with(code, |room| {
+            room.check_host(host)?;
+            room.check_waiting()?;
+            room.check_seat(seat)?;
+            if room.seat_of(host) == Some(seat) {
+                return Err(LobbyError::SeatTaken(seat));
+            }
+            room.seats[seat] = match bot {
+                Some(bot) if strategy::by_name(bot, 0).is_some() => Occupant::Bot(bot.trim().to_lowercase()),
+                Some(bot) => return Err(LobbyError::UnknownBot(bot.to_string())),
+                None => Occupant::Open,
+            };
+            Ok(())
+        })
+    }
+
+    /// Lets the host start the game once every seat is filled and every
+    /// person in one is ready. Bots play until the first person's turn.
+    pub fn start(&self, code: &str, host: &str, seed: u64) -> Result<(), LobbyError> {
+        self.with(code, |room| {
+            room.check_host(host)?;
+            room.check_waiting()?;
+            let mut players = Vec::new();
+            let mut controllers = Vec::new();
+            for (seat, occupant) in room.seats.iter().enumerate() {
+                match occupant {
+                    Occupant::Open => return Err(LobbyError::EmptySeat(seat)),
+                    Occupant::Human { name, ready: false } => return Err(LobbyError::NotReady(name.clone())),
+                    Occupant::Human { name, .. } => {
+                        players.push(Player::new(name));
+                        controllers.push(Seat::Human);
+                    },
+                    Occupant::Bot(bot) => {
+                        players.push(Player::new(bot));
+                        controllers.push(Seat::Computer(strategy::by_name(bot, seed.wrapping_add(seat as u64)).unwrap()));
+                    },
+                }
+            }
+
+            room.driver = Some(Driver::new(Uno::create_game_with(players, room.rules.clone(), seed), controllers));
+            room.run();
+            Ok(())
+        })
+    }
+
+    /// Makes `name`'s move, calling UNO first if asked, then lets the bots
+    /// play until someone has to move again.
+    pub fn play(&self, code: &str, name: &str, action: Action, uno: bool) -> Result<TurnResult, LobbyError> {
+        self.with(code, |room| {
+            let seat = room.seated(name)?;
+            let driver = room.driver()?;
+            if driver.uno().winner().is_some() {
+                return Err(LobbyError::Finished);
+            }
+            if driver.uno().current_seat() != seat {
+                return Err(LobbyError::NotYourTurn);
+            }
+            if uno {
+                driver.call_uno();
+            }
+            let result = driver.apply(action);
+            room.run();
+            Ok(result)
+        })
+    }
+
+    /// What `name` can see of the game.
+    pub fn view(&self, code: &str, name: &str) -> Result<PlayerView, LobbyError> {
+        self.with(code, |room| {
+            let seat = room.seated(name)?;
+            Ok(room.driver()?.uno().view(seat))
+        })
+    }
+
+    /// What `name` can do, nothing when it isn't their turn.
+    pub fn legal_actions(&self, code: &str, name: &str) -> Result<Vec<Action>, LobbyError> {
+        self.with(code, |room| {
+            let seat = room.seated(name)?;
+            let uno = room.driver()?.uno();
+            match uno.winner().is_none() && uno.current_seat() == seat {
+                true => Ok(uno.legal_actions()),
+                false => Ok(Vec::new()),
+            }
+        })
+    }
+
+    /// The game's events from index `since` on.
+    pub fn events(&self, code: &str, since: usize) -> Result<Vec<Event>, LobbyError> {
+        self.with(code, |room| Ok(room.driver()?.uno().events().get(since..).unwrap_or(&[]).to_vec()))
+    }
+
+    /// Lets the host close the room whatever state it is in.
+    pub fn close(&self, code: &str, host: &str) -> Result<(), LobbyError> {
+        self.with(code, |room| room.check_host(host))?;
+        self.rooms.lock().unwrap().remove(&normalize(code));
+        Ok(())
+    }
+
+    /// Closes every room whose game is over, returning their codes.
+    pub fn close_finished(&self) -> Vec<String> {
+        self.close_expired(Duration::MAX, Duration::ZERO)
+    }
+
+    /// Closes every room nobody has used for `idle_ttl`, or whose game has
+    /// been over for `finished_ttl`, returning their codes.
+    pub fn close_expired(&self, idle_ttl: Duration, finished_ttl: Duration) -> Vec<String> {
+        //copy the rooms out so that no room is locked under the lobby's lock
+        let rooms: Vec<(String, Arc<Mutex<Room>>)> = self.rooms.lock().unwrap().iter().map(|(code, room)| (code.clone(), Arc::clone(room))).collect();
+        let now = Instant::now();
+        let mut expired: Vec<(String, Arc<Mutex<Room>>)> = rooms.into_iter()
+            //a room that is busy is in use, so only idle ones are looked at
+            .filter(|(_, room)| room.try_lock().is_ok_and(|room| room.expired(now, idle_ttl, finished_ttl)))
+            .collect();
+        expired.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut rooms = self.rooms.lock().unwrap();
+        //a code may have been given to a new room since
+        expired.retain(|(code, room)| rooms.get(code).is_some_and(|open| Arc::ptr_eq(open, room)));
+        expired.iter().for_each(|(code, _)| drop(rooms.remove(code)));
+        expired.into_iter().map(|(code, _)| code).collect()
+    }
+
+    /// Runs `f` on a room with it locked, having let go of the lobby first.
+    fn with<R, F: FnOnce(&mut Room) -> Result<R, LobbyError>>(&self, code: &str, f: F) -> Result<R, LobbyError> {
+        let code = normalize(code);
+        let room = self.rooms.lock().unwrap().get(&code).cloned().ok_or(LobbyError::NoSuchRoom(code))?;
+        let mut room = room.lock().unwrap();
+        room.touched = Instant::now();
+        f(&mut room)
+    }
+}
+
+fn normalize(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+fn info(code: &str, room: &Room) -> RoomInfo {
+    RoomInfo { code: code.to_string(), host: room.host.clone(), seats: room.seats.clone(), rules: room.rules.clone(), status: room.status() }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LobbyError {
+    NoSuchRoom(String),
+    /// Only the host may do that.
+    NotHost(String),
+    NotSeated(String),
+    NoSuchSeat(usize),
+    SeatTaken(usize),
+    Full,
+    UnknownBot(String),
+    EmptySeat(usize),
+    NotReady(String),
+    Started,
+    NotStarted,
+    Finished,
+    NotYourTurn,
+    Rules(RuleError),
+}
+
+impl Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LobbyError::NoSuchRoom(code) => write!(f, "there is no room {}", code),
+            LobbyError::NotHost(name) => write!(f, "{} is not the host", name),
+            LobbyError::NotSeated(name) => write!(f, "{} has no seat in this room", name),
+            LobbyError::NoSuchSeat(seat) => write!(f, "there is no seat {}", seat),
+            LobbyError::SeatTaken(seat) => write!(f, "seat {} is taken", seat),
+            LobbyError::Full => write!(f, "every seat is taken"),
+            LobbyError::UnknownBot(name) => write!(f, "unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")),
+            LobbyError::EmptySeat(seat) => write!(f, "seat {} is empty", seat),
+            LobbyError::NotReady(name) => write!(f, "{} is not ready", name),
+            LobbyError::Started => write!(f, "the game has already started"),
+            LobbyError::NotStarted => write!(f, "the game has not started"),
+            LobbyError::Finished => write!(f, "the game is over"),
+            LobbyError::NotYourTurn => write!(f, "it is not your turn"),
+            LobbyError::Rules(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for LobbyError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    #[test]
+    fn rooms_fill_and_start() {
+        let lobby = Lobby::new(1);
+        let code = lobby.create("Ann", 3, Rules::default()).unwrap();
+        assert_eq!(CODE_LENGTH, code.len());
+        assert_eq!(Err(LobbyError::NotStarted), lobby.view(&code, "Ann").map(|_| ()));
+
+        assert_eq!(Ok(1), lobby.claim(&code.to_lowercase(), "Bo", None));
+        assert_eq!(Err(LobbyError::SeatTaken(0)), lobby.claim(&code, "Cy", Some(0)));
+        assert_eq!(Ok(2), lobby.claim(&code, "Bo", Some(2)));
+        assert_eq!(Ok(1), lobby.claim(&code, "Cy", None));
+        assert_eq!(Err(LobbyError::Full), lobby.claim(&code, "Di", None));
+        lobby.leave(&code, "Cy").unwrap();
+        lobby.set_seat(&code, "Ann", 1, Some("Greedy")).unwrap();
+
+        lobby.ready(&code, "Ann", true).unwrap();
+        assert_eq!(Err(LobbyError::NotReady("Bo".into())), lobby.start(&code, "Ann", 5));
+        lobby.ready(&code, "Bo", true).unwrap();
+        assert!(lobby.set_rules(&code, "Ann", Rules::preset("stacking").unwrap()).is_ok());
+        assert_eq!(Err(LobbyError::NotReady("Ann".into())), lobby.start(&code, "Ann", 5));
+        lobby.ready(&code, "Ann", true).unwrap();
+        lobby.ready(&code, "Bo", true).unwrap();
+        lobby.start(&code, "Ann", 5).unwrap();
+
+        let room = lobby.room(&code).unwrap();
+        assert_eq!(RoomStatus::Playing, room.status);
+        assert!(room.rules.stacking);
+        assert_eq!(Occupant::Bot("greedy".into()), room.seats[1]);
+        assert_eq!(Err(LobbyError::Started), lobby.claim(&code, "Di", None));
+        assert_eq!(2, lobby.view(&code, "Bo").unwrap().seat);
+    }
+
+    #[test]
+    fn only_the_host_controls_the_room() {
+        let lobby = Lobby::new(2);
+        let code = lobby.create("Ann", 2, Rules::default()).unwrap();
+        lobby.claim(&code, "Bo", None).unwrap();
+        assert_eq!(Err(LobbyError::NotHost("Bo".into())), lobby.set_seat(&code, "Bo", 0, None));
+        assert_eq!(Err(LobbyError::NotHost("Bo".into())), lobby.start(&code, "Bo", 0));
+        assert_eq!(Err(LobbyError::NotHost("Bo".into())), lobby.close(&code, "Bo"));
+        assert_eq!(Err(LobbyError::SeatTaken(0)), lobby.set_seat(&code, "Ann", 0, None));
+        assert_eq!(Err(LobbyError::UnknownBot("nobody".into())), lobby.set_seat(&code, "Ann", 1, Some("nobody")));
+        assert!(matches!(lobby.set_rules(&code, "Ann", Rules { hand_size: 40, ..Rules::default() }), Err(LobbyError::Rules(_))));
+
+        //the host's role passes on when they leave, and the room closes when everyone has
+        lobby.leave(&code, "Ann").unwrap();
+        assert_eq!("Bo", lobby.room(&code).unwrap().host);
+        lobby.leave(&code, "Bo").unwrap();
+        assert_eq!(Err(LobbyError::NoSuchRoom(code.clone())), lobby.room(&code));
+        assert!(lobby.rooms().is_empty());
+    }
+
+    #[test]
+    fn rooms_play_at_once() {
+        let lobby = Arc::new(Lobby::new(3));
+        let moves = thread::scope(|scope| {
+            let handles: Vec<_> = (0..8).map(|i| {
+                let lobby = Arc::clone(&lobby);
+                scope.spawn(move || {
+                    let host = format!("host{}", i);
+                    let code = lobby.create(&host, 2, Rules::default()).unwrap();
+                    lobby.set_seat(&code, &host, 1, Some("basic")).unwrap();
+                    lobby.ready(&code, &host, true).unwrap();
+                    lobby.start(&code, &host, i).unwrap();
+                    let mut moves = 0;
+                    while lobby.room(&code).unwrap().status == RoomStatus::Playing {
+                        let action = lobby.legal_actions(&code, &host).unwrap()[0];
+                        lobby.play(&code, &host, action, true).unwrap();
+                        moves += 1;
+                    }
+                    assert_eq!(Err(LobbyError::Finished), lobby.play(&code, &host, Action::Draw, false));
+                    moves
+                })
+            }).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>()
+        });
+
+        assert!(moves.iter().all(|&moves| moves > 0));
+        assert_eq!(8, lobby.rooms().len());
+        assert_eq!(8, lobby.close_finished().len());
+        assert!(lobby.rooms().is_empty());
+    }
+
+    #[test]
+    fn a_busy_room_holds_up_no_other() {
+        let lobby = Arc::new(Lobby::new(4));
+        let busy = lobby.create("Ann", 2, Rules::default()).unwrap();
+        let other = lobby.create("Bo", 2, Rules::default()).unwrap();
+        let room = lobby.rooms.lock().unwrap()[&busy].clone();
+        let held = room.lock().unwrap();
+
+        //everything about the other room, and the lobby as a whole, still goes through
+        let (done, finished) = mpsc::channel();
+        let worker = Arc::clone(&lobby);
+        thread::spawn(move || {
+            worker.set_seat(&other, "Bo", 1, Some("basic")).unwrap();
+            worker.ready(&other, "Bo", true).unwrap();
+            worker.start(&other, "Bo", 1).unwrap();
+            worker.view(&other, "Bo").unwrap();
+            worker.create("Cy", 3, Rules::default()).unwrap();
+            worker.close_finished();
+            done.send(worker.rooms.lock().unwrap().len()).unwrap();
+        });
+        assert_eq!(Ok(3), finished.recv_timeout(Duration::from_secs(10)));
+        drop(held);
+        assert_eq!(RoomStatus::Waiting, lobby.room(&busy).unwrap().status);
+    }
+
+    #[test]
+    fn idle_rooms_close() {
+        let lobby = Lobby::new(5);
+        let code = lobby.create("Ann", 2, Rules::default()).unwrap();
+        assert!(lobby.close_expired(Duration::from_secs(60), Duration::ZERO).is_empty());
+        assert_eq!(vec![code], lobby.close_expired(Duration::ZERO, Duration::ZERO));
+        assert!(lobby.rooms().is_empty());
+    }
+}
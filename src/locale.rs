@@ -0,0 +1,417 @@
+use crate::card::{color::Color, face::Face, Card, ParseCardError, ParseCardErrorKind};
+use crate::direction::GameDirection;
+use crate::event::Event;
+#[cfg(feature = "std")]
+use crate::uno::SaveError;
+use crate::uno::TurnResult;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+use core::time::Duration;
+
+/// The languages card names and game messages can be shown in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+/// Everything the engine or a front end may need to tell a player.
+#[derive(Clone, Debug)]
+pub enum Message<'a> {
+    Turn(usize),
+    DrawPileSize(usize),
+    DiscardPileSize(usize),
+    LastCardPlayed(Card),
+    LastCardColor(Color),
+    TopOfDiscard(&'a str),
+    Direction(GameDirection),
+    PlayersTurn(&'a str),
+    YourCards(&'a str),
+    YourMove,
+    YouDrew(Card),
+    YouPlayed(Card),
+    InvalidMove { played: Card, on: Card },
+    NotHoldingCard(Card),
+    /// The move can't be made at this point of the turn.
+    NotNow,
+    YouWon,
+    CouldNotParse { input: &'a str, error: &'a ParseCardError },
+    Goodbye,
+    YouDrewCards(usize),
+    YouPassed,
+    PlayDrawnOrPass(Card),
+    PenaltyPending(usize),
+    ChallengePrompt(&'a str),
+    ChallengeResult { bluffed: bool },
+    SwapPrompt,
+    UnoCalled,
+    Played { name: &'a str, card: Card },
+    Drew { name: &'a str, count: usize },
+    Passed(&'a str),
+    Challenged { name: &'a str, bluffed: bool },
+    Accepted(&'a str),
+    SwappedHands { name: &'a str, with: &'a str },
+    PlayerWon(&'a str),
+    Skipped(&'a str),
+    HandsRotated,
+    UnoCalledBy(&'a str),
+    UnoPenalty(&'a str),
+    Reshuffled,
+    PassDevice(&'a str),
+    SinceYourLastTurn,
+    HideHand,
+    /// Someone joined a networked game and was given a seat, counting from 1.
+    Seated { name: &'a str, seat: usize },
+    /// The chance a player holds each color, in `Color` order, and a wild.
+    HoldsOdds { name: &'a str, colors: [f64; 4], wild: f64 },
+    /// How many cards of each color the draw pile probably holds, in `Color` order.
+    DrawPileHolds([f64; 4]),
+    /// A player's chance of winning with the ends of its 95% confidence interval.
+    WinChance { name: &'a str, chance: f64, low: f64, high: f64 },
+    /// The connection to the server was lost and this is the attempt to get it back, counting from 1.
+    Reconnecting(usize),
+    /// The seat was taken back after losing the connection.
+    Rejoined { name: &'a str, seat: usize },
+    /// How long the player has left to make their move.
+    TimeLeft(Duration),
+    OutOfTime(&'a str),
+    /// The game was saved to this file.
+    Saved(&'a str),
+    /// The game saved in this file has taken the place of the one being played.
+    Loaded(&'a str),
+    #[cfg(feature = "std")]
+    SaveFailed { path: &'a str, error: &'a SaveError },
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Spanish];
+
+    /// The short code accepted by `Locale::from_str`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Spanish => "es",
+        }
+    }
+
+    pub fn color_name(self, color: Color) -> &'static str {
+        match (self, color) {
+            (Locale::English, Color::Red) => "Red",
+            (Locale::English, Color::Green) => "Green",
+            (Locale::English, Color::Blue) => "Blue",
+            (Locale::English, Color::Yellow) => "Yellow",
+            (Locale::Spanish, Color::Red) => "Rojo",
+            (Locale::Spanish, Color::Green) => "Verde",
+            (Locale::Spanish, Color::Blue) => "Azul",
+            (Locale::Spanish, Color::Yellow) => "Amarillo",
+        }
+    }
+
+    pub fn face_name(self, face: Face) -> &'static str {
+        match self {
+            Locale::English => match face {
+                Face::DrawFour => "Draw Four",
+                Face::ColorCard => "Wild Card",
+                Face::DrawTwo => "Draw Two",
+                Face::Skip => "Skip",
+                Face::Reverse => "Reverse",
+                Face::Zero => "Zero",
+                Face::One => "One",
+                Face::Two => "Two",
+                Face::Three => "Three",
+                Face::Four => "Four",
+                Face::Five => "Five",
+                Face::Six => "Six",
+                Face::Seven => "Seven",
+                Face::Eight => "Eight",
+                Face::Nine => "Nine",
+            },
+            Locale::Spanish => match face {
+                Face::DrawFour => "Comodín Roba Cuatro",
+                Face::ColorCard => "Comodín",
+                Face::DrawTwo => "Roba Dos",
+                Face::Skip => "Salta",
+                Face::Reverse => "Reversa",
+                Face::Zero => "Cero",
+                Face::One => "Uno",
+                Face::Two => "Dos",
+                Face::Three => "Tres",
+                Face::Four => "Cuatro",
+                Face::Five => "Cinco",
+                Face::Six => "Seis",
+                Face::Seven => "Siete",
+                Face::Eight => "Ocho",
+                Face::Nine => "Nueve",
+            },
+        }
+    }
+
+    /// The full name of a card. Wild cards are named without their color.
+    pub fn card_name(self, card: Card) -> String {
+        let face = self.face_name(card.face);
+        match (self, card.face) {
+            (_, Face::DrawFour) | (_, Face::ColorCard) => face.into(),
+            (Locale::English, _) => format!("{} {}", self.color_name(card.color), face),
+            (Locale::Spanish, _) => format!("{} {}", face, self.color_name(card.color)),
+        }
+    }
+
+    /// A card on the discard pile: wild cards show the color that was declared.
+    fn played_name(self, card: Card) -> String {
+        match card.face.is_wild() {
+            true => format!("{} ({})", self.card_name(card), self.color_name(card.color)),
+            false => self.card_name(card),
+        }
+    }
+
+    pub fn direction_name(self, direction: GameDirection) -> &'static str {
+        match (self, direction) {
+            (Locale::English, GameDirection::Clockwise) => "Clockwise",
+            (Locale::English, GameDirection::CounterClockwise) => "Counter-clockwise",
+            (Locale::Spanish, GameDirection::Clockwise) => "Horario",
+            (Locale::Spanish, GameDirection::CounterClockwise) => "Antihorario",
+        }
+    }
+
+    /// Describes a public event. `names` are the player names in seat order.
+    pub fn event<S: AsRef<str>>(self, event: &Event, names: &[S]) -> String {
+        let name = |seat: usize| names[seat].as_ref();
+        let message = match *event {
+            Event::Played { seat, card } => Message::Played { name: name(seat), card },
+            Event::Drew { seat, count, .. } => Message::Drew { name: name(seat), count },
+            Event::Passed { seat } => Message::Passed(name(seat)),
+            Event::Skipped { seat } => Message::Skipped(name(seat)),
+            Event::Challenged { seat, bluffed, .. } => Message::Challenged { name: name(seat), bluffed },
+            Event::Swapped { seat, with } => Message::SwappedHands { name: name(seat), with: name(with) },
+            Event::Rotated => Message::HandsRotated,
+            Event::UnoCalled { seat } => Message::UnoCalledBy(name(seat)),
+            Event::UnoPenalty { seat } => Message::UnoPenalty(name(seat)),
+            Event::Reshuffled => Message::Reshuffled,
+            Event::Won { seat } => Message::PlayerWon(name(seat)),
+        };
+        self.message(&message)
+    }
+
+    /// Tells a player what came of their own move. `seat` is theirs and
+    /// `names` are the player names in seat order.
+    pub fn result<S: AsRef<str>>(self, result: &TurnResult, seat: usize, names: &[S]) -> String {
+        let message = match *result {
+            TurnResult::Success(card) => Message::YouPlayed(card),
+            TurnResult::InvalidMove(on, played) => Message::InvalidMove { played, on },
+            TurnResult::NotHoldingCard(card) => Message::NotHoldingCard(card),
+            TurnResult::Drew(ref cards) if cards.len() == 1 => Message::YouDrew(cards[0]),
+            TurnResult::Drew(ref cards) => Message::YouDrewCards(cards.len()),
+            TurnResult::Passed => Message::YouPassed,
+            TurnResult::Challenged { bluffed } => Message::ChallengeResult { bluffed },
+            TurnResult::Swapped(other) => Message::SwappedHands { name: names[seat].as_ref(), with: names[other].as_ref() },
+            TurnResult::IllegalAction(_) => Message::NotNow,
+            TurnResult::GameOver => Message::YouWon,
+        };
+        self.message(&message)
+    }
+
+    fn parse_error(self, error: &ParseCardError) -> String {
+        let column = error.span().start + 1;
+        match (self, error.kind()) {
+            (Locale::English, _) => error.to_string(),
+            (Locale::Spanish, ParseCardErrorKind::Empty) => "no se indicó ninguna carta".into(),
+            (Locale::Spanish, ParseCardErrorKind::UnknownWord(word)) => format!("palabra desconocida \"{}\" en la columna {}", word, column),
+            (Locale::Spanish, ParseCardErrorKind::ExpectedDrawAmount) => format!("se esperaba \"two\" o \"four\" tras \"draw\" en la columna {}", column),
+            (Locale::Spanish, ParseCardErrorKind::DuplicateColor) => format!("segundo color en la columna {}", column),
+            (Locale::Spanish, ParseCardErrorKind::DuplicateFace) => format!("segunda figura en la columna {}", column),
+            (Locale::Spanish, ParseCardErrorKind::MissingColor) => "falta el color".into(),
+            (Locale::Spanish, ParseCardErrorKind::MissingFace) => "falta la figura".into(),
+        }
+    }
+
+    /// "Red 80%, Green 12%, ..." for chances given in `Color` order.
+    fn percents(self, chances: &[f64; 4]) -> String {
+        let colors = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+        colors.iter().zip(chances.iter()).map(|(&color, chance)| format!("{} {:.0}%", self.color_name(color), 100.0 * chance)).collect::<Vec<_>>().join(", ")
+    }
+
+    /// "Red 8, Green 5, ..." for counts given in `Color` order.
+    fn counts(self, counts: &[f64; 4]) -> String {
+        let colors = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+        colors.iter().zip(counts.iter()).map(|(&color, count)| format!("{} {:.0}", self.color_name(color), count)).collect::<Vec<_>>().join(", ")
+    }
+
+    pub fn message(self, message: &Message) -> String {
+        match self {
+            Locale::English => match message {
+                Message::Turn(turn) => format!("Turn {}", turn),
+                Message::DrawPileSize(n) => format!("Cards in draw pile: {}", n),
+                Message::DiscardPileSize(n) => format!("Cards in discard pile: {}", n),
+                Message::LastCardPlayed(card) => format!("Last card played: {}", self.card_name(*card)),
+                Message::LastCardColor(color) => format!("Color of last card: {}", self.color_name(*color)),
+                Message::TopOfDiscard(card) => format!("Top of discard pile: {}", card),
+                Message::Direction(direction) => format!("Game direction: {}", self.direction_name(*direction)),
+                Message::PlayersTurn(name) => format!("Player {}'s turn", name),
+                Message::YourCards(hand) => format!("Your cards: {}", hand),
+                Message::YourMove => "Your move?".into(),
+                Message::YouDrew(card) => format!("You drew a card: {}", self.card_name(*card)),
+                Message::YouPlayed(card) => format!("You played a {}!", self.card_name(*card)),
+                Message::InvalidMove { played, on } => format!(
+                    "Invalid move! You cannot play a {} on a {}!",
+                    self.card_name(*played),
+                    self.card_name(*on)
+                ),
+                Message::NotHoldingCard(card) => format!("You don't have a {}!", self.card_name(*card)),
+                Message::NotNow => "You can't do that now.".into(),
+                Message::YouWon => "Game over! You won!".into(),
+                Message::CouldNotParse { input, error } => format!("Error parsing card from {}: {}", input, self.parse_error(error)),
+                Message::Goodbye => "Thanks for playing! Good bye.".into(),
+                Message::YouDrewCards(n) => format!("You drew {} cards.", n),
+                Message::YouPassed => "You passed.".into(),
+                Message::PlayDrawnOrPass(card) => format!("You may play the {} you drew, or pass with P.", self.card_name(*card)),
+                Message::PenaltyPending(n) => format!("Stack a draw card, or draw {} cards with D.", n),
+                Message::ChallengePrompt(name) => format!("{} played a Draw Four on you. Challenge it (C) or accept it (A)?", name),
+                Message::ChallengeResult { bluffed: true } => "Challenge won! They were bluffing.".into(),
+                Message::ChallengeResult { bluffed: false } => "Challenge lost! You draw six.".into(),
+                Message::SwapPrompt => "Pick a player to swap hands with:".into(),
+                Message::UnoCalled => "UNO!".into(),
+                Message::Played { name, card } => format!("{} played a {}.", name, self.played_name(*card)),
+                Message::Drew { name, count: 1 } => format!("{} drew a card.", name),
+                Message::Drew { name, count } => format!("{} drew {} cards.", name, count),
+                Message::Passed(name) => format!("{} passed.", name),
+                Message::Challenged { name, bluffed: true } => format!("{} challenged the Draw Four and caught a bluff.", name),
+                Message::Challenged { name, bluffed: false } => format!("{} challenged the Draw Four and lost.", name),
+                Message::Accepted(name) => format!("{} accepted the Draw Four.", name),
+                Message::SwappedHands { name, with } => format!("{} swapped hands with {}.", name, with),
+                Message::PlayerWon(name) => format!("Game over! {} won!", name),
+                Message::Skipped(name) => format!("{} was skipped.", name),
+                Message::HandsRotated => "Every hand moved along one seat.".into(),
+                Message::UnoCalledBy(name) => format!("{} called UNO!", name),
+                Message::UnoPenalty(name) => format!("{} forgot to call UNO.", name),
+                Message::Reshuffled => "The discard pile was shuffled into the draw pile.".into(),
+                Message::PassDevice(name) => format!("Pass the device to {}, then press Enter.", name),
+                Message::SinceYourLastTurn => "Since your last turn:".into(),
+                Message::HideHand => "Press Enter to hide your hand.".into(),
+                Message::Seated { name, seat } => format!("You joined as {} in seat {}.", name, seat),
+                Message::HoldsOdds { name, colors, wild } => format!("{} probably holds: {}, a wild {:.0}%", name, self.percents(colors), 100.0 * wild),
+                Message::DrawPileHolds(colors) => format!("The draw pile holds about: {}", self.counts(colors)),
+                Message::WinChance { name, chance, low, high } => format!("{} wins {:.0}% of the time ({:.0}-{:.0}%)", name, 100.0 * chance, 100.0 * low, 100.0 * high),
+                Message::Reconnecting(attempt) => format!("Lost the connection, reconnecting (attempt {})...", attempt),
+                Message::Rejoined { name, seat } => format!("You are back as {} in seat {}.", name, seat),
+                Message::TimeLeft(left) => format!("Time left: {}s", left.as_nanos().div_ceil(1_000_000_000)),
+                Message::OutOfTime(name) => format!("{} ran out of time.", name),
+                Message::Saved(path) => format!("Game saved to {}.", path),
+                Message::Loaded(path) => format!("Game loaded from {}.", path),
+                #[cfg(feature = "std")]
+                Message::SaveFailed { path, error } => format!("Could not use {}: {}", path, error),
+            },
+            Locale::Spanish => match message {
+                Message::Turn(turn) => format!("Turno {}", turn),
+                Message::DrawPileSize(n) => format!("Cartas en el mazo: {}", n),
+                Message::DiscardPileSize(n) => format!("Cartas en el descarte: {}", n),
+                Message::LastCardPlayed(card) => format!("Última carta jugada: {}", self.card_name(*card)),
+                Message::LastCardColor(color) => format!("Color de la última carta: {}", self.color_name(*color)),
+                Message::TopOfDiscard(card) => format!("Carta en el descarte: {}", card),
+                Message::Direction(direction) => format!("Sentido del juego: {}", self.direction_name(*direction)),
+                Message::PlayersTurn(name) => format!("Turno de {}", name),
+                Message::YourCards(hand) => format!("Tus cartas: {}", hand),
+                Message::YourMove => "¿Tu jugada?".into(),
+                Message::YouDrew(card) => format!("Robaste una carta: {}", self.card_name(*card)),
+                Message::YouPlayed(card) => format!("¡Jugaste {}!", self.card_name(*card)),
+                Message::InvalidMove { played, on } => format!(
+                    "¡Jugada inválida! No puedes jugar {} sobre {}.",
+                    self.card_name(*played),
+                    self.card_name(*on)
+                ),
+                Message::NotHoldingCard(card) => format!("¡No tienes {}!", self.card_name(*card)),
+                Message::NotNow => "No puedes hacer eso ahora.".into(),
+                Message::YouWon => "¡Fin del juego! ¡Ganaste!".into(),
+                Message::CouldNotParse { input, error } => format!("No se pudo leer una carta de {}: {}", input, self.parse_error(error)),
+                Message::Goodbye => "¡Gracias por jugar! Adiós.".into(),
+                Message::YouDrewCards(n) => format!("Robaste {} cartas.", n),
+                Message::YouPassed => "Pasaste.".into(),
+                Message::PlayDrawnOrPass(card) => format!("Puedes jugar {} que robaste, o pasar con P.", self.card_name(*card)),
+                Message::PenaltyPending(n) => format!("Acumula una carta de robo, o roba {} cartas con D.", n),
+                Message::ChallengePrompt(name) => format!("{} te jugó un Comodín Roba Cuatro. ¿Desafiarlo (C) o aceptarlo (A)?", name),
+                Message::ChallengeResult { bluffed: true } => "¡Desafío ganado! Estaba faroleando.".into(),
+                Message::ChallengeResult { bluffed: false } => "¡Desafío perdido! Robas seis.".into(),
+                Message::SwapPrompt => "Elige con quién intercambiar la mano:".into(),
+                Message::UnoCalled => "¡UNO!".into(),
+                Message::Played { name, card } => format!("{} jugó {}.", name, self.played_name(*card)),
+                Message::Drew { name, count: 1 } => format!("{} robó una carta.", name),
+                Message::Drew { name, count } => format!("{} robó {} cartas.", name, count),
+                Message::Passed(name) => format!("{} pasó.", name),
+                Message::Challenged { name, bluffed: true } => format!("{} desafió el Roba Cuatro y descubrió el farol.", name),
+                Message::Challenged { name, bluffed: false } => format!("{} desafió el Roba Cuatro y perdió.", name),
+                Message::Accepted(name) => format!("{} aceptó el Roba Cuatro.", name),
+                Message::SwappedHands { name, with } => format!("{} intercambió la mano con {}.", name, with),
+                Message::PlayerWon(name) => format!("¡Fin del juego! ¡Ganó {}!", name),
+                Message::Skipped(name) => format!("{} perdió el turno.", name),
+                Message::HandsRotated => "Todas las manos avanzaron un asiento.".into(),
+                Message::UnoCalledBy(name) => format!("¡{} dijo UNO!", name),
+                Message::UnoPenalty(name) => format!("{} olvidó decir UNO.", name),
+                Message::Reshuffled => "El descarte se barajó de nuevo en el mazo.".into(),
+                Message::PassDevice(name) => format!("Pasa el dispositivo a {} y pulsa Enter.", name),
+                Message::SinceYourLastTurn => "Desde tu último turno:".into(),
+                Message::HideHand => "Pulsa Enter para ocultar tu mano.".into(),
+                Message::Seated { name, seat } => format!("Te has unido como {} en el asiento {}.", name, seat),
+                Message::HoldsOdds { name, colors, wild } => format!("{} probablemente tiene: {}, un comodín {:.0}%", name, self.percents(colors), 100.0 * wild),
+                Message::DrawPileHolds(colors) => format!("El mazo tiene más o menos: {}", self.counts(colors)),
+                Message::WinChance { name, chance, low, high } => format!("{} gana el {:.0}% de las veces ({:.0}-{:.0}%)", name, 100.0 * chance, 100.0 * low, 100.0 * high),
+                Message::Reconnecting(attempt) => format!("Se perdió la conexión, reconectando (intento {})...", attempt),
+                Message::Rejoined { name, seat } => format!("Has vuelto como {} en el asiento {}.", name, seat),
+                Message::TimeLeft(left) => format!("Tiempo restante: {} s", left.as_nanos().div_ceil(1_000_000_000)),
+                Message::OutOfTime(name) => format!("{} se quedó sin tiempo.", name),
+                Message::Saved(path) => format!("Partida guardada en {}.", path),
+                Message::Loaded(path) => format!("Partida cargada de {}.", path),
+                #[cfg(feature = "std")]
+                Message::SaveFailed { path, error } => format!("No se pudo usar {}: {}", path, error),
+            },
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Locale, Self::Err> {
+        match s.trim().to_lowercase().as_ref() {
+            "en" | "english" => Ok(Locale::English),
+            "es" | "spanish" | "español" | "espanol" => Ok(Locale::Spanish),
+            _ => Err("Locales must be one of en or es."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn english_names() {
+        let names = [
+            ("R0", "Red Zero"), ("G1", "Green One"), ("B2", "Blue Two"), ("Y3", "Yellow Three"), ("R4", "Red Four"),
+            ("G5", "Green Five"), ("B6", "Blue Six"), ("Y7", "Yellow Seven"), ("R8", "Red Eight"), ("G9", "Green Nine"),
+            ("BS", "Blue Skip"), ("YR", "Yellow Reverse"), ("RT", "Red Draw Two"), ("GC", "Wild Card"), ("BD", "Draw Four"),
+        ];
+        for &(short, name) in names.iter() {
+            let card: Card = short.parse().unwrap();
+            assert_eq!(name, Locale::English.card_name(card));
+            assert_eq!(name, card.display_name());
+        }
+    }
+
+    #[test]
+    fn spanish_names() {
+        assert_eq!("Siete Azul", Locale::Spanish.card_name(Card::new(Color::Blue, Face::Seven)));
+        assert_eq!("Comodín", Locale::Spanish.card_name(Card::new(Color::Red, Face::ColorCard)));
+        assert_eq!("Turno 3", Locale::Spanish.message(&Message::Turn(3)));
+    }
+
+    #[test]
+    fn locale_fromstr() {
+        for &locale in Locale::ALL.iter() {
+            assert_eq!(Ok(locale), locale.code().parse());
+        }
+        assert!("xx".parse::<Locale>().is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use crate::belief::Beliefs;
+use crate::driver::play_out;
+use crate::event::Event;
+use crate::sim::{bot_seed, in_parallel, BatchError};
+use crate::strategy;
+use crate::uno::{Phase, Uno};
+use crate::view::PlayerView;
+
+use shuffle::Xorshift;
+use std::fmt::{self, Display};
+
+/// Estimates everyone's chance of winning from one player's point of view,
+/// by dealing out the cards they can't see and playing the game to the end
+/// many times over.
+///
+/// The hidden cards are dealt as that player's `Beliefs` suggest, so the
+/// estimate only uses what they could know.
+#[derive(Clone, Debug)]
+pub struct Rollouts {
+    /// The strategy every seat plays the rollouts with, by the names `strategy::by_name` knows.
+    pub bot: String,
+    pub games: usize,
+    /// Rollout `i` is dealt with seed `seed + i`.
+    pub seed: u64,
+    /// Rollouts still going after this many more turns count as unfinished.
+    pub max_turns: usize,
+    /// Rollouts are shared out between this many threads. The results don't depend on it.
+    pub threads: usize,
+}
+
+impl Rollouts {
+    pub fn new<S: ToString>(bot: S) -> Rollouts {
+        Rollouts { bot: bot.to_string(), games: 500, seed: 0, max_turns: 2000, threads: 1 }
+    }
+
+    pub fn check(&self) -> Result<(), BatchError> {
+        match strategy::by_name(&self.bot, 0) {
+            Some(_) => Ok(()),
+            None => Err(BatchError::UnknownBot(self.bot.clone())),
+        }
+    }
+
+    /// Plays out the game `view` shows. `events` is the public event log up to
+    /// now, which tells what the other hands are likely to hold.
+    ///
+    /// Panics if `check` would fail.
+    pub fn estimate(&self, view: &PlayerView, events: &[Event]) -> WinOdds {
+        if let Err(e) = self.check() {
+            panic!("{}", e);
+        }
+
+        let mut beliefs = Beliefs::new();
+        events.iter().for_each(|event| beliefs.observe(event));
+        let estimate = beliefs.estimate(view);
+
+        let seats = view.hand_sizes.len();
+        let winners = match view.phase {
+            Phase::GameOver { winner } => vec![Some(winner); self.games],
+            _ => in_parallel(self.games, self.threads, |game| {
+                let seed = self.seed.wrapping_add(game as u64);
+                let uno = Uno::determinize_with(view, &estimate, &mut Xorshift::new(seed));
+                let strategies = (0..seats).map(|seat| strategy::by_name(&self.bot, bot_seed(seed, seat)).unwrap()).collect();
+                play_out(uno, strategies, view.turn + self.max_turns).0
+            }),
+        };
+
+        let mut wins = vec![0; seats];
+        winners.iter().flatten().for_each(|&seat| wins[seat] += 1);
+        WinOdds { games: self.games, unfinished: winners.iter().filter(|winner| winner.is_none()).count(), wins }
+    }
+}
+
+/// How a set of rollouts ended.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WinOdds {
+    pub games: usize,
+    /// Rollouts won from each seat.
+    pub wins: Vec<usize>,
+    /// Rollouts nobody won in time. They count against every seat.
+    pub unfinished: usize,
+}
+
+impl WinOdds {
+    /// The share of rollouts `seat` won.
+    pub fn chance(&self, seat: usize) -> f64 {
+        self.wins[seat] as f64 / self.games.max(1) as f64
+    }
+
+    /// The 95% confidence interval of `seat`'s chance, as a Wilson score
+    /// interval so it stays sensible near 0% and 100%.
+    pub fn bounds(&self, seat: usize) -> (f64, f64) {
+        let z: f64 = 1.96;
+        let n = self.games.max(1) as f64;
+        let p = self.chance(seat);
+        let centre = (p + z * z / (2.0 * n)) / (1.0 + z * z / n);
+        let margin = z / (1.0 + z * z / n) * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt();
+        ((centre - margin).max(0.0), (centre + margin).min(1.0))
+    }
+}
+
+impl Display for WinOdds {
+    /// One bar per seat, e.g. `seat 1  ############--------  61.2% (57.0-65.3)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const WIDTH: usize = 20;
+        for seat in 0..self.wins.len() {
+            let (low, high) = self.bounds(seat);
+            let filled = (self.chance(seat) * WIDTH as f64).round() as usize;
+            writeln!(
+                f,
+                "seat {}  {}{}  {:>5.1}% ({:.1}-{:.1})",
+                seat + 1,
+                "#".repeat(filled),
+                "-".repeat(WIDTH - filled),
+                100.0 * self.chance(seat),
+                100.0 * low,
+                100.0 * high
+            )?;
+        }
+        if self.unfinished > 0 {
+            writeln!(f, "{} of {} rollouts unfinished", self.unfinished, self.games)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{face::Face, Card};
+    use crate::rules::Rules;
+    use crate::strategy::Greedy;
+
+    fn rollouts(games: usize) -> Rollouts {
+        let mut rollouts = Rollouts::new("greedy");
+        rollouts.games = games;
+        rollouts
+    }
+
+    #[test]
+    fn chances_add_up() {
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into(), "C".into()], Rules::official(), 4);
+        let odds = rollouts(200).estimate(&uno.view(0), uno.events());
+
+        assert_eq!(200, odds.wins.iter().sum::<usize>() + odds.unfinished);
+        for seat in 0..3 {
+            let (low, high) = odds.bounds(seat);
+            assert!(low <= odds.chance(seat) && odds.chance(seat) <= high);
+            assert!(high - low < 0.15);
+        }
+        assert_eq!(odds, rollouts(200).estimate(&uno.view(0), uno.events()));
+        assert!(odds.to_string().starts_with("seat 1  #"));
+    }
+
+    #[test]
+    fn a_playable_last_card_wins() {
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 9);
+        let mut view = uno.view(0);
+        view.hand = vec![Card::new(view.top.color, Face::Five)];
+        view.hand_sizes[0] = 1;
+        let odds = rollouts(50).estimate(&view, &[]);
+        assert_eq!(50, odds.wins[0]);
+        assert_eq!((1.0, 1.0), (odds.chance(0), odds.bounds(0).1));
+
+        let (winner, uno) = play_out(uno, vec![Box::new(Greedy), Box::new(Greedy)], 10_000);
+        let winner = winner.unwrap();
+        assert_eq!(1.0, rollouts(10).estimate(&uno.view(0), uno.events()).chance(winner));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::card::Card;
 use crate::deck::Deck;
 
-use std::fmt::{self, Debug, Display};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display};
 
+#[derive(Clone)]
 pub struct Player {
     name: String,
     hand: Deck,
@@ -15,6 +18,9 @@ impl Debug for Player {
 }
 
 impl Player {
+    /// Names for the players of a game nobody named, one for each seat.
+    pub const NAMES: [&'static str; 8] = ["Ali", "Bob", "Cam", "Dan", "Edd", "Fil", "Gem", "Hal"];
+
     pub fn new<S: ToString>(name: S) -> Player {
         Player {
             name: name.to_string(),
@@ -0,0 +1,433 @@
+use crate::card::Card;
+use crate::direction::GameDirection;
+use crate::event::Event;
+use crate::rules::Rules;
+use crate::uno::{Action, Phase, TurnResult};
+use crate::view::PlayerView;
+
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::BuildHasher;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A line a client sends to the server.
+///
+/// | line                      | meaning                                   |
+/// |---------------------------|-------------------------------------------|
+/// | `JOIN <name>`             | take a seat; names can't contain commas   |
+/// | `RESUME <token> [<seen>]` | take back the seat a `SESSION` was for    |
+/// | `PLAY <card>`             | play a card, a wild with its color: `BC`  |
+/// | `DRAW`                    | draw, or take the pending penalty         |
+/// | `PASS`                    | keep the card just drawn                  |
+/// | `CHALLENGE`               | challenge a Draw Four                     |
+/// | `ACCEPT`                  | take a Draw Four without challenging      |
+/// | `SWAP <seat>`             | swap hands after a seven                  |
+/// | `UNO`                     | call UNO before playing the next card     |
+/// | `PING`                    | nothing; shows the client is still there  |
+/// | `QUIT`                    | leave the game                            |
+///
+/// Cards are written as in `Card`'s `Display`, e.g. `Y0`, `RS`, `GD`, and
+/// seats count from 0. `JOIN` or `RESUME` has to come first. `seen` is how
+/// many `EVENT`s the client got before losing its connection, and the ones
+/// after are sent again. The server takes a client that sends nothing for a
+/// while to be gone, so clients `PING` when they have nothing else to say.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ClientLine {
+    Join(String),
+    Resume { token: String, seen: Option<usize> },
+    Act(Action),
+    Uno,
+    Ping,
+    Quit,
+}
+
+/// A line the server sends to a client.
+///
+/// | line                         | meaning                                           |
+/// |------------------------------|---------------------------------------------------|
+/// | `WELCOME <seat> <names>`     | the game starts; the names are comma separated    |
+/// | `SESSION <token>`            | what to `RESUME` with after losing the connection |
+/// | `VIEW <key=value>...`        | everything this client can see, after any change  |
+/// | `EVENT <event>`              | something everyone at the table saw happen        |
+/// | `YOURMOVE <actions>`         | this client's turn, with its legal actions        |
+/// | `RESULT <result>`            | what came of this client's last action            |
+/// | `ERROR <message>`            | a line the server could not accept                |
+/// | `END`                        | the game is over and the connection will close    |
+///
+/// `VIEW` has the keys `seat`, `turn`, `current`, `direction` (`cw` or
+/// `ccw`), `phase` (`play`, `drawn`, `challenge:<offender>`, `swap` or
+/// `over:<winner>`), `pending`, `draw`, `sizes`, `top`, `drawn` (`-` for
+/// none), `hand`, `discard` and `rules`, with lists comma separated.
+/// Events are `PLAYED <seat> <card>`, `DREW <seat> <count> FORCED|CHOSE`,
+/// `PASSED <seat>`, `SKIPPED <seat>`, `CHALLENGED <seat> <offender> BLUFF|FAIR`,
+/// `SWAPPED <seat> <with>`, `ROTATED`, `UNO <seat>`, `UNOPENALTY <seat>`,
+/// `RESHUFFLED` and `WON <seat>`. Actions in `YOURMOVE` are comma separated
+/// and written as the client would send them.
+#[derive(Clone, Debug)]
+pub enum ServerLine {
+    Welcome { seat: usize, names: Vec<String> },
+    Session(String),
+    View(PlayerView),
+    Event(Event),
+    YourMove(Vec<Action>),
+    Result(TurnResult),
+    Error(String),
+    End,
+}
+
+/// A fresh token nobody could guess, for taking a seat back. `RandomState`
+/// is seeded from the operating system, the only randomness std offers.
+pub fn new_token() -> String {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+    let count = COUNT.fetch_add(1, Ordering::Relaxed);
+    let half = || RandomState::new().hash_one(count);
+    format!("{:016x}{:016x}", half(), half())
+}
+
+/// A line that doesn't follow the protocol.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProtocolError {
+    pub line: String,
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "could not understand \"{}\"", self.line)
+    }
+}
+
+impl Error for ProtocolError {}
+
+pub(crate) fn action_text(action: Action) -> String {
+    match action {
+        Action::Play(card) => format!("PLAY {}", card),
+        Action::Draw => "DRAW".into(),
+        Action::Pass => "PASS".into(),
+        Action::Challenge => "CHALLENGE".into(),
+        Action::Accept => "ACCEPT".into(),
+        Action::Swap(seat) => format!("SWAP {}", seat),
+    }
+}
+
+pub(crate) fn parse_action(words: &[&str]) -> Option<Action> {
+    match words {
+        ["PLAY", card] => card.parse().ok().map(Action::Play),
+        ["DRAW"] => Some(Action::Draw),
+        ["PASS"] => Some(Action::Pass),
+        ["CHALLENGE"] => Some(Action::Challenge),
+        ["ACCEPT"] => Some(Action::Accept),
+        ["SWAP", seat] => seat.parse().ok().map(Action::Swap),
+        _ => None,
+    }
+}
+
+pub(crate) fn cards_text(cards: &[Card]) -> String {
+    cards.iter().map(|card| card.to_string()).collect::<Vec<_>>().join(",")
+}
+
+pub(crate) fn parse_cards(text: &str) -> Option<Vec<Card>> {
+    text.split(',').filter(|card| !card.is_empty()).map(|card| card.parse().ok()).collect()
+}
+
+impl Display for ClientLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientLine::Join(name) => write!(f, "JOIN {}", name),
+            ClientLine::Resume { token, seen: None } => write!(f, "RESUME {}", token),
+            ClientLine::Resume { token, seen: Some(seen) } => write!(f, "RESUME {} {}", token, seen),
+            ClientLine::Act(action) => write!(f, "{}", action_text(*action)),
+            ClientLine::Uno => write!(f, "UNO"),
+            ClientLine::Ping => write!(f, "PING"),
+            ClientLine::Quit => write!(f, "QUIT"),
+        }
+    }
+}
+
+impl FromStr for ClientLine {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<ClientLine, ProtocolError> {
+        let line = line.trim();
+        let error = || ProtocolError { line: line.into() };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["JOIN", ..] => {
+                let name = line["JOIN".len()..].trim();
+                match name.is_empty() || name.contains(',') {
+                    true => Err(error()),
+                    false => Ok(ClientLine::Join(name.into())),
+                }
+            },
+            ["RESUME", token] => Ok(ClientLine::Resume { token: token.to_string(), seen: None }),
+            ["RESUME", token, seen] => seen.parse().map(|seen| ClientLine::Resume { token: token.to_string(), seen: Some(seen) }).map_err(|_| error()),
+            ["UNO"] => Ok(ClientLine::Uno),
+            ["PING"] => Ok(ClientLine::Ping),
+            ["QUIT"] => Ok(ClientLine::Quit),
+            words => parse_action(words).map(ClientLine::Act).ok_or_else(error),
+        }
+    }
+}
+
+pub(crate) fn event_text(event: &Event) -> String {
+    match *event {
+        Event::Played { seat, card } => format!("PLAYED {} {}", seat, card),
+        Event::Drew { seat, count, forced } => format!("DREW {} {} {}", seat, count, if forced { "FORCED" } else { "CHOSE" }),
+        Event::Passed { seat } => format!("PASSED {}", seat),
+        Event::Skipped { seat } => format!("SKIPPED {}", seat),
+        Event::Challenged { seat, offender, bluffed } => format!("CHALLENGED {} {} {}", seat, offender, if bluffed { "BLUFF" } else { "FAIR" }),
+        Event::Swapped { seat, with } => format!("SWAPPED {} {}", seat, with),
+        Event::Rotated => "ROTATED".into(),
+        Event::UnoCalled { seat } => format!("UNO {}", seat),
+        Event::UnoPenalty { seat } => format!("UNOPENALTY {}", seat),
+        Event::Reshuffled => "RESHUFFLED".into(),
+        Event::Won { seat } => format!("WON {}", seat),
+    }
+}
+
+pub(crate) fn parse_event(words: &[&str]) -> Option<Event> {
+    let n = |word: &str| word.parse::<usize>().ok();
+    let flag = |word: &str, on: &str, off: &str| match word {
+        _ if word == on => Some(true),
+        _ if word == off => Some(false),
+        _ => None,
+    };
+    Some(match *words {
+        ["PLAYED", seat, card] => Event::Played { seat: n(seat)?, card: card.parse().ok()? },
+        ["DREW", seat, count, forced] => Event::Drew { seat: n(seat)?, count: n(count)?, forced: flag(forced, "FORCED", "CHOSE")? },
+        ["PASSED", seat] => Event::Passed { seat: n(seat)? },
+        ["SKIPPED", seat] => Event::Skipped { seat: n(seat)? },
+        ["CHALLENGED", seat, offender, bluffed] => Event::Challenged { seat: n(seat)?, offender: n(offender)?, bluffed: flag(bluffed, "BLUFF", "FAIR")? },
+        ["SWAPPED", seat, with] => Event::Swapped { seat: n(seat)?, with: n(with)? },
+        ["ROTATED"] => Event::Rotated,
+        ["UNO", seat] => Event::UnoCalled { seat: n(seat)? },
+        ["UNOPENALTY", seat] => Event::UnoPenalty { seat: n(seat)? },
+        ["RESHUFFLED"] => Event::Reshuffled,
+        ["WON", seat] => Event::Won { seat: n(seat)? },
+        _ => return None,
+    })
+}
+
+pub(crate) fn result_text(result: &TurnResult) -> String {
+    match result {
+        TurnResult::Success(card) => format!("PLAYED {}", card),
+        TurnResult::InvalidMove(on, played) => format!("INVALID {} {}", played, on),
+        TurnResult::NotHoldingCard(card) => format!("NOTHOLDING {}", card),
+        TurnResult::Drew(cards) => format!("DREW {}", cards_text(cards)),
+        TurnResult::Passed => "PASSED".into(),
+        TurnResult::Challenged { bluffed } => format!("CHALLENGED {}", if *bluffed { "BLUFF" } else { "FAIR" }),
+        TurnResult::Swapped(seat) => format!("SWAPPED {}", seat),
+        TurnResult::IllegalAction(action) => format!("ILLEGAL {}", action_text(*action)),
+        TurnResult::GameOver => "WON".into(),
+    }
+}
+
+pub(crate) fn parse_result(words: &[&str]) -> Option<TurnResult> {
+    Some(match *words {
+        ["PLAYED", card] => TurnResult::Success(card.parse().ok()?),
+        ["INVALID", played, on] => TurnResult::InvalidMove(on.parse().ok()?, played.parse().ok()?),
+        ["NOTHOLDING", card] => TurnResult::NotHoldingCard(card.parse().ok()?),
+        ["DREW", cards] => TurnResult::Drew(parse_cards(cards)?),
+        ["PASSED"] => TurnResult::Passed,
+        ["CHALLENGED", "BLUFF"] => TurnResult::Challenged { bluffed: true },
+        ["CHALLENGED", "FAIR"] => TurnResult::Challenged { bluffed: false },
+        ["SWAPPED", seat] => TurnResult::Swapped(seat.parse().ok()?),
+        ["ILLEGAL", ref action @ ..] => TurnResult::IllegalAction(parse_action(action)?),
+        ["WON"] => TurnResult::GameOver,
+        _ => return None,
+    })
+}
+
+pub(crate) fn phase_text(phase: Phase) -> String {
+    match phase {
+        Phase::Play => "play".to_string(),
+        Phase::Drawn => "drawn".to_string(),
+        Phase::Challenge { offender } => format!("challenge:{}", offender),
+        Phase::Swap => "swap".to_string(),
+        Phase::GameOver { winner } => format!("over:{}", winner),
+    }
+}
+
+pub(crate) fn parse_phase(text: &str) -> Option<Phase> {
+    Some(match text.split_once(':') {
+        None => match text {
+            "play" => Phase::Play,
+            "drawn" => Phase::Drawn,
+            "swap" => Phase::Swap,
+            _ => return None,
+        },
+        Some(("challenge", offender)) => Phase::Challenge { offender: offender.parse().ok()? },
+        Some(("over", winner)) => Phase::GameOver { winner: winner.parse().ok()? },
+        _ => return None,
+    })
+}
+
+pub(crate) fn view_text(view: &PlayerView) -> String {
+    let list = |numbers: &[usize]| numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+    let direction = match view.direction {
+        GameDirection::Clockwise => "cw",
+        GameDirection::CounterClockwise => "ccw",
+    };
+    format!(
+        "seat={} turn={} current={} direction={} phase={} pending={} draw={} sizes={} top={} drawn={} hand={} discard={} rules={}",
+        view.seat,
+        view.turn,
+        view.current_seat,
+        direction,
+        phase_text(view.phase),
+        view.pending_draw,
+        view.draw_pile,
+        list(&view.hand_sizes),
+        view.top,
+        view.drawn.map(|card| card.to_string()).unwrap_or_else(|| "-".into()),
+        cards_text(&view.hand),
+        cards_text(&view.discard),
+        view.rules
+    )
+}
+
+pub(crate) fn parse_view(words: &[&str]) -> Option<PlayerView> {
+    let value = |key: &str| words.iter().find_map(|word| word.strip_prefix(key).and_then(|rest| rest.strip_prefix('=')));
+    let number = |key: &str| value(key)?.parse::<usize>().ok();
+
+    let phase = parse_phase(value("phase")?)?;
+    let mut rules = Rules::official();
+    for option in value("rules")?.split(',') {
+        let (key, setting) = option.split_once('=')?;
+        rules.set(key, setting).ok()?;
+    }
+
+    Some(PlayerView {
+        seat: number("seat")?,
+        hand: parse_cards(value("hand")?)?,
+        top: value("top")?.parse().ok()?,
+        discard: parse_cards(value("discard")?)?,
+        direction: match value("direction")? {
+            "cw" => GameDirection::Clockwise,
+            "ccw" => GameDirection::CounterClockwise,
+            _ => return None,
+        },
+        current_seat: number("current")?,
+        phase,
+        drawn: match value("drawn")? {
+            "-" => None,
+            card => Some(card.parse().ok()?),
+        },
+        pending_draw: number("pending")?,
+        hand_sizes: value("sizes")?.split(',').map(|size| size.parse().ok()).collect::<Option<_>>()?,
+        draw_pile: number("draw")?,
+        turn: number("turn")?,
+        rules,
+    })
+}
+
+impl Display for ServerLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServerLine::Welcome { seat, names } => write!(f, "WELCOME {} {}", seat, names.join(",")),
+            ServerLine::Session(token) => write!(f, "SESSION {}", token),
+            ServerLine::View(view) => write!(f, "VIEW {}", view_text(view)),
+            ServerLine::Event(event) => write!(f, "EVENT {}", event_text(event)),
+            ServerLine::YourMove(actions) => {
+                let actions: Vec<_> = actions.iter().map(|&action| action_text(action)).collect();
+                write!(f, "YOURMOVE {}", actions.join(","))
+            },
+            ServerLine::Result(result) => write!(f, "RESULT {}", result_text(result)),
+            ServerLine::Error(message) => write!(f, "ERROR {}", message),
+            ServerLine::End => write!(f, "END"),
+        }
+    }
+}
+
+impl FromStr for ServerLine {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<ServerLine, ProtocolError> {
+        let line = line.trim();
+        let error = || ProtocolError { line: line.into() };
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let words: Vec<&str> = rest.split_whitespace().collect();
+        let parsed = match command {
+            "WELCOME" => {
+                let (seat, names) = rest.split_once(' ').ok_or_else(error)?;
+                let names = names.split(',').map(String::from).collect();
+                seat.parse().ok().map(|seat| ServerLine::Welcome { seat, names })
+            },
+            "SESSION" => match words.as_slice() {
+                [token] => Some(ServerLine::Session(token.to_string())),
+                _ => None,
+            },
+            "VIEW" => parse_view(&words).map(ServerLine::View),
+            "EVENT" => parse_event(&words).map(ServerLine::Event),
+            "YOURMOVE" => rest.split(',')
+                .map(|action| parse_action(&action.split_whitespace().collect::<Vec<_>>()))
+                .collect::<Option<_>>()
+                .map(ServerLine::YourMove),
+            "RESULT" => parse_result(&words).map(ServerLine::Result),
+            "ERROR" => Some(ServerLine::Error(rest.into())),
+            "END" => Some(ServerLine::End),
+            _ => None,
+        };
+        parsed.ok_or_else(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uno::Uno;
+
+    /// Writes `line`, reads it back, and writes it again.
+    fn round_trip(line: &ServerLine) -> String {
+        let text = line.to_string();
+        assert_eq!(text, text.parse::<ServerLine>().unwrap().to_string());
+        text
+    }
+
+    #[test]
+    fn client_lines() {
+        let lines = ["JOIN Ann Lee", "RESUME 0f3a", "RESUME 0f3a 12", "PLAY R5", "PLAY BC", "PLAY GD", "DRAW", "PASS", "CHALLENGE", "ACCEPT", "SWAP 2", "UNO", "PING", "QUIT"];
+        for line in lines.iter() {
+            assert_eq!(*line, line.parse::<ClientLine>().unwrap().to_string());
+        }
+        assert_eq!(ClientLine::Act(Action::Swap(2)), "SWAP 2".parse().unwrap());
+        assert!("JOIN".parse::<ClientLine>().is_err());
+        assert!("JOIN A,B".parse::<ClientLine>().is_err());
+        assert!("RESUME".parse::<ClientLine>().is_err());
+        assert!("RESUME 0f3a many".parse::<ClientLine>().is_err());
+        assert!("PLAY X9".parse::<ClientLine>().is_err());
+        assert!("SHOUT".parse::<ClientLine>().is_err());
+    }
+
+    #[test]
+    fn server_lines() {
+        let mut uno = Uno::create_game_with(vec!["A".into(), "B".into(), "C".into()], Rules::stacking(), 6);
+        uno.apply(Action::Draw);
+        let view = uno.view(uno.current_seat());
+        let text = round_trip(&ServerLine::View(view.clone()));
+        match text.parse().unwrap() {
+            ServerLine::View(copy) => {
+                assert_eq!(view.hand, copy.hand);
+                assert_eq!(view.rules, copy.rules);
+                assert_eq!(view.phase, copy.phase);
+                assert_eq!(view.drawn, copy.drawn);
+            },
+            line => panic!("read back {:?}", line),
+        }
+
+        for event in uno.events() {
+            round_trip(&ServerLine::Event(*event));
+        }
+        round_trip(&ServerLine::Event(Event::Challenged { seat: 1, offender: 0, bluffed: true }));
+        assert_eq!("WELCOME 1 Ann Lee,Bo", round_trip(&ServerLine::Welcome { seat: 1, names: vec!["Ann Lee".into(), "Bo".into()] }));
+        assert_eq!("SESSION 0f3a", round_trip(&ServerLine::Session("0f3a".into())));
+        assert_eq!("YOURMOVE PLAY R5,DRAW", round_trip(&ServerLine::YourMove(vec![Action::Play("R5".parse().unwrap()), Action::Draw])));
+        round_trip(&ServerLine::Result(TurnResult::Drew(vec!["R5".parse().unwrap(), "YC".parse().unwrap()])));
+        round_trip(&ServerLine::Result(TurnResult::IllegalAction(Action::Swap(3))));
+        //the engine gives the card on top first, the line the card played first
+        assert_eq!("RESULT INVALID B7 R5", round_trip(&ServerLine::Result(TurnResult::InvalidMove("R5".parse().unwrap(), "B7".parse().unwrap()))));
+        round_trip(&ServerLine::Error("not your turn".into()));
+        assert!("VIEW seat=0".parse::<ServerLine>().is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::card::{color::Color, face::Face, Card};
+use crate::deck::Deck;
+use crate::locale::{Locale, Message};
+use crate::uno::Phase;
+use crate::view::PlayerView;
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// How cards are drawn on a terminal.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    /// Cards in their own colors using ANSI escapes and Unicode symbols.
+    Ansi,
+    /// Plain text without escape codes or symbols, for pipes, logs, and terminals without color.
+    Plain,
+}
+
+impl RenderMode {
+    /// Picks `Ansi` only when stdout is a terminal that can show color.
+    ///
+    /// `NO_COLOR` always wins, then `CLICOLOR_FORCE`, then `TERM=dumb`.
+    pub fn detect() -> RenderMode {
+        let set = |name| env::var_os(name).map(|val| !val.is_empty()).unwrap_or(false);
+        if set("NO_COLOR") {
+            return RenderMode::Plain;
+        }
+        if set("CLICOLOR_FORCE") {
+            return RenderMode::Ansi;
+        }
+
+        let dumb = env::var("TERM").map(|term| term == "dumb").unwrap_or(cfg!(not(windows)));
+        if dumb || !io::stdout().is_terminal() {
+            RenderMode::Plain
+        } else {
+            RenderMode::Ansi
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Draws single cards, hands, and the top of the discard pile.
+#[derive(Copy, Clone, Debug)]
+pub struct Renderer {
+    mode: RenderMode,
+    locale: Locale,
+}
+
+impl Renderer {
+    pub fn new(mode: RenderMode, locale: Locale) -> Renderer {
+        Renderer { mode, locale }
+    }
+
+    pub fn mode(&self) -> RenderMode {
+        self.mode
+    }
+
+    /// A card as held in a hand. Wild cards have no color until played.
+    pub fn card(&self, card: Card) -> String {
+        match card.face {
+            Face::ColorCard | Face::DrawFour => self.draw(card, None),
+            _ => self.draw(card, Some(card.color)),
+        }
+    }
+
+    /// Every card in a hand, in order.
+    pub fn hand(&self, hand: &Deck) -> String {
+        let cards: Vec<_> = hand.iter().map(|&card| self.card(card)).collect();
+        match self.mode {
+            RenderMode::Ansi => cards.join(" "),
+            RenderMode::Plain => cards.join(", "),
+        }
+    }
+
+    /// The card on top of the discard pile, showing the declared color of a wild.
+    pub fn discard_top(&self, discard: &Deck) -> String {
+        match discard.peek_top_card() {
+            Some(&card) => self.draw(card, Some(card.color)),
+            None => match self.mode {
+                RenderMode::Ansi => "\x1b[2m[ ]\x1b[0m".into(),
+                RenderMode::Plain => "[ ]".into(),
+            },
+        }
+    }
+
+    /// The lines a player is shown at the start of their turn, down to their hand.
+    /// `names` are the players' names by seat.
+    pub fn table(&self, view: &PlayerView, names: &[String]) -> Vec<String> {
+        let locale = self.locale;
+        let discard: Deck = view.discard.clone().into();
+        let hand: Deck = view.hand.clone().into();
+        vec![
+            format!("\n{}", locale.message(&Message::Turn(view.turn))),
+            locale.message(&Message::DrawPileSize(view.draw_pile)),
+            locale.message(&Message::DiscardPileSize(view.discard.len())),
+            locale.message(&Message::TopOfDiscard(&self.discard_top(&discard))),
+            locale.message(&Message::Direction(view.direction)),
+            locale.message(&Message::PlayersTurn(&names[view.current_seat])),
+            format!("\n{}", locale.message(&Message::YourCards(&self.hand(&hand)))),
+        ]
+    }
+
+    /// The lines that tell a player what they're being asked to do, if it's
+    /// anything more than playing a card or drawing.
+    pub fn prompt(&self, view: &PlayerView, names: &[String]) -> Vec<String> {
+        let locale = self.locale;
+        match view.phase {
+            Phase::Drawn => view.drawn.iter().map(|&card| locale.message(&Message::PlayDrawnOrPass(card))).collect(),
+            Phase::Challenge { offender } => vec![locale.message(&Message::ChallengePrompt(&names[offender]))],
+            Phase::Swap => {
+                let others = names.iter().enumerate().filter(|&(other, _)| other != view.seat);
+                Some(locale.message(&Message::SwapPrompt)).into_iter().chain(others.map(|(other, name)| format!("  {}: {}", other + 1, name))).collect()
+            },
+            Phase::Play if view.pending_draw > 0 => vec![locale.message(&Message::PenaltyPending(view.pending_draw))],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Clears the terminal, or scrolls old output away when escapes can't be used.
+    pub fn clear_screen(&self) -> String {
+        match self.mode {
+            RenderMode::Ansi => "\x1b[2J\x1b[H".into(),
+            RenderMode::Plain => "\n".repeat(60),
+        }
+    }
+
+    fn draw(&self, card: Card, color: Option<Color>) -> String {
+        match self.mode {
+            RenderMode::Ansi => format!("{} {} {}", Renderer::escape(color), Renderer::symbol(card.face), RESET),
+            RenderMode::Plain => {
+                let name = self.locale.card_name(card);
+                match (card.face, color) {
+                    (Face::ColorCard, Some(color)) | (Face::DrawFour, Some(color)) => {
+                        format!("[{}: {}]", name, self.locale.color_name(color))
+                    },
+                    _ => format!("[{}]", name),
+                }
+            },
+        }
+    }
+
+    fn escape(color: Option<Color>) -> &'static str {
+        match color {
+            Some(Color::Red) => "\x1b[1;97;41m",
+            Some(Color::Green) => "\x1b[1;97;42m",
+            Some(Color::Blue) => "\x1b[1;97;44m",
+            Some(Color::Yellow) => "\x1b[1;30;43m",
+            None => "\x1b[1;97;40m",
+        }
+    }
+
+    fn symbol(face: Face) -> &'static str {
+        match face {
+            Face::DrawFour => "+4",
+            Face::ColorCard => "✱",
+            Face::DrawTwo => "+2",
+            Face::Skip => "⊘",
+            Face::Reverse => "⇄",
+            Face::Zero => "0",
+            Face::One => "1",
+            Face::Two => "2",
+            Face::Three => "3",
+            Face::Four => "4",
+            Face::Five => "5",
+            Face::Six => "6",
+            Face::Seven => "7",
+            Face::Eight => "8",
+            Face::Nine => "9",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_is_ascii() {
+        let plain = Renderer::new(RenderMode::Plain, Locale::English);
+        let hand: Deck = vec![Card::new(Color::Red, Face::Seven), Card::new(Color::Red, Face::ColorCard)].into();
+        assert_eq!("[Red Seven], [Wild Card]", plain.hand(&hand));
+
+        let discard: Deck = vec![Card::new(Color::Blue, Face::DrawFour)].into();
+        assert_eq!("[Draw Four: Blue]", plain.discard_top(&discard));
+        assert!(plain.hand(&Deck::default()).is_ascii());
+    }
+
+    #[test]
+    fn ansi_uses_card_colors() {
+        let ansi = Renderer::new(RenderMode::Ansi, Locale::English);
+        let skip = ansi.card(Card::new(Color::Green, Face::Skip));
+        assert!(skip.starts_with("\x1b[1;97;42m"));
+        assert!(skip.contains('⊘'));
+        assert!(skip.ends_with(RESET));
+
+        let discard: Deck = vec![Card::new(Color::Yellow, Face::ColorCard)].into();
+        assert!(ansi.discard_top(&discard).starts_with("\x1b[1;30;43m"));
+    }
+}
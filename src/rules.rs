@@ -0,0 +1,311 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt::{self, Display};
+
+/// The house rules a game is played with.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rules {
+    /// Cards dealt to each player at the start.
+    pub hand_size: usize,
+    /// A Draw Two or Draw Four may be played on top of a pending penalty,
+    /// passing the whole lot on to the next player.
+    pub stacking: bool,
+    /// Playing a seven swaps hands with a chosen player, and playing a zero
+    /// passes every hand along in the direction of play.
+    pub seven_o: bool,
+    /// The victim of a Draw Four may challenge it. A player who bluffed
+    /// (held a card of the current color) draws four instead; otherwise
+    /// the challenger draws six.
+    pub challenge_draw_four: bool,
+    /// Cards drawn by a player who gets down to one card without calling UNO.
+    /// Zero turns UNO calls off.
+    pub uno_penalty: usize,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules::classic()
+    }
+}
+
+impl Rules {
+    /// The named rule sets accepted by `Rules::preset`.
+    pub const PRESETS: [&'static str; 4] = ["classic", "official", "stacking", "seven-o"];
+
+    /// The options accepted by `Rules::set`, with a short description of each.
+    pub const OPTIONS: [(&'static str, &'static str); 5] = [
+        ("hand-size", "cards dealt to each player (1-13)"),
+        ("stacking", "stack draw cards onto a pending penalty (on/off)"),
+        ("seven-o", "sevens swap hands, zeros rotate hands (on/off)"),
+        ("challenge", "allow challenging a Draw Four (on/off)"),
+        ("uno-penalty", "cards drawn for not calling UNO (0 turns it off)"),
+    ];
+
+    /// The rules games were played by before there were options: official
+    /// dealing, but no Draw Four challenges and no UNO calls.
+    pub fn classic() -> Rules {
+        Rules {
+            challenge_draw_four: false,
+            uno_penalty: 0,
+            ..Rules::official()
+        }
+    }
+
+    pub fn official() -> Rules {
+        Rules {
+            hand_size: 7,
+            stacking: false,
+            seven_o: false,
+            challenge_draw_four: true,
+            uno_penalty: 2,
+        }
+    }
+
+    pub fn stacking() -> Rules {
+        Rules {
+            stacking: true,
+            challenge_draw_four: false,
+            ..Rules::official()
+        }
+    }
+
+    pub fn seven_o() -> Rules {
+        Rules {
+            seven_o: true,
+            ..Rules::official()
+        }
+    }
+
+    /// Looks up one of the `PRESETS` by name.
+    pub fn preset(name: &str) -> Result<Rules, RuleError> {
+        match name.trim().to_lowercase().as_ref() {
+            "classic" => Ok(Rules::classic()),
+            "official" => Ok(Rules::official()),
+            "stacking" => Ok(Rules::stacking()),
+            "seven-o" | "seveno" | "7-0" => Ok(Rules::seven_o()),
+            _ => Err(RuleError::UnknownPreset(name.into())),
+        }
+    }
+
+    /// Changes one of the `OPTIONS`.
+    pub fn set(&mut self, option: &str, value: &str) -> Result<(), RuleError> {
+        let invalid = || RuleError::InvalidValue { option: option.into(), value: value.into() };
+        let flag = || match value.trim().to_lowercase().as_ref() {
+            "on" | "yes" | "true" | "1" => Ok(true),
+            "off" | "no" | "false" | "0" => Ok(false),
+            _ => Err(invalid()),
+        };
+        let number = || value.trim().parse::<usize>().map_err(|_| invalid());
+
+        match option.trim().to_lowercase().replace('_', "-").as_ref() {
+            "hand-size" => self.hand_size = number()?,
+            "stacking" => self.stacking = flag()?,
+            "seven-o" => self.seven_o = flag()?,
+            "challenge" => self.challenge_draw_four = flag()?,
+            "uno-penalty" => self.uno_penalty = number()?,
+            _ => return Err(RuleError::UnknownOption(option.into())),
+        }
+        Ok(())
+    }
+
+    /// The `OPTIONS` as a section of a program's help.
+    pub fn usage() -> String {
+        let options: Vec<_> = Rules::OPTIONS.iter().map(|(name, help)| format!("  {:<22}  {}", name, help)).collect();
+        format!("rule options:\n{}", options.join("\n"))
+    }
+
+    /// Changes an option given as `KEY=VALUE`, the way `Display` writes them.
+    pub fn apply(&mut self, option: &str) -> Result<(), RuleError> {
+        let (key, value) = option.split_once('=').ok_or_else(|| RuleError::NotKeyValue(option.into()))?;
+        self.set(key, value)
+    }
+
+    /// Checks these rules can be used for a game with `players` players.
+    pub fn check(&self, players: usize) -> Result<(), RuleError> {
+        if !(2..=8).contains(&players) {
+            return Err(RuleError::PlayerCount(players));
+        }
+
+        //every hand has to come out of one deck of 108 and leave a card that
+        //isn't one of the 8 wilds to start the discard pile
+        if self.hand_size == 0 || self.hand_size > 13 || self.hand_size * players > 108 - 8 - 1 {
+            return Err(RuleError::HandSize { hand_size: self.hand_size, players });
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Rules {
+    /// Every option as `KEY=VALUE`, comma separated, e.g. `hand-size=7,stacking=off,...`.
+    /// Each pair can be given back to `Rules::set`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flag = |on: bool| if on { "on" } else { "off" };
+        write!(
+            f,
+            "hand-size={},stacking={},seven-o={},challenge={},uno-penalty={}",
+            self.hand_size,
+            flag(self.stacking),
+            flag(self.seven_o),
+            flag(self.challenge_draw_four),
+            self.uno_penalty
+        )
+    }
+}
+
+/// The rule arguments every program takes on its command line: a preset,
+/// options changing it and the hand size. Flags that `RuleArgs::takes` are
+/// handed to `add`, and `rules` puts them together at the end.
+#[derive(Clone, Default, Debug)]
+pub struct RuleArgs {
+    preset: Option<String>,
+    options: Vec<String>,
+    hand_size: Option<String>,
+}
+
+impl RuleArgs {
+    /// Whether `flag` is one of the rule arguments, all of which take a value.
+    pub fn takes(flag: &str) -> bool {
+        matches!(flag, "-r" | "--rules" | "-o" | "--option" | "--hand-size")
+    }
+
+    /// Records the value given for `flag`, which `takes` must accept.
+    pub fn add(&mut self, flag: &str, value: String) {
+        match flag {
+            "-r" | "--rules" => self.preset = Some(value),
+            "-o" | "--option" => self.options.push(value),
+            "--hand-size" => self.hand_size = Some(value),
+            _ => panic!("{} is not a rule argument", flag),
+        }
+    }
+
+    /// The preset, `Rules::default()` if none was given, with the options
+    /// applied in order and then the hand size.
+    pub fn rules(&self) -> Result<Rules, RuleError> {
+        let mut rules = match &self.preset {
+            Some(preset) => Rules::preset(preset)?,
+            None => Rules::default(),
+        };
+        for option in self.options.iter() {
+            let key = option.split('=').next().unwrap().trim().to_lowercase().replace('_', "-");
+            if self.hand_size.is_some() && key == "hand-size" {
+                return Err(RuleError::HandSizeTwice);
+            }
+            rules.apply(option)?;
+        }
+        if let Some(size) = &self.hand_size {
+            rules.set("hand-size", size)?;
+        }
+        Ok(rules)
+    }
+
+    /// How the rule arguments and options are described in a program's help.
+    pub fn usage() -> String {
+        format!(
+            "rules:
+  -r, --rules <PRESET>    rule preset: {} (default {})
+  -o, --option <K=V>      change a rule option, may be repeated
+      --hand-size <N>     cards dealt to each player (default 7)
+
+{}",
+            Rules::PRESETS.join(", "),
+            Rules::PRESETS[0],
+            Rules::usage()
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RuleError {
+    UnknownPreset(String),
+    UnknownOption(String),
+    InvalidValue { option: String, value: String },
+    /// An option that isn't written as `KEY=VALUE`.
+    NotKeyValue(String),
+    /// `--hand-size` and a `hand-size` option were both given.
+    HandSizeTwice,
+    PlayerCount(usize),
+    HandSize { hand_size: usize, players: usize },
+}
+
+impl Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::UnknownPreset(name) => write!(f, "unknown rule preset \"{}\" (expected one of {})", name, Rules::PRESETS.join(", ")),
+            RuleError::UnknownOption(name) => write!(f, "unknown rule option \"{}\"", name),
+            RuleError::InvalidValue { option, value } => write!(f, "\"{}\" is not a valid value for {}", value, option),
+            RuleError::NotKeyValue(option) => write!(f, "rule options look like KEY=VALUE, not \"{}\"", option),
+            RuleError::HandSizeTwice => write!(f, "--hand-size and -o hand-size cannot both be given"),
+            RuleError::PlayerCount(n) => write!(f, "a game needs 2 to 8 players, not {}", n),
+            RuleError::HandSize { hand_size, players } => {
+                write!(f, "cannot deal {} cards each to {} players (hand size must be 1-13 and fit in the deck)", hand_size, players)
+            },
+        }
+    }
+}
+
+impl Error for RuleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_and_options() {
+        for name in Rules::PRESETS.iter() {
+            assert!(Rules::preset(name).is_ok());
+        }
+        assert!(Rules::preset("nope").is_err());
+
+        let mut rules = Rules::preset("Official").unwrap();
+        rules.set("stacking", "on").unwrap();
+        rules.set("hand_size", "5").unwrap();
+        assert!(rules.stacking);
+        assert_eq!(5, rules.hand_size);
+        assert!(rules.set("stacking", "maybe").is_err());
+        assert!(rules.set("bogus", "on").is_err());
+
+        let mut copy = Rules::official();
+        for option in rules.to_string().split(',') {
+            copy.apply(option).unwrap();
+        }
+        assert_eq!(rules, copy);
+        assert_eq!(Err(RuleError::NotKeyValue("stacking".into())), copy.apply("stacking"));
+    }
+
+    #[test]
+    fn command_line_arguments() {
+        let parse = |args: &[(&str, &str)]| {
+            let mut rule_args = RuleArgs::default();
+            for &(flag, value) in args {
+                assert!(RuleArgs::takes(flag));
+                rule_args.add(flag, value.into());
+            }
+            rule_args.rules()
+        };
+        assert_eq!(Ok(Rules::default()), parse(&[]));
+        let rules = parse(&[("--hand-size", "5"), ("-o", "uno-penalty=4"), ("-r", "stacking")]).unwrap();
+        assert_eq!(Rules { hand_size: 5, uno_penalty: 4, ..Rules::stacking() }, rules);
+        assert_eq!(Err(RuleError::HandSizeTwice), parse(&[("--hand-size", "5"), ("-o", "Hand_Size=6")]));
+        assert!(parse(&[("-r", "house")]).is_err());
+        assert!(!RuleArgs::takes("--seed"));
+    }
+
+    #[test]
+    fn check_hand_size() {
+        let mut rules = Rules::official();
+        assert!(rules.check(8).is_ok());
+        assert!(rules.check(1).is_err());
+
+        rules.hand_size = 12;
+        assert!(rules.check(8).is_ok());
+        //8 hands of 13 would leave only 4 cards, which could all be wilds
+        rules.hand_size = 13;
+        assert!(rules.check(8).is_err());
+        assert!(rules.check(7).is_ok());
+        rules.hand_size = 0;
+        assert!(rules.check(2).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use crate::card::face::Face;
+use crate::driver::play_out;
+use crate::event::Event;
+use crate::player::Player;
+use crate::rules::{RuleError, Rules};
+use crate::strategy::{self, Strategy};
+use crate::uno::Uno;
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::thread;
+
+/// A batch of seeded games between computer players.
+#[derive(Clone, Debug)]
+pub struct Batch {
+    pub rules: Rules,
+    /// The strategy in each seat, by the names `strategy::by_name` knows.
+    pub bots: Vec<String>,
+    pub games: usize,
+    /// Game `i` is dealt with seed `seed + i`.
+    pub seed: u64,
+    /// Move every bot one seat along after each game, so no bot keeps the best seat.
+    pub rotate: bool,
+    /// Games still going after this many turns are stopped and count as unfinished.
+    pub max_turns: usize,
+    /// Games are shared out between this many threads. The results don't depend on it.
+    pub threads: usize,
+}
+
+impl Batch {
+    pub fn new(bots: Vec<String>, rules: Rules) -> Batch {
+        Batch { rules, bots, games: 1000, seed: 0, rotate: false, max_turns: 10_000, threads: 1 }
+    }
+
+    /// Checks every bot exists and the rules suit the number of seats.
+    pub fn check(&self) -> Result<(), BatchError> {
+        if let Some(name) = self.bots.iter().find(|name| strategy::by_name(name, 0).is_none()) {
+            return Err(BatchError::UnknownBot(name.clone()));
+        }
+        self.rules.check(self.bots.len()).map_err(BatchError::Rules)
+    }
+
+    /// The bots by seat for game `game`.
+    pub fn seating(&self, game: usize) -> Vec<String> {
+        let mut bots = self.bots.clone();
+        if self.rotate {
+            let seats = bots.len();
+            bots.rotate_left(game % seats);
+        }
+        bots
+    }
+
+    /// Plays every game, returning their records in seed order.
+    ///
+    /// Panics if `check` would fail.
+    pub fn run(&self) -> Vec<GameRecord> {
+        if let Err(e) = self.check() {
+            panic!("{}", e);
+        }
+
+        in_parallel(self.games, self.threads, |game| {
+            let seed = self.seed.wrapping_add(game as u64);
+            let bots = self.seating(game);
+            let strategies = bots.iter().enumerate().map(|(seat, name)| strategy::by_name(name, bot_seed(seed, seat)).unwrap()).collect();
+            play_game(&self.rules, bots, strategies, seed, self.max_turns)
+        })
+    }
+}
+
+/// The seed for the strategy in `seat` of the game dealt with `seed`.
+pub(crate) fn bot_seed(seed: u64, seat: usize) -> u64 {
+    seed ^ ((seat as u64) << 32)
+}
+
+/// Plays one seeded game between `strategies`, named `bots`, and records it.
+pub fn play_game(rules: &Rules, bots: Vec<String>, strategies: Vec<Box<dyn Strategy>>, seed: u64, max_turns: usize) -> GameRecord {
+    let players = (0..bots.len()).map(|seat| Player::new(seat + 1)).collect();
+    let uno = Uno::create_game_with(players, rules.clone(), seed);
+    let (_, uno) = play_out(uno, strategies, max_turns);
+    GameRecord::new(seed, bots, &uno)
+}
+
+/// Calls `job` for `0..count` across `threads` threads, returning the results in order.
+pub(crate) fn in_parallel<T, F>(count: usize, threads: usize, job: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = threads.clamp(1, count.max(1));
+    let per_thread = count.div_ceil(threads);
+    let job = &job;
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                let jobs = (i * per_thread)..((i + 1) * per_thread).min(count);
+                scope.spawn(move || jobs.map(job).collect::<Vec<_>>())
+            })
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BatchError {
+    UnknownBot(String),
+    Rules(RuleError),
+}
+
+impl Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::UnknownBot(name) => write!(f, "unknown bot \"{}\" (expected one of {})", name, strategy::NAMES.join(", ")),
+            BatchError::Rules(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BatchError {}
+
+/// What happened in one finished (or abandoned) game.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GameRecord {
+    pub seed: u64,
+    /// The strategy in each seat.
+    pub bots: Vec<String>,
+    pub winner: Option<usize>,
+    pub turns: usize,
+    /// Cards drawn by each seat, by choice or as a penalty.
+    pub drawn: Vec<usize>,
+    /// Times someone was made to draw, including UNO penalties.
+    pub penalties: usize,
+    /// Cards drawn in those penalties.
+    pub penalty_cards: usize,
+    pub reshuffles: usize,
+    pub skips: usize,
+    pub reverses: usize,
+}
+
+impl GameRecord {
+    /// Tallies up the event log of `uno`.
+    pub fn new(seed: u64, bots: Vec<String>, uno: &Uno) -> GameRecord {
+        let mut record = GameRecord {
+            seed,
+            drawn: vec![0; bots.len()],
+            bots,
+            winner: uno.winner(),
+            turns: uno.current_turn(),
+            penalties: 0,
+            penalty_cards: 0,
+            reshuffles: 0,
+            skips: 0,
+            reverses: 0,
+        };
+
+        for event in uno.events() {
+            match *event {
+                Event::Drew { seat, count, forced } => {
+                    record.drawn[seat] += count;
+                    if forced {
+                        record.penalties += 1;
+                        record.penalty_cards += count;
+                    }
+                },
+                Event::Played { card, .. } if card.face == Face::Skip => record.skips += 1,
+                Event::Played { card, .. } if card.face == Face::Reverse => record.reverses += 1,
+                Event::Reshuffled => record.reshuffles += 1,
+                _ => {},
+            }
+        }
+        record
+    }
+}
+
+/// Totals over a batch of games.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Summary {
+    pub games: usize,
+    pub unfinished: usize,
+    pub turns: usize,
+    pub drawn: usize,
+    pub reshuffles: usize,
+    /// Games in which the draw pile ran out at least once.
+    pub reshuffled_games: usize,
+    pub wins_by_seat: Vec<usize>,
+    /// Each strategy with the seats it played and how many of those it won.
+    pub by_bot: Vec<BotTally>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BotTally {
+    pub name: String,
+    pub seats: usize,
+    pub wins: usize,
+}
+
+impl Summary {
+    pub fn new(records: &[GameRecord]) -> Summary {
+        let seats = records.iter().map(|record| record.bots.len()).max().unwrap_or(0);
+        let mut summary = Summary {
+            games: records.len(),
+            unfinished: 0,
+            turns: 0,
+            drawn: 0,
+            reshuffles: 0,
+            reshuffled_games: 0,
+            wins_by_seat: vec![0; seats],
+            by_bot: Vec::new(),
+        };
+
+        for record in records {
+            summary.turns += record.turns;
+            summary.drawn += record.drawn.iter().sum::<usize>();
+            summary.reshuffles += record.reshuffles;
+            if record.reshuffles > 0 {
+                summary.reshuffled_games += 1;
+            }
+            match record.winner {
+                Some(seat) => summary.wins_by_seat[seat] += 1,
+                None => summary.unfinished += 1,
+            }
+
+            for (seat, name) in record.bots.iter().enumerate() {
+                let i = match summary.by_bot.iter().position(|tally| &tally.name == name) {
+                    Some(i) => i,
+                    None => {
+                        summary.by_bot.push(BotTally { name: name.clone(), seats: 0, wins: 0 });
+                        summary.by_bot.len() - 1
+                    },
+                };
+                summary.by_bot[i].seats += 1;
+                if record.winner == Some(seat) {
+                    summary.by_bot[i].wins += 1;
+                }
+            }
+        }
+        summary
+    }
+
+    /// Total divided by the number of games, or zero without any games.
+    fn per_game(&self, total: usize) -> f64 {
+        total as f64 / self.games.max(1) as f64
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let percent = |part: usize, whole: usize| 100.0 * part as f64 / whole.max(1) as f64;
+
+        writeln!(f, "games:            {} ({} unfinished)", self.games, self.unfinished)?;
+        writeln!(f, "average length:   {:.1} turns", self.per_game(self.turns))?;
+        writeln!(f, "cards drawn:      {:.1} per game", self.per_game(self.drawn))?;
+        writeln!(f, "reshuffles:       {:.2} per game, in {:.1}% of games", self.per_game(self.reshuffles), percent(self.reshuffled_games, self.games))?;
+
+        writeln!(f, "\nseat  wins    win rate")?;
+        for (seat, &wins) in self.wins_by_seat.iter().enumerate() {
+            writeln!(f, "{:<4}  {:<6}  {:>5.1}%", seat + 1, wins, percent(wins, self.games))?;
+        }
+
+        writeln!(f, "\nbot       seats   wins    win rate")?;
+        for tally in self.by_bot.iter() {
+            writeln!(f, "{:<8}  {:<6}  {:<6}  {:>5.1}%", tally.name, tally.seats, tally.wins, percent(tally.wins, tally.seats))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch() -> Batch {
+        let mut batch = Batch::new(vec!["greedy".into(), "random".into(), "basic".into()], Rules::stacking());
+        batch.games = 60;
+        batch.seed = 100;
+        batch.rotate = true;
+        batch
+    }
+
+    #[test]
+    fn threads_do_not_change_results() {
+        let mut batch = batch();
+        let alone = batch.run();
+        batch.threads = 4;
+        assert_eq!(alone, batch.run());
+        assert_eq!((100..160).collect::<Vec<_>>(), alone.iter().map(|record| record.seed).collect::<Vec<_>>());
+        assert_eq!(vec!["random", "basic", "greedy"], alone[1].bots);
+    }
+
+    #[test]
+    fn summary_adds_up() {
+        let records = batch().run();
+        let summary = Summary::new(&records);
+        assert_eq!(60, summary.games);
+        assert_eq!(60 - summary.unfinished, summary.wins_by_seat.iter().sum::<usize>());
+        assert_eq!(3, summary.by_bot.len());
+        assert!(summary.by_bot.iter().all(|tally| tally.seats == 60));
+        assert!(summary.to_string().contains("greedy"));
+    }
+
+    #[test]
+    fn bad_batches() {
+        assert_eq!(Err(BatchError::UnknownBot("nobody".into())), Batch::new(vec!["basic".into(), "nobody".into()], Rules::official()).check());
+        assert!(Batch::new(vec!["basic".into()], Rules::official()).check().is_err());
+    }
+}
@@ -0,0 +1,137 @@
+mod basic;
+mod expert;
+mod greedy;
+mod ismcts;
+mod random;
+
+pub use basic::Basic;
+pub use expert::{Expert, ExpertWeights};
+pub use greedy::Greedy;
+pub use ismcts::{Ismcts, IsmctsConfig};
+pub use random::Random;
+
+use crate::card::color::Color;
+use crate::event::Event;
+use crate::uno::Action;
+use crate::view::PlayerView;
+
+/// A computer player.
+///
+/// The driver asks for a decision only when it is this player's turn, and
+/// only offers actions the rules allow. `Send` so games can run on other threads.
+pub trait Strategy: Send {
+    /// A short name for logs and leaderboards.
+    fn name(&self) -> &str;
+
+    /// Picks one of `legal`, which is never empty. A wild card may be picked
+    /// with any color; `choose_color` is asked for the real one afterwards.
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action;
+
+    /// The color to declare for a wild card that is about to be played.
+    fn choose_color(&mut self, view: &PlayerView) -> Color;
+
+    /// Whether to challenge the Draw Four just played on this player.
+    fn challenge(&mut self, _view: &PlayerView) -> bool {
+        false
+    }
+
+    /// Whether to call UNO before playing down to one card.
+    fn call_uno(&mut self, _view: &PlayerView) -> bool {
+        true
+    }
+
+    /// Sees every public event, in order, as it happens.
+    fn observe(&mut self, _event: &Event) {}
+}
+
+/// The names accepted by `by_name`.
+pub const NAMES: [&str; 5] = ["basic", "random", "greedy", "expert", "ismcts"];
+
+/// Creates one of the built-in strategies. `seed` makes randomised ones repeatable.
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Strategy>> {
+    match name.trim().to_lowercase().as_ref() {
+        "basic" => Some(Box::new(Basic)),
+        "random" => Some(Box::new(Random::new(seed))),
+        "greedy" => Some(Box::new(Greedy)),
+        "expert" => Some(Box::new(Expert::default())),
+        "ismcts" => Some(Box::new(Ismcts::new(IsmctsConfig::default(), seed))),
+        _ => None,
+    }
+}
+
+/// The color `hand` holds most of, ignoring wilds. Ties go to the earlier color.
+pub fn most_held_color(hand: &[crate::card::Card]) -> Color {
+    let colors = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+    let count = |color| hand.iter().filter(|c| !c.face.is_wild() && c.color == color).count();
+    colors.iter().copied().rev().max_by_key(|&color| count(color)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::play_out;
+    use crate::rules::Rules;
+    use crate::uno::Uno;
+
+    /// Fails the test if the wrapped strategy picks something it wasn't offered.
+    struct Checked(Box<dyn Strategy>);
+
+    impl Strategy for Checked {
+        fn name(&self) -> &str {
+            self.0.name()
+        }
+
+        fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+            let action = self.0.choose_action(view, legal);
+            assert!(legal.contains(&action), "{} picked {:?} from {:?}", self.name(), action, legal);
+            action
+        }
+
+        fn choose_color(&mut self, view: &PlayerView) -> Color {
+            self.0.choose_color(view)
+        }
+
+        fn challenge(&mut self, view: &PlayerView) -> bool {
+            self.0.challenge(view)
+        }
+    }
+
+    fn game(seed: u64, rules: &Rules, bots: &[&str]) -> (Option<usize>, Uno) {
+        let players = bots.iter().map(|&name| name.into()).collect();
+        let uno = Uno::create_game_with(players, rules.clone(), seed);
+        let strategies = bots.iter().enumerate()
+            .map(|(i, name)| Box::new(Checked(by_name(name, seed + i as u64).unwrap())) as Box<dyn Strategy>)
+            .collect();
+        play_out(uno, strategies, 10_000)
+    }
+
+    #[test]
+    fn bots_never_make_illegal_moves() {
+        let presets = [Rules::official(), Rules::stacking(), Rules::seven_o()];
+        let tables: [&[&str]; 3] = [&["random", "greedy"], &["greedy", "random", "random"], &["random", "random", "greedy", "basic", "random"]];
+        for seed in 0..400 {
+            for rules in presets.iter() {
+                let bots = tables[seed as usize % tables.len()];
+                let (winner, uno) = game(seed, rules, bots);
+                let winner = winner.expect("game did not finish");
+                assert!(uno.players()[winner].get_hand().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_bots_repeat() {
+        let bots = ["random", "greedy", "random"];
+        let (_, a) = game(11, &Rules::seven_o(), &bots);
+        let (_, b) = game(11, &Rules::seven_o(), &bots);
+        assert_eq!(a.events(), b.events());
+    }
+
+    #[test]
+    fn greedy_plays_its_best_card() {
+        let mut greedy = Greedy;
+        let uno = Uno::create_game_with(vec!["Ali".into(), "Bob".into()], Rules::official(), 1);
+        let legal = [Action::Play("R3".parse().unwrap()), Action::Play("RS".parse().unwrap()), Action::Draw];
+        assert_eq!(Action::Play("RS".parse().unwrap()), greedy.choose_action(&uno.view(0), &legal));
+    }
+}
@@ -0,0 +1,34 @@
+use super::{most_held_color, Strategy};
+use crate::card::color::Color;
+use crate::uno::Action;
+use crate::view::PlayerView;
+
+/// Plays the first card it can, otherwise draws. Never challenges.
+pub struct Basic;
+
+impl Strategy for Basic {
+    fn name(&self) -> &str {
+        "basic"
+    }
+
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        if let Some(&play) = legal.iter().find(|action| matches!(action, Action::Play(_))) {
+            return play;
+        }
+
+        //after a seven, take the smallest hand on the table
+        let swap = legal.iter().filter_map(|&action| match action {
+            Action::Swap(seat) => Some(seat),
+            _ => None,
+        }).min_by_key(|&seat| view.hand_sizes[seat]);
+
+        match swap {
+            Some(seat) => Action::Swap(seat),
+            None => legal[0],
+        }
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        most_held_color(&view.hand)
+    }
+}
@@ -0,0 +1,256 @@
+use super::Strategy;
+use crate::belief::Beliefs;
+use crate::card::{color::Color, face::Face, Card};
+use crate::event::Event;
+use crate::uno::{Action, Phase};
+use crate::view::PlayerView;
+
+const COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+
+/// How much the expert cares about each part of its heuristics.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExpertWeights {
+    /// Cost of playing a Wild Card while it still has other options.
+    pub hold_wild: f32,
+    /// Cost of playing a Draw Four while it still has other options.
+    pub hold_draw_four: f32,
+    /// Value of hitting an opponent with a skip or draw card, per point of danger.
+    pub attack: f32,
+    /// Opponents with this many cards or fewer are treated as about to win.
+    pub danger_hand_size: usize,
+    /// Value of playing a color it holds many of, so it keeps its options open.
+    pub color_majority: f32,
+    /// Value of getting rid of high-scoring cards.
+    pub points: f32,
+    /// Value per card of ending up with a smaller hand after a seven or a zero.
+    pub swap: f32,
+    /// Value per held card when declaring a color.
+    pub color_in_hand: f32,
+    /// Value per opponent unlikely to hold a color when declaring it, scaled by how unlikely.
+    pub opponent_lacks: f32,
+    /// Challenge a Draw Four when the chance the player held the old color is above this.
+    pub challenge_threshold: f32,
+}
+
+impl Default for ExpertWeights {
+    fn default() -> ExpertWeights {
+        ExpertWeights {
+            hold_wild: 2.5,
+            hold_draw_four: 3.5,
+            attack: 1.5,
+            danger_hand_size: 3,
+            color_majority: 2.0,
+            points: 0.5,
+            swap: 0.75,
+            color_in_hand: 1.0,
+            opponent_lacks: 0.75,
+            challenge_threshold: 0.4,
+        }
+    }
+}
+
+/// Plays with the heuristics a careful human would use: keeps wilds back,
+/// aims penalties at whoever is closest to winning, and declares colors its
+/// opponents seem to be out of.
+pub struct Expert {
+    weights: ExpertWeights,
+    beliefs: Beliefs,
+}
+
+impl Default for Expert {
+    fn default() -> Expert {
+        Expert::new(ExpertWeights::default())
+    }
+}
+
+impl Expert {
+    pub fn new(weights: ExpertWeights) -> Expert {
+        Expert { weights, beliefs: Beliefs::new() }
+    }
+
+    pub fn weights(&self) -> &ExpertWeights {
+        &self.weights
+    }
+
+    /// How close `seat` is to winning, from zero up.
+    fn danger(&self, view: &PlayerView, seat: usize) -> f32 {
+        (self.weights.danger_hand_size + 1).saturating_sub(view.hand_sizes[seat]) as f32
+    }
+
+    /// The seat that would play next if the direction were reversed.
+    fn previous_seat(view: &PlayerView) -> usize {
+        *view.opponents().last().unwrap()
+    }
+
+    fn score(&self, view: &PlayerView, action: Action, others: bool) -> f32 {
+        let w = &self.weights;
+        match action {
+            Action::Play(card) => self.score_play(view, card, others),
+            Action::Draw => -2.0 - view.pending_draw as f32,
+            Action::Pass => -1.0,
+            Action::Accept => -(view.pending_draw as f32),
+            Action::Challenge => 0.0,
+            Action::Swap(seat) => w.swap * (view.hand.len() as f32 - view.hand_sizes[seat] as f32),
+        }
+    }
+
+    /// `others` is whether there is anything else it could do instead.
+    fn score_play(&self, view: &PlayerView, card: Card, others: bool) -> f32 {
+        let w = &self.weights;
+        let left = view.hand.len() - 1;
+        let next = view.next_seat();
+        let mut score = w.points * card.face.points() as f32 / 50.0;
+
+        if !card.face.is_wild() {
+            let same = view.hand.iter().filter(|c| !c.face.is_wild() && c.color == card.color).count() - 1;
+            score += w.color_majority * same as f32 / left.max(1) as f32;
+        } else if others && left > 1 {
+            score -= match card.face {
+                Face::DrawFour => w.hold_draw_four,
+                _ => w.hold_wild,
+            };
+        }
+
+        score += match card.face {
+            Face::DrawFour | Face::DrawTwo | Face::Skip => w.attack * self.danger(view, next),
+            Face::Reverse if view.hand_sizes.len() == 2 => w.attack * self.danger(view, next),
+            Face::Reverse => w.attack * (self.danger(view, next) - self.danger(view, Expert::previous_seat(view))),
+            Face::Seven if view.rules.seven_o => {
+                let smallest = view.opponents().iter().map(|&seat| view.hand_sizes[seat]).min().unwrap();
+                w.swap * (left as f32 - smallest as f32)
+            },
+            Face::Zero if view.rules.seven_o => {
+                //our hand moves on, and we get the one from the seat behind us
+                w.swap * (left as f32 - view.hand_sizes[Expert::previous_seat(view)] as f32)
+            },
+            _ => 0.0,
+        };
+
+        //answering a penalty with a stack beats swallowing it
+        score + view.pending_draw as f32
+    }
+}
+
+impl Strategy for Expert {
+    fn name(&self) -> &str {
+        "expert"
+    }
+
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        let plays = legal.iter().filter(|action| matches!(action, Action::Play(_))).count();
+
+        let mut best = legal[0];
+        let mut best_score = f32::MIN;
+        for &action in legal {
+            let others = match action {
+                Action::Play(card) if card.face.is_wild() => legal.iter().any(|other| match other {
+                    Action::Play(other) => !other.face.is_wild(),
+                    _ => false,
+                }),
+                _ => plays > 1,
+            };
+            let score = self.score(view, action, others);
+            if score > best_score {
+                best = action;
+                best_score = score;
+            }
+        }
+        best
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        let estimate = self.beliefs.estimate(view);
+        let w = &self.weights;
+        let score = |color: Color| {
+            let held = view.hand.iter().filter(|c| !c.face.is_wild() && c.color == color).count() as f32;
+            let lacking: f32 = view.opponents().iter()
+                .map(|&seat| (1.0 - estimate.holds_color(seat, color)) as f32 * (1.0 + self.danger(view, seat)))
+                .sum();
+            w.color_in_hand * held + w.opponent_lacks * lacking
+        };
+
+        let mut best = COLORS[0];
+        for &color in COLORS.iter().skip(1) {
+            if score(color) > score(best) {
+                best = color;
+            }
+        }
+        best
+    }
+
+    fn challenge(&mut self, view: &PlayerView) -> bool {
+        let offender = match view.phase {
+            Phase::Challenge { offender } => offender,
+            _ => return false,
+        };
+
+        //the color the Draw Four was played on
+        let color = match view.discard.get(1) {
+            Some(card) => card.color,
+            None => return false,
+        };
+
+        let held = self.beliefs.estimate(view).holds_color(offender, color);
+        held as f32 > self.weights.challenge_threshold
+    }
+
+    fn call_uno(&mut self, view: &PlayerView) -> bool {
+        view.hand.len() == 2
+    }
+
+    fn observe(&mut self, event: &Event) {
+        self.beliefs.observe(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::play_out;
+    use crate::rules::Rules;
+    use crate::strategy::by_name;
+    use crate::uno::Uno;
+
+    /// Games the expert won out of `games` against `opponent`, alternating seats.
+    fn wins_against(opponent: &str, games: u64) -> u64 {
+        (0..games).filter(|&seed| {
+            let expert_seat = (seed % 2) as usize;
+            let strategies: Vec<Box<dyn Strategy>> = (0..2).map(|seat| match seat == expert_seat {
+                true => Box::new(Expert::default()) as Box<dyn Strategy>,
+                false => by_name(opponent, seed).unwrap(),
+            }).collect();
+            let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), seed);
+            play_out(uno, strategies, 10_000).0 == Some(expert_seat)
+        }).count() as u64
+    }
+
+    #[test]
+    fn beats_simpler_bots() {
+        assert!(wins_against("random", 400) > 340);
+        assert!(wins_against("basic", 400) > 220);
+        assert!(wins_against("greedy", 400) > 220);
+    }
+
+    #[test]
+    fn holds_wilds_back() {
+        let mut expert = Expert::default();
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 1);
+        let mut view = uno.view(0);
+        view.hand = vec!["R3".parse().unwrap(), "RC".parse().unwrap(), "B5".parse().unwrap(), "B6".parse().unwrap()];
+        view.top = "R9".parse().unwrap();
+        let legal = [Action::Play("R3".parse().unwrap()), Action::Play("RC".parse().unwrap()), Action::Draw];
+        assert_eq!(Action::Play("R3".parse().unwrap()), expert.choose_action(&view, &legal));
+    }
+
+    #[test]
+    fn declares_colors_opponents_lack() {
+        let mut expert = Expert::default();
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 1);
+        let mut view = uno.view(0);
+        view.hand = vec!["R3".parse().unwrap(), "G3".parse().unwrap()];
+
+        expert.observe(&Event::Played { seat: 0, card: "G5".parse().unwrap() });
+        expert.observe(&Event::Drew { seat: 1, count: 1, forced: false });
+        assert_eq!(Color::Green, expert.choose_color(&view));
+    }
+}
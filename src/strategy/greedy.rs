@@ -0,0 +1,39 @@
+use super::{most_held_color, Strategy};
+use crate::card::color::Color;
+use crate::uno::Action;
+use crate::view::PlayerView;
+
+/// Gets rid of its most valuable card first and declares the color it holds
+/// most of. Only draws when it has nothing to play.
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        let best = legal.iter().filter_map(|&action| match action {
+            Action::Play(card) => Some(card),
+            _ => None,
+        }).max_by_key(|card| card.face.points());
+
+        if let Some(card) = best {
+            return Action::Play(card);
+        }
+
+        let swap = legal.iter().filter_map(|&action| match action {
+            Action::Swap(seat) => Some(seat),
+            _ => None,
+        }).min_by_key(|&seat| view.hand_sizes[seat]);
+
+        match swap {
+            Some(seat) => Action::Swap(seat),
+            None => legal[0],
+        }
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        most_held_color(&view.hand)
+    }
+}
@@ -0,0 +1,341 @@
+use super::{most_held_color, Strategy};
+use crate::belief::Beliefs;
+use crate::card::{color::Color, Card};
+use crate::event::Event;
+use crate::uno::{Action, Phase, Uno};
+use crate::view::PlayerView;
+
+use shuffle::Xorshift;
+use std::time::{Duration, Instant};
+
+const COLORS: [Color; 4] = [Color::Red, Color::Green, Color::Blue, Color::Yellow];
+
+/// How long the search runs for and how it explores.
+#[derive(Clone, PartialEq, Debug)]
+pub struct IsmctsConfig {
+    /// Sampled games to search through for each decision.
+    pub iterations: usize,
+    /// Stops the search early once this much time has passed. How far it got
+    /// then depends on the machine, so leave this unset for repeatable play.
+    pub time_limit: Option<Duration>,
+    /// How strongly the search favours moves it knows little about.
+    pub exploration: f32,
+    /// Moves played past the search tree before an unfinished game is scored.
+    pub rollout_depth: usize,
+}
+
+impl Default for IsmctsConfig {
+    fn default() -> IsmctsConfig {
+        IsmctsConfig {
+            iterations: 1000,
+            time_limit: None,
+            exploration: 0.7,
+            rollout_depth: 300,
+        }
+    }
+}
+
+/// Information set Monte Carlo tree search.
+///
+/// Every iteration deals the cards it can't see at random, consistent with
+/// its own hand, the discard pile, and how many cards everyone holds, and
+/// weighted by what the other players' moves suggest they hold. It then
+/// plays that game out with the engine's own rules. The statistics are
+/// shared across all the sampled games, so moves are judged by how well they
+/// do whatever the hidden cards turn out to be.
+pub struct Ismcts {
+    config: IsmctsConfig,
+    rng: Xorshift,
+    beliefs: Beliefs,
+    //the color for the wild the last search picked
+    color: Option<Color>,
+    //the move found while deciding whether to challenge
+    planned: Option<Action>,
+}
+
+struct Node {
+    action: Action,
+    //who made the move leading here
+    seat: usize,
+    children: Vec<usize>,
+    visits: u32,
+    //how often this move was legal when its parent was visited
+    available: u32,
+    reward: f32,
+}
+
+impl Ismcts {
+    /// The same seed makes the same decisions, as long as no time limit is set.
+    pub fn new(config: IsmctsConfig, seed: u64) -> Ismcts {
+        Ismcts { config, rng: Xorshift::new(seed), beliefs: Beliefs::new(), color: None, planned: None }
+    }
+
+    pub fn config(&self) -> &IsmctsConfig {
+        &self.config
+    }
+
+    /// The most visited move from `view`, with a wild carrying its color.
+    fn search(&mut self, view: &PlayerView) -> Action {
+        //the root's action is never used
+        let mut nodes = vec![Node::new(Action::Draw, view.seat)];
+        let start = Instant::now();
+        let estimate = self.beliefs.estimate(view);
+
+        for i in 0..self.config.iterations.max(1) {
+            //one game is always searched, so there is a move to give
+            if i > 0 && self.config.time_limit.is_some_and(|limit| start.elapsed() >= limit) {
+                break;
+            }
+
+            let mut game = Uno::determinize_with(view, &estimate, &mut self.rng);
+            let mut path = Vec::new();
+            let mut node = 0;
+
+            while game.winner().is_none() {
+                let moves = moves(&game);
+                let known: Vec<usize> = nodes[node].children.iter().copied()
+                    .filter(|&child| moves.iter().any(|&action| same(action, nodes[child].action)))
+                    .collect();
+
+                //expand a move never tried from here, then stop
+                let untried: Vec<Action> = moves.into_iter()
+                    .filter(|&action| !known.iter().any(|&child| same(action, nodes[child].action)))
+                    .collect();
+                if !untried.is_empty() {
+                    let action = untried[self.rng.below(untried.len())];
+                    nodes.push(Node::new(action, game.current_seat()));
+                    let child = nodes.len() - 1;
+                    nodes[node].children.push(child);
+                    path.push(child);
+                    play(&mut game, action);
+                    break;
+                }
+
+                known.iter().for_each(|&child| nodes[child].available += 1);
+                node = *known.iter()
+                    .max_by(|&&a, &&b| self.ucb(&nodes[a]).partial_cmp(&self.ucb(&nodes[b])).unwrap())
+                    .unwrap();
+                path.push(node);
+                play(&mut game, nodes[node].action);
+            }
+
+            let rewards = self.rollout(&mut game);
+            for &node in path.iter() {
+                nodes[node].visits += 1;
+                nodes[node].reward += rewards[nodes[node].seat];
+            }
+        }
+
+        match nodes[0].children.iter().max_by_key(|&&child| nodes[child].visits) {
+            Some(&best) => nodes[best].action,
+            //the sampled game had no move left to try, so take any that is legal
+            None => moves(&Uno::determinize_with(view, &estimate, &mut self.rng)).first().copied().unwrap_or(Action::Draw),
+        }
+    }
+
+    fn ucb(&self, node: &Node) -> f32 {
+        let mean = node.reward / node.visits as f32;
+        mean + self.config.exploration * ((node.available as f32).ln() / node.visits as f32).sqrt()
+    }
+
+    /// Finishes `game` with a quick policy and scores it for every seat.
+    fn rollout(&mut self, game: &mut Uno) -> Vec<f32> {
+        for _ in 0..self.config.rollout_depth {
+            if game.winner().is_some() {
+                break;
+            }
+            let action = rollout_action(game, &mut self.rng);
+            play(game, action);
+        }
+
+        let sizes: Vec<usize> = game.players().iter().map(|p| p.get_hand().len()).collect();
+        if let Some(winner) = game.winner() {
+            return (0..sizes.len()).map(|seat| if seat == winner { 1.0 } else { 0.0 }).collect();
+        }
+
+        //nobody won in time, so share the win among the smallest hands
+        let fewest = *sizes.iter().min().unwrap();
+        let leaders = sizes.iter().filter(|&&size| size == fewest).count() as f32;
+        sizes.iter().map(|&size| if size == fewest { 1.0 / leaders } else { 0.0 }).collect()
+    }
+
+    fn decide(&mut self, view: &PlayerView) -> Action {
+        let action = self.search(view);
+        if let Action::Play(card) = action {
+            if card.face.is_wild() {
+                self.color = Some(card.color);
+            }
+        }
+        action
+    }
+}
+
+impl Node {
+    fn new(action: Action, seat: usize) -> Node {
+        Node { action, seat, children: Vec::new(), visits: 0, available: 1, reward: 0.0 }
+    }
+}
+
+impl Strategy for Ismcts {
+    fn name(&self) -> &str {
+        "ismcts"
+    }
+
+    fn choose_action(&mut self, view: &PlayerView, legal: &[Action]) -> Action {
+        if let Some(action) = self.planned.take() {
+            if legal.contains(&action) {
+                return action;
+            }
+        }
+
+        match legal {
+            [Action::Play(card)] if card.face.is_wild() => self.decide(view),
+            [action] => *action,
+            _ => self.decide(view),
+        }
+    }
+
+    fn choose_color(&mut self, view: &PlayerView) -> Color {
+        self.color.take().unwrap_or_else(|| most_held_color(&view.hand))
+    }
+
+    fn challenge(&mut self, view: &PlayerView) -> bool {
+        match self.decide(view) {
+            Action::Challenge => true,
+            action => {
+                self.planned = Some(action);
+                false
+            },
+        }
+    }
+
+    fn observe(&mut self, event: &Event) {
+        self.beliefs.observe(event);
+    }
+}
+
+/// Like `==`, except wild cards declaring different colors are different moves.
+fn same(a: Action, b: Action) -> bool {
+    match (a, b) {
+        (Action::Play(a), Action::Play(b)) => a == b && a.color == b.color,
+        _ => a == b,
+    }
+}
+
+/// The legal actions of `game`, with a wild card once for each color it could declare.
+fn moves(game: &Uno) -> Vec<Action> {
+    let mut moves = Vec::new();
+    for action in game.legal_actions() {
+        match action {
+            Action::Play(card) if card.face.is_wild() => {
+                COLORS.iter().for_each(|&color| moves.push(Action::Play(Card::new(color, card.face))));
+            },
+            action => moves.push(action),
+        }
+    }
+    moves
+}
+
+/// Applies `action`, calling UNO first whenever it is needed.
+fn play(game: &mut Uno, action: Action) {
+    if let Action::Play(_) = action {
+        if game.players()[game.current_seat()].get_hand().len() == 2 {
+            game.call_uno();
+        }
+    }
+    game.apply(action);
+}
+
+/// A cheap policy for playouts: any playable card, otherwise draw.
+fn rollout_action(game: &Uno, rng: &mut Xorshift) -> Action {
+    let legal = game.legal_actions();
+    let hand = game.players()[game.current_seat()].get_hand();
+
+    if let Phase::Swap = game.phase() {
+        let sizes = |action: &&Action| match **action {
+            Action::Swap(seat) => game.players()[seat].get_hand().len(),
+            _ => usize::MAX,
+        };
+        return *legal.iter().min_by_key(sizes).unwrap();
+    }
+
+    let plays: Vec<Card> = legal.iter().filter_map(|&action| match action {
+        Action::Play(card) => Some(card),
+        _ => None,
+    }).collect();
+    if plays.is_empty() {
+        return match game.phase() {
+            Phase::Challenge { .. } => Action::Accept,
+            _ => legal[0],
+        };
+    }
+
+    //keep wilds for when nothing else fits
+    let colored: Vec<Card> = plays.iter().copied().filter(|card| !card.face.is_wild()).collect();
+    let plays = if colored.is_empty() { plays } else { colored };
+    let card = plays[rng.below(plays.len())];
+    match card.face.is_wild() {
+        true => Action::Play(Card::new(most_held_color(hand), card.face)),
+        false => Action::Play(card),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::play_out;
+    use crate::rules::Rules;
+    use crate::strategy::by_name;
+
+    fn config(iterations: usize) -> IsmctsConfig {
+        IsmctsConfig { iterations, ..IsmctsConfig::default() }
+    }
+
+    fn game(seed: u64, opponent: &str) -> (Option<usize>, Uno) {
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), seed);
+        let strategies = vec![Box::new(Ismcts::new(config(30), seed)) as Box<dyn Strategy>, by_name(opponent, seed).unwrap()];
+        play_out(uno, strategies, 10_000)
+    }
+
+    #[test]
+    fn beats_random() {
+        let wins = (0..12).filter(|&seed| game(seed, "random").0 == Some(0)).count();
+        assert!(wins >= 10, "only won {} of 12", wins);
+    }
+
+    #[test]
+    fn seeded_searches_repeat() {
+        let (_, a) = game(5, "greedy");
+        let (_, b) = game(5, "greedy");
+        assert_eq!(a.events(), b.events());
+    }
+
+    #[test]
+    fn no_time_still_gives_a_move() {
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 4);
+        let mut bot = Ismcts::new(IsmctsConfig { time_limit: Some(Duration::ZERO), ..config(200) }, 1);
+        let (view, legal) = (uno.view(uno.current_seat()), uno.legal_actions());
+        assert!(legal.contains(&bot.choose_action(&view, &legal)));
+        bot.challenge(&view);
+    }
+
+    #[test]
+    fn takes_a_sure_win() {
+        //the opponent is down to one card: the skip keeps the turn and wins with
+        //the three, while playing the three first lets them go out
+        let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::classic(), 3);
+        let mut view = uno.view(0);
+        view.hand = vec!["R3".parse().unwrap(), "RS".parse().unwrap()];
+        view.hand_sizes = vec![2, 1];
+        assert_eq!((0, Phase::Play), (view.current_seat, view.phase));
+        view.top = "R9".parse().unwrap();
+        view.discard[0] = view.top;
+
+        let skip = Action::Play("RS".parse().unwrap());
+        let legal = [Action::Play("R3".parse().unwrap()), skip, Action::Draw];
+        for seed in 0..4 {
+            let mut bot = Ismcts::new(config(200), seed);
+            assert_eq!(skip, bot.choose_action(&view, &legal));
+        }
+    }
+}
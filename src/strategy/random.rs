@@ -0,0 +1,35 @@
+use super::Strategy;
+use crate::card::color::Color;
+use crate::uno::Action;
+use crate::view::PlayerView;
+
+use shuffle::Xorshift;
+
+/// Picks uniformly from whatever it is allowed to do.
+pub struct Random {
+    rng: Xorshift,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Random {
+        Random { rng: Xorshift::new(seed) }
+    }
+}
+
+impl Strategy for Random {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn choose_action(&mut self, _view: &PlayerView, legal: &[Action]) -> Action {
+        legal[self.rng.below(legal.len())]
+    }
+
+    fn choose_color(&mut self, _view: &PlayerView) -> Color {
+        [Color::Red, Color::Green, Color::Blue, Color::Yellow][self.rng.below(4)]
+    }
+
+    fn challenge(&mut self, _view: &PlayerView) -> bool {
+        self.rng.below(2) == 0
+    }
+}
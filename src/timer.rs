@@ -0,0 +1,286 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Where the time comes from, so that tests can move it along by hand.
+pub trait Clock: Send {
+    fn now(&self) -> Instant;
+}
+
+/// The time as the operating system tells it.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> ManualClock {
+        ManualClock::new()
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock { start: Instant::now(), elapsed: Arc::new(Mutex::new(Duration::from_secs(0))) }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.elapsed.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+}
+
+/// What is done for a player who runs out of time.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OnTimeout {
+    /// Draw, and keep the card if it could be played. Draw Fours are
+    /// accepted rather than challenged.
+    DrawAndPass,
+    /// Let the basic bot make the move.
+    AutoPlay,
+}
+
+impl FromStr for OnTimeout {
+    type Err = TimeError;
+
+    fn from_str(s: &str) -> Result<OnTimeout, TimeError> {
+        match s.trim().to_lowercase().as_ref() {
+            "draw" | "draw-and-pass" => Ok(OnTimeout::DrawAndPass),
+            "auto" | "auto-play" => Ok(OnTimeout::AutoPlay),
+            _ => Err(TimeError::UnknownTimeout(s.to_string())),
+        }
+    }
+}
+
+/// How long people get to move, like the time controls of chess.
+///
+/// Each turn has to be finished within `per_turn`, and all of a player's
+/// turns together within `bank`, which grows by `increment` after every
+/// move. Someone whose bank has run out has their moves made for them as
+/// soon as it is their turn. Computer players are never timed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct TimeControl {
+    pub per_turn: Option<Duration>,
+    pub bank: Option<Duration>,
+    pub increment: Duration,
+    pub on_timeout: OnTimeout,
+}
+
+impl Default for TimeControl {
+    /// No time limits at all.
+    fn default() -> TimeControl {
+        TimeControl { per_turn: None, bank: None, increment: Duration::from_secs(0), on_timeout: OnTimeout::DrawAndPass }
+    }
+}
+
+impl TimeControl {
+    pub fn is_timed(&self) -> bool {
+        self.per_turn.is_some() || self.bank.is_some()
+    }
+}
+
+/// Reads a number of seconds such as `30` or `0.5`, for command lines.
+pub fn parse_seconds(text: &str) -> Result<Duration, TimeError> {
+    match text.trim().parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(Duration::from_secs_f64(seconds)),
+        _ => Err(TimeError::InvalidSeconds(text.to_string())),
+    }
+}
+
+/// The clocks of every seat in a game. `Driver::set_timers` keeps them
+/// running as the turns go by.
+pub struct Timers {
+    pub control: TimeControl,
+    clock: Box<dyn Clock>,
+    banks: Vec<Duration>,
+    //the seat being timed, on which turn, and since when
+    running: Option<(usize, usize, Instant)>,
+}
+
+impl Timers {
+    pub fn new(control: TimeControl, players: usize, clock: Box<dyn Clock>) -> Timers {
+        let bank = control.bank.unwrap_or_else(|| Duration::from_secs(0));
+        Timers { control, clock, banks: vec![bank; players], running: None }
+    }
+
+    /// What is left of a seat's time bank, counting the turn it is taking.
+    pub fn bank(&self, seat: usize) -> Option<Duration> {
+        self.control.bank?;
+        Some(self.banks[seat].saturating_sub(self.used(seat)))
+    }
+
+    /// How long the seat being timed has to finish its turn. `None` when
+    /// nobody is being timed.
+    pub fn remaining(&self) -> Option<Duration> {
+        let (seat, _, _) = self.running?;
+        let turn = self.control.per_turn.map(|limit| limit.saturating_sub(self.used(seat)));
+        match (turn, self.bank(seat)) {
+            (Some(turn), Some(bank)) => Some(turn.min(bank)),
+            (turn, bank) => turn.or(bank),
+        }
+    }
+
+    pub fn expired(&self) -> bool {
+        self.remaining() == Some(Duration::from_secs(0))
+    }
+
+    /// The turn being taken now, by `seat` if it is to be timed. Whoever was
+    /// being timed before is charged for their turn if it has ended.
+    pub(crate) fn track(&mut self, seat: Option<usize>, turn: usize) {
+        if !self.control.is_timed() {
+            return;
+        }
+        if let Some((running, running_turn, _)) = self.running {
+            if seat == Some(running) && turn == running_turn {
+                return;
+            }
+            self.banks[running] = self.banks[running].saturating_sub(self.used(running)) + self.control.increment;
+        }
+        self.running = seat.map(|seat| (seat, turn, self.clock.now()));
+    }
+
+    /// How long `seat` has spent on the turn it is taking.
+    fn used(&self, seat: usize) -> Duration {
+        match self.running {
+            Some((running, _, start)) if running == seat => self.clock.now().saturating_duration_since(start),
+            _ => Duration::from_secs(0),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TimeError {
+    UnknownTimeout(String),
+    InvalidSeconds(String),
+}
+
+impl Display for TimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimeError::UnknownTimeout(name) => write!(f, "unknown timeout action \"{}\" (expected draw or auto)", name),
+            TimeError::InvalidSeconds(text) => write!(f, "\"{}\" is not a number of seconds", text),
+        }
+    }
+}
+
+impl Error for TimeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{Driver, Seat};
+    use crate::rules::Rules;
+    use crate::strategy;
+    use crate::uno::{Action, Phase, Uno};
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    /// A game between two people with the given time control.
+    fn game(control: TimeControl, clock: &ManualClock) -> Driver {
+        let uno = Uno::create_game_with(vec!["Ann".into(), "Bo".into()], Rules::default(), 3);
+        let mut driver = Driver::new(uno, vec![Seat::Human, Seat::Human]);
+        driver.set_timers(Timers::new(control, 2, Box::new(clock.clone())));
+        driver
+    }
+
+    /// Draws, and passes if need be, until the turn moves on.
+    fn finish_turn(driver: &mut Driver) {
+        let seat = driver.uno().current_seat();
+        while driver.uno().current_seat() == seat {
+            driver.apply(Action::Draw);
+            if driver.uno().phase() == Phase::Drawn {
+                driver.apply(Action::Pass);
+            }
+        }
+    }
+
+    #[test]
+    fn turns_and_banks_run_down() {
+        let clock = ManualClock::new();
+        let control = TimeControl { per_turn: Some(secs(10)), bank: Some(secs(15)), increment: secs(1), ..TimeControl::default() };
+        let mut driver = game(control, &clock);
+        let first = driver.uno().current_seat();
+        let second = 1 - first;
+        assert_eq!(Some(secs(10)), driver.time_left());
+
+        clock.advance(secs(4));
+        assert_eq!(Some(secs(6)), driver.time_left());
+        assert_eq!(Some(secs(11)), driver.timers().unwrap().bank(first));
+        assert!(driver.time_out().is_empty());
+        finish_turn(&mut driver);
+        //charged for the turn, then given the increment
+        assert_eq!(Some(secs(12)), driver.timers().unwrap().bank(first));
+        assert_eq!(Some(secs(15)), driver.timers().unwrap().bank(second));
+
+        clock.advance(secs(9));
+        finish_turn(&mut driver);
+        assert_eq!(Some(secs(7)), driver.timers().unwrap().bank(second));
+        clock.advance(secs(2));
+        finish_turn(&mut driver);
+
+        //now the bank runs out before the turn limit does
+        assert_eq!(Some(secs(7)), driver.time_left());
+        clock.advance(secs(7));
+        assert!(driver.timers().unwrap().expired());
+    }
+
+    #[test]
+    fn default_actions_end_the_turn() {
+        for &on_timeout in [OnTimeout::DrawAndPass, OnTimeout::AutoPlay].iter() {
+            let clock = ManualClock::new();
+            let mut driver = game(TimeControl { per_turn: Some(secs(30)), on_timeout, ..TimeControl::default() }, &clock);
+            for _ in 0..20 {
+                let (seat, turn) = (driver.uno().current_seat(), driver.uno().current_turn());
+                clock.advance(secs(31));
+                assert!(driver.timers().unwrap().expired());
+                let results = driver.time_out();
+                assert!(!results.is_empty());
+                if driver.uno().winner().is_some() {
+                    break;
+                }
+                assert!(driver.uno().current_turn() > turn || driver.uno().current_seat() != seat);
+                assert_eq!(Some(secs(30)), driver.time_left());
+            }
+            if on_timeout == OnTimeout::DrawAndPass {
+                assert!(driver.uno().players().iter().all(|player| player.get_hand().len() > 7));
+            }
+        }
+    }
+
+    #[test]
+    fn bots_and_untimed_games_have_no_clock() {
+        let uno = Uno::create_game_with(vec!["Ann".into(), "Bo".into()], Rules::default(), 3);
+        let mut driver = Driver::new(uno, vec![Seat::Human, Seat::Computer(strategy::by_name("basic", 0).unwrap())]);
+        assert_eq!(None, driver.time_left());
+        let clock = ManualClock::new();
+        driver.set_timers(Timers::new(TimeControl { per_turn: Some(secs(5)), ..TimeControl::default() }, 2, Box::new(clock.clone())));
+        driver.run();
+        assert_eq!(0, driver.uno().current_seat());
+        assert_eq!(Some(secs(5)), driver.time_left());
+
+        assert_eq!(Ok(Duration::from_millis(500)), parse_seconds("0.5"));
+        assert!(parse_seconds("-1").is_err());
+        assert_eq!(Ok(OnTimeout::AutoPlay), "auto".parse());
+        assert!("wait".parse::<OnTimeout>().is_err());
+    }
+}
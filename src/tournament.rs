@@ -0,0 +1,378 @@
+use crate::rules::{RuleError, Rules};
+use crate::sim::{bot_seed, in_parallel, play_game, GameRecord};
+use crate::strategy::{self, Strategy};
+
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// A strategy taking part in a tournament, under a name of its own so that
+/// differently tuned versions of one bot can play each other.
+pub struct Entrant {
+    pub name: String,
+    make: Box<dyn Fn(u64) -> Box<dyn Strategy> + Send + Sync>,
+}
+
+impl Entrant {
+    /// `make` creates a fresh player for every game from that game's seed.
+    pub fn new<S, F>(name: S, make: F) -> Entrant
+    where
+        S: ToString,
+        F: Fn(u64) -> Box<dyn Strategy> + Send + Sync + 'static,
+    {
+        Entrant { name: name.to_string(), make: Box::new(make) }
+    }
+
+    /// One of the strategies `strategy::by_name` knows.
+    pub fn builtin(name: &str) -> Option<Entrant> {
+        strategy::by_name(name, 0)?;
+        let owned = name.to_string();
+        Some(Entrant::new(name.trim().to_lowercase(), move |seed| strategy::by_name(&owned, seed).unwrap()))
+    }
+
+    pub fn create(&self, seed: u64) -> Box<dyn Strategy> {
+        (self.make)(seed)
+    }
+}
+
+/// How tables are drawn up.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// Every group of `table_size` entrants meets once.
+    RoundRobin,
+    /// Each round seats entrants with similar scores together, avoiding
+    /// repeat meetings where it can.
+    Swiss { rounds: usize },
+}
+
+pub struct Tournament {
+    pub entrants: Vec<Entrant>,
+    pub rules: Rules,
+    pub format: Format,
+    /// Players at each table.
+    pub table_size: usize,
+    /// Games played at each table. The seating rotates after every game.
+    pub games: usize,
+    /// Games are dealt with seeds counting up from this one.
+    pub seed: u64,
+    pub max_turns: usize,
+    pub threads: usize,
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Entrant>, rules: Rules) -> Tournament {
+        Tournament {
+            entrants,
+            rules,
+            format: Format::RoundRobin,
+            table_size: 2,
+            games: 100,
+            seed: 0,
+            max_turns: 10_000,
+            threads: 1,
+        }
+    }
+
+    pub fn check(&self) -> Result<(), TournamentError> {
+        if self.entrants.len() < self.table_size {
+            return Err(TournamentError::TooFewEntrants { entrants: self.entrants.len(), table_size: self.table_size });
+        }
+        let names: Vec<&str> = self.entrants.iter().map(|entrant| entrant.name.as_ref()).collect();
+        if let Some(name) = names.iter().enumerate().find(|(i, name)| names[..*i].contains(name)).map(|(_, name)| name) {
+            return Err(TournamentError::DuplicateName(name.to_string()));
+        }
+        self.rules.check(self.table_size).map_err(TournamentError::Rules)
+    }
+
+    /// Plays the whole tournament. Panics if `check` would fail.
+    pub fn run(&self) -> Standings {
+        if let Err(e) = self.check() {
+            panic!("{}", e);
+        }
+
+        let mut records = Vec::new();
+        match self.format {
+            Format::RoundRobin => self.play_round(&combinations(self.entrants.len(), self.table_size), &mut records),
+            Format::Swiss { rounds } => {
+                for _ in 0..rounds {
+                    let tables = self.swiss_tables(&records);
+                    self.play_round(&tables, &mut records);
+                }
+            },
+        }
+
+        let names: Vec<String> = self.entrants.iter().map(|entrant| entrant.name.clone()).collect();
+        Standings { leaderboard: leaderboard(&names, &records), records }
+    }
+
+    /// Plays every table, adding the games to `records` in seed order.
+    fn play_round(&self, tables: &[Vec<usize>], records: &mut Vec<GameRecord>) {
+        let first = self.seed.wrapping_add(records.len() as u64);
+        let played = in_parallel(tables.len() * self.games, self.threads, |i| {
+            let (table, game) = (&tables[i / self.games], i % self.games);
+            let mut seats = table.clone();
+            seats.rotate_left(game % table.len());
+
+            let seed = first.wrapping_add(i as u64);
+            let bots = seats.iter().map(|&entrant| self.entrants[entrant].name.clone()).collect();
+            let strategies = seats.iter().enumerate().map(|(seat, &entrant)| self.entrants[entrant].create(bot_seed(seed, seat))).collect();
+            play_game(&self.rules, bots, strategies, seed, self.max_turns)
+        });
+        records.extend(played);
+    }
+
+    /// Tables for the next Swiss round, best scores first. Anyone left over
+    /// sits out the round, unless there are enough of them for a smaller table.
+    fn swiss_tables(&self, records: &[GameRecord]) -> Vec<Vec<usize>> {
+        let names: Vec<&str> = self.entrants.iter().map(|entrant| entrant.name.as_ref()).collect();
+        let mut scores = vec![(0usize, 0usize); names.len()];
+        let mut met = vec![vec![false; names.len()]; names.len()];
+        for record in records {
+            let seats: Vec<usize> = record.bots.iter().map(|bot| names.iter().position(|name| name == bot).unwrap()).collect();
+            for (seat, &entrant) in seats.iter().enumerate() {
+                scores[entrant].1 += 1;
+                if record.winner == Some(seat) {
+                    scores[entrant].0 += 1;
+                }
+                seats.iter().for_each(|&other| met[entrant][other] = true);
+            }
+        }
+
+        //by win rate, then by seating order so the first round is the entry list
+        let mut order: Vec<usize> = (0..names.len()).collect();
+        let rate = |(wins, games): (usize, usize)| wins as f64 / games.max(1) as f64;
+        order.sort_by(|&a, &b| rate(scores[b]).partial_cmp(&rate(scores[a])).unwrap().then(a.cmp(&b)));
+
+        let mut tables = Vec::new();
+        while order.len() >= 2 {
+            let mut table = vec![order.remove(0)];
+            while table.len() < self.table_size && !order.is_empty() {
+                let fresh = order.iter().position(|&other| table.iter().all(|&seated| !met[seated][other]));
+                table.push(order.remove(fresh.unwrap_or(0)));
+            }
+            tables.push(table);
+        }
+        tables
+    }
+}
+
+/// Every way of picking `size` of `0..count`, in order.
+fn combinations(count: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    (0..count)
+        .flat_map(|last| combinations(last, size - 1).into_iter().map(move |mut rest| {
+            rest.push(last);
+            rest
+        }))
+        .collect()
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TournamentError {
+    TooFewEntrants { entrants: usize, table_size: usize },
+    DuplicateName(String),
+    Rules(RuleError),
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TournamentError::TooFewEntrants { entrants, table_size } => {
+                write!(f, "{} entrants cannot fill a table of {}", entrants, table_size)
+            },
+            TournamentError::DuplicateName(name) => write!(f, "two entrants are called {}", name),
+            TournamentError::Rules(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for TournamentError {}
+
+/// Every game played, and the ratings they add up to.
+#[derive(Clone, Debug)]
+pub struct Standings {
+    pub records: Vec<GameRecord>,
+    /// Best rated first.
+    pub leaderboard: Vec<Standing>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub games: usize,
+    pub wins: usize,
+    /// On the Elo scale, where 400 points is ten-to-one odds. The field averages 1500.
+    pub rating: f64,
+    /// Half the width of the 95% confidence interval around `rating`.
+    pub margin: f64,
+}
+
+/// Rates everyone named in `records` with a Bradley-Terry model, the
+/// order-independent version of Elo.
+///
+/// A game counts as a win for the winner over each of the other players at
+/// the table. Unfinished games don't count. Each entrant also gets one drawn
+/// game against an average player, so an unbeaten entrant still gets a finite
+/// rating.
+pub fn leaderboard(names: &[String], records: &[GameRecord]) -> Vec<Standing> {
+    let count = names.len();
+    let mut games = vec![0usize; count];
+    let mut wins = vec![0usize; count];
+    //meetings[i][j] is how many decided pairs i and j were part of
+    let mut meetings = vec![vec![0.0f64; count]; count];
+    let mut pair_wins = vec![0.0f64; count];
+
+    for record in records {
+        let seats: Vec<usize> = record.bots.iter().filter_map(|bot| names.iter().position(|name| name == bot)).collect();
+        if seats.len() != record.bots.len() {
+            continue;
+        }
+        seats.iter().for_each(|&entrant| games[entrant] += 1);
+
+        if let Some(winner) = record.winner {
+            let winner = seats[winner];
+            wins[winner] += 1;
+            for &loser in seats.iter().filter(|&&entrant| entrant != winner) {
+                pair_wins[winner] += 1.0;
+                meetings[winner][loser] += 1.0;
+                meetings[loser][winner] += 1.0;
+            }
+        }
+    }
+
+    //fit the strengths with the usual fixed-point iteration
+    let mut strength = vec![1.0f64; count];
+    for _ in 0..1000 {
+        let next: Vec<f64> = (0..count)
+            .map(|i| {
+                let expected: f64 = (0..count).filter(|&j| meetings[i][j] > 0.0).map(|j| meetings[i][j] / (strength[i] + strength[j])).sum::<f64>()
+                    + 1.0 / (strength[i] + 1.0);
+                (pair_wins[i] + 0.5) / expected
+            })
+            .collect();
+        let change = next.iter().zip(strength.iter()).map(|(a, b)| (a.ln() - b.ln()).abs()).fold(0.0, f64::max);
+        strength = next;
+        if change < 1e-9 {
+            break;
+        }
+    }
+
+    let scale = 400.0 / std::f64::consts::LN_10;
+    let mean = strength.iter().map(|s| s.ln()).sum::<f64>() / count.max(1) as f64;
+    let mut leaderboard: Vec<Standing> = (0..count)
+        .map(|i| {
+            //the information about entrant i's strength, ignoring how uncertain everyone else is
+            let p = |j_strength: f64| strength[i] / (strength[i] + j_strength);
+            let information: f64 = (0..count).map(|j| meetings[i][j] * p(strength[j]) * (1.0 - p(strength[j]))).sum::<f64>()
+                + p(1.0) * (1.0 - p(1.0));
+            Standing {
+                name: names[i].clone(),
+                games: games[i],
+                wins: wins[i],
+                rating: 1500.0 + scale * (strength[i].ln() - mean),
+                margin: 1.96 * scale / information.sqrt(),
+            }
+        })
+        .collect();
+
+    leaderboard.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap());
+    leaderboard
+}
+
+impl Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.leaderboard.iter().map(|standing| standing.name.len()).max().unwrap_or(0).max(3);
+        writeln!(f, "rank  {:<width$}  rating  95% ci   games   wins  win rate", "bot", width = width)?;
+        for (rank, standing) in self.leaderboard.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<4}  {:<width$}  {:>6.0}  {:>6}  {:>6}  {:>5}  {:>7.1}%",
+                rank + 1,
+                standing.name,
+                standing.rating,
+                format!("±{:.0}", standing.margin),
+                standing.games,
+                standing.wins,
+                100.0 * standing.wins as f64 / standing.games.max(1) as f64,
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entrants(names: &[&str]) -> Vec<Entrant> {
+        names.iter().map(|name| Entrant::builtin(name).unwrap()).collect()
+    }
+
+    #[test]
+    fn round_robin_meets_everyone() {
+        let mut tournament = Tournament::new(entrants(&["basic", "random", "greedy"]), Rules::official());
+        tournament.games = 10;
+        tournament.threads = 2;
+        let standings = tournament.run();
+
+        assert_eq!(30, standings.records.len());
+        assert!(standings.leaderboard.iter().all(|standing| standing.games == 20));
+        assert_eq!("random", standings.leaderboard[2].name);
+        assert_eq!(vec!["random", "basic"], standings.records[1].bots);
+        assert_eq!(vec![vec![0, 1], vec![0, 2], vec![1, 2]], combinations(3, 2));
+    }
+
+    #[test]
+    fn swiss_avoids_rematches() {
+        let mut tournament = Tournament::new(entrants(&["basic", "random", "greedy", "expert"]), Rules::official());
+        tournament.format = Format::Swiss { rounds: 3 };
+        tournament.games = 4;
+        let standings = tournament.run();
+
+        assert_eq!(3 * 2 * 4, standings.records.len());
+        let mut tables: Vec<Vec<String>> = standings.records.iter().step_by(4).map(|record| {
+            let mut bots = record.bots.clone();
+            bots.sort();
+            bots
+        }).collect();
+        tables.sort();
+        tables.dedup();
+        assert_eq!(6, tables.len());
+    }
+
+    #[test]
+    fn ratings_follow_results() {
+        let names = vec!["a".to_string(), "b".to_string()];
+        let record = |winner| GameRecord {
+            seed: 0,
+            bots: names.clone(),
+            winner: Some(winner),
+            turns: 1,
+            drawn: vec![0, 0],
+            penalties: 0,
+            penalty_cards: 0,
+            reshuffles: 0,
+            skips: 0,
+            reverses: 0,
+        };
+        let records: Vec<_> = (0..100).map(|i| record(if i % 4 == 0 { 1 } else { 0 })).collect();
+        let board = leaderboard(&names, &records);
+
+        assert_eq!("a", board[0].name);
+        //three to one is about 190 Elo points
+        let gap = board[0].rating - board[1].rating;
+        assert!((160.0..200.0).contains(&gap), "gap was {}", gap);
+        assert!((board[0].rating + board[1].rating - 3000.0).abs() < 1e-6);
+        assert!(board[0].margin > 0.0 && board[0].margin < 150.0);
+        assert!(leaderboard(&names, &records[..10])[0].margin > board[0].margin);
+    }
+
+    #[test]
+    fn bad_tournaments() {
+        assert!(Tournament::new(entrants(&["basic"]), Rules::official()).check().is_err());
+        assert!(Tournament::new(entrants(&["basic", "basic"]), Rules::official()).check().is_err());
+        assert!(Entrant::builtin("nobody").is_none());
+    }
+}
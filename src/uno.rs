@@ -1,8 +1,25 @@
+#[cfg(feature = "std")]
+mod save;
+
+#[cfg(feature = "std")]
+use crate::belief::{kind, Estimate};
 use crate::card::{color::*, face::*, *};
 use crate::deck::Deck;
 use crate::direction::*;
+use crate::event::Event;
 use crate::player::Player;
+use crate::rules::Rules;
+use crate::view::PlayerView;
+
+use shuffle::Xorshift;
+
+use alloc::vec;
+use alloc::vec::Vec;
 
+#[cfg(feature = "std")]
+pub use save::{SaveError, SAVE_VERSION};
+
+#[derive(Clone)]
 pub struct Uno {
     draw_deck: Deck,
     discard: Deck,
@@ -10,6 +27,48 @@ pub struct Uno {
     current_turn: usize,
     players: Vec<Player>,
     current_player: usize,
+    rules: Rules,
+    rng: Xorshift,
+    phase: Phase,
+    pending_draw: usize,
+    uno_called: bool,
+    //the card drawn in `Phase::Drawn`, and whether a challengeable Draw Four was a bluff
+    drawn: Option<Card>,
+    bluffed: bool,
+    events: Vec<Event>,
+}
+
+/// What the current player is being asked to do.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Play a card or draw.
+    Play,
+    /// The player drew a card that can be played (see `Uno::drawn_card`).
+    /// Play it or pass.
+    Drawn,
+    /// A Draw Four was played on the player, who may challenge it.
+    Challenge { offender: usize },
+    /// The player played a seven and picks whose hand to swap with.
+    Swap,
+    /// The game has been won.
+    GameOver { winner: usize },
+}
+
+/// Something the current player can do.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Play a card. Wild cards carry the color being declared.
+    Play(Card),
+    /// Draw a card, or take the pending penalty.
+    Draw,
+    /// End the turn after drawing instead of playing the drawn card.
+    Pass,
+    /// Challenge the Draw Four that was just played.
+    Challenge,
+    /// Take the Draw Four penalty without challenging.
+    Accept,
+    /// Swap hands with this seat after playing a seven.
+    Swap(usize),
 }
 
 impl Uno {
@@ -33,7 +92,59 @@ impl Uno {
         self.current_turn
     }
 
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Cards the current player will have to draw unless they stack on them.
+    pub fn pending_draw(&self) -> usize {
+        self.pending_draw
+    }
+
+    /// The card the current player just drew, while they decide whether to play it.
+    pub fn drawn_card(&self) -> Option<Card> {
+        match self.phase {
+            Phase::Drawn => self.drawn,
+            _ => None,
+        }
+    }
+
+    pub fn current_seat(&self) -> usize {
+        self.current_player
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        match self.phase {
+            Phase::GameOver { winner } => Some(winner),
+            _ => None,
+        }
+    }
+
+    /// Everything that has happened so far, oldest first.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn top_card(&self) -> Card {
+        self.discard.peek_top_card().copied().unwrap_or_else(|| Card::new(Color::Red, Face::ColorCard))
+    }
+
+    /// Starts a game seeded from the system clock.
+    #[cfg(feature = "std")]
     pub fn create_game(players: Vec<Player>) -> Uno {
+        Uno::start(players, Rules::default(), Xorshift::from_time())
+    }
+
+    /// Starts a game with the given rules. The same seed always deals the same game.
+    pub fn create_game_with(players: Vec<Player>, rules: Rules, seed: u64) -> Uno {
+        Uno::start(players, rules, Xorshift::new(seed))
+    }
+
+    fn start(players: Vec<Player>, rules: Rules, mut rng: Xorshift) -> Uno {
         if players.len() < 2 {
             panic!("Need at least two players to play!");
         }
@@ -42,75 +153,344 @@ impl Uno {
             panic!("Cannot have more than 8 players in a game!");
         }
 
+        if let Err(e) = rules.check(players.len()) {
+            panic!("{}", e);
+        }
+
+        let mut draw_deck = Deck::standard();
+        draw_deck.shuffle_with(&mut rng);
+
         let mut uno = Uno {
-            draw_deck: Deck::default(),
+            draw_deck,
             discard: Vec::with_capacity(108).into(),
             direction: GameDirection::Clockwise,
             current_turn: 1,
             players,
             current_player: 0,
+            rules,
+            rng,
+            phase: Phase::Play,
+            pending_draw: 0,
+            uno_called: false,
+            drawn: None,
+            bluffed: false,
+            events: Vec::new(),
         };
 
-        (0..7).into_iter().for_each(|_| {
-            (0..uno.players.len()).into_iter().for_each(|i| {
+        (0..uno.rules.hand_size).for_each(|_| {
+            (0..uno.players.len()).for_each(|i| {
                 let card = uno.draw_card();
                 uno.players[i].add_card(card);
             });
         });
 
-        let top = uno.draw_card();
+        //a wild can't start the discard pile, so put it back somewhere in the deck
+        let mut top = uno.draw_card();
+        for _ in 0..uno.draw_deck.len() {
+            if !top.face.is_wild() {
+                break;
+            }
+            let at = uno.rng.below(uno.draw_deck.len());
+            uno.draw_deck.insert(at, top);
+            top = uno.draw_card();
+        }
+        //out of luck that many times, so take the first card that can start
+        if top.face.is_wild() {
+            let at = uno.rng.below(uno.draw_deck.len());
+            uno.draw_deck.insert(at, top);
+            let at = uno.draw_deck.iter().rposition(|card| !card.face.is_wild()).expect("Rules::check leaves a card that can start");
+            top = uno.draw_deck.remove(at);
+        }
         uno.discard += top;
         uno
     }
 
+    /// A game that matches everything `view` shows, with the cards it can't
+    /// see dealt out at random: the other hands and the order of the draw pile.
+    ///
+    /// Players are named after their seats, and the event log starts empty.
+    pub fn determinize(view: &PlayerView, rng: &mut Xorshift) -> Uno {
+        Uno::deal_unseen(view, rng, |_, size, unseen, _, player| (0..size).for_each(|_| player.add_card(unseen.draw().unwrap())))
+    }
+
+    /// Like `determinize`, but deals each hand cards in proportion to how many
+    /// of each kind `estimate` expects it to hold.
+    #[cfg(feature = "std")]
+    pub fn determinize_with(view: &PlayerView, estimate: &Estimate, rng: &mut Xorshift) -> Uno {
+        Uno::deal_unseen(view, rng, |seat, size, unseen, rng, player| {
+            //how likely each unseen card is to be here rather than in a hand not dealt yet or the draw pile
+            let rest = |k: usize| estimate.draw_pile[k] + (seat..estimate.hands.len()).filter(|&s| s != view.seat).map(|s| estimate.hands[s][k]).sum::<f64>();
+            let mut weights: Vec<f64> = unseen.iter()
+                .map(|&card| (estimate.hands[seat][kind(card)] / rest(kind(card))).max(0.0))
+                .map(|weight| if weight.is_finite() { weight } else { 0.0 })
+                .collect();
+            for _ in 0..size {
+                let total: f64 = weights.iter().sum();
+                let at = match total > 0.0 {
+                    true => {
+                        let mut point = total * (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+                        weights.iter().position(|&weight| { point -= weight; point < 0.0 }).unwrap_or(weights.len() - 1)
+                    },
+                    false => rng.below(weights.len()),
+                };
+                //move the card to the end, where `draw` takes from
+                let last = unseen.len() - 1;
+                unseen.swap(at, last);
+                weights.swap_remove(at);
+                player.add_card(unseen.draw().unwrap());
+            }
+        })
+    }
+
+    /// Deals out what `view` can't see. `deal` gives the hand of `seat`
+    /// its `size` cards from the shuffled unseen ones.
+    fn deal_unseen<F>(view: &PlayerView, rng: &mut Xorshift, mut deal: F) -> Uno
+    where
+        F: FnMut(usize, usize, &mut Deck, &mut Xorshift, &mut Player),
+    {
+        let mut unseen = Deck::standard();
+        view.hand.iter().chain(view.discard.iter()).for_each(|&card| unseen -= card);
+        unseen.shuffle_with(rng);
+
+        //a reshuffle with an empty discard pile brings in a fresh deck, so there may be more hidden cards than expected
+        let hidden = view.hand_sizes.iter().sum::<usize>() - view.hand.len() + view.draw_pile;
+        while unseen.len() < hidden {
+            let mut extra = Deck::standard();
+            extra.shuffle_with(rng);
+            unseen.reclaim(&mut extra);
+        }
+
+        let players = view.hand_sizes.iter().enumerate().map(|(seat, &size)| {
+            let mut player = Player::new(seat);
+            match seat == view.seat {
+                true => view.hand.iter().rev().for_each(|&card| player.add_card(card)),
+                false => deal(seat, size, &mut unseen, rng, &mut player),
+            }
+            player
+        }).collect::<Vec<_>>();
+        unseen.truncate(view.draw_pile);
+
+        //whether a Draw Four was a bluff depends on the hand it was played from
+        let bluffed = match (view.phase, view.discard.get(1)) {
+            (Phase::Challenge { offender }, Some(under)) => players[offender].get_hand().iter()
+                .any(|c| !c.face.is_wild() && c.color == under.color),
+            _ => false,
+        };
+
+        let drawn = match view.phase {
+            //a drawn card always goes to the front of the hand
+            Phase::Drawn => view.drawn.or_else(|| players[view.current_seat].get_hand().first().copied()),
+            _ => None,
+        };
+
+        Uno {
+            draw_deck: unseen,
+            discard: view.discard.clone().into(),
+            direction: view.direction,
+            current_turn: view.turn,
+            players,
+            current_player: view.current_seat,
+            rules: view.rules.clone(),
+            rng: Xorshift::new(rng.next_u64()),
+            phase: view.phase,
+            pending_draw: view.pending_draw,
+            uno_called: false,
+            drawn,
+            bluffed,
+            events: Vec::new(),
+        }
+    }
+
+    /// Declares UNO for the current player. Do this before playing your
+    /// second to last card, or draw `Rules::uno_penalty` cards.
+    pub fn call_uno(&mut self) {
+        self.uno_called = true;
+    }
+
     pub fn play_card(&mut self, card: Card) -> TurnResult {
-        let top_discard = self.discard.peek_top_card().map(|&x| x).unwrap_or(Card::new(Color::Red, Face::ColorCard));
-        let player = &mut self.players[self.current_player];
+        self.apply(Action::Play(card))
+    }
 
-        if !player.get_hand().has_card(card) {
+    /// Everything the current player may do right now. Wild cards are listed
+    /// once each, with whatever color they carry in the hand.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let hand = self.players[self.current_player].get_hand();
+        legal_actions(hand, self.phase, self.drawn, self.players.len(), self.current_player, |card| self.can_play(card))
+    }
+
+    /// Whether `card` may be played on the discard pile right now, ignoring
+    /// whether the current player holds it.
+    pub fn can_play(&self, card: Card) -> bool {
+        can_play(card, self.top_card(), self.phase, self.pending_draw, &self.rules)
+    }
+
+    pub fn apply(&mut self, action: Action) -> TurnResult {
+        match (self.phase, action) {
+            (Phase::GameOver { .. }, _) => TurnResult::IllegalAction(action),
+            (Phase::Drawn, Action::Play(card)) if Some(card) != self.drawn => TurnResult::IllegalAction(action),
+            (Phase::Play, Action::Play(card)) | (Phase::Drawn, Action::Play(card)) => self.play(card),
+            (Phase::Challenge { .. }, Action::Play(card)) if self.rules.stacking => self.play(card),
+            (Phase::Play, Action::Draw) => {
+                if self.pending_draw > 0 {
+                    return TurnResult::Drew(self.take_penalty());
+                }
+
+                let card = self.draw_card();
+                self.players[self.current_player].add_card(card);
+                self.events.push(Event::Drew { seat: self.current_player, count: 1, forced: false });
+                if self.can_play(card) {
+                    self.phase = Phase::Drawn;
+                    self.drawn = Some(card);
+                } else {
+                    self.end_turn();
+                }
+                TurnResult::Drew(vec![card])
+            },
+            (Phase::Drawn, Action::Pass) => {
+                self.events.push(Event::Passed { seat: self.current_player });
+                self.end_turn();
+                TurnResult::Passed
+            },
+            (Phase::Challenge { .. }, Action::Accept) => TurnResult::Drew(self.take_penalty()),
+            (Phase::Challenge { offender }, Action::Challenge) => {
+                let bluffed = self.bluffed;
+                self.events.push(Event::Challenged { seat: self.current_player, offender, bluffed });
+                if bluffed {
+                    //the bluffer takes the four, and the challenger plays on
+                    self.pending_draw -= 4;
+                    self.deal(offender, 4);
+                    if self.pending_draw > 0 {
+                        self.take_penalty();
+                    } else {
+                        self.phase = Phase::Play;
+                    }
+                } else {
+                    self.pending_draw += 2;
+                    self.take_penalty();
+                }
+                TurnResult::Challenged { bluffed }
+            },
+            (Phase::Swap, Action::Swap(seat)) if seat != self.current_player && seat < self.players.len() => {
+                let (a, b) = (self.current_player.min(seat), self.current_player.max(seat));
+                let (left, right) = self.players.split_at_mut(b);
+                core::mem::swap(left[a].get_hand_mut(), right[0].get_hand_mut());
+                self.events.push(Event::Swapped { seat: self.current_player, with: seat });
+                self.end_turn();
+                TurnResult::Swapped(seat)
+            },
+            _ => TurnResult::IllegalAction(action),
+        }
+    }
+
+    fn play(&mut self, card: Card) -> TurnResult {
+        let top_discard = self.top_card();
+        let seat = self.current_player;
+
+        if !self.players[seat].get_hand().has_card(card) {
             return TurnResult::NotHoldingCard(card);
         }
 
-        if !card.can_play_on(top_discard) {
+        if !self.can_play(card) {
             return TurnResult::InvalidMove(top_discard, card);
         }
 
-        *player.get_hand_mut() -= card;
+        let hand = self.players[seat].get_hand_mut();
+        *hand -= card;
+        //a Draw Four is only allowed when the player has nothing of the current color
+        let bluffed = card.face == Face::DrawFour && hand.iter().any(|c| c.face != Face::ColorCard && c.face != Face::DrawFour && c.color == top_discard.color);
+        let left = hand.len();
         self.discard += card;
+        self.events.push(Event::Played { seat, card });
 
-        if player.get_hand().is_empty() {
+        if left == 0 {
+            self.phase = Phase::GameOver { winner: seat };
+            self.events.push(Event::Won { seat });
             return TurnResult::GameOver;
         }
 
+        if left == 1 && self.uno_called {
+            self.events.push(Event::UnoCalled { seat });
+        } else if left == 1 && self.rules.uno_penalty > 0 {
+            self.events.push(Event::UnoPenalty { seat });
+            self.deal(seat, self.rules.uno_penalty);
+        }
+
         match card.face {
             Face::DrawFour | Face::DrawTwo => {
-                let amount = match card.face {
+                self.pending_draw += match card.face {
                     Face::DrawFour => 4,
-                    Face::DrawTwo => 2,
-                    _ => unreachable!(),
+                    _ => 2,
                 };
 
-                self.do_turn_increase();
-
-                //work around borrowing &mut self multiple times
-                let drawcards: Vec<_> = (0..amount).into_iter().map(|_| self.draw_card()).collect();
-                let player = &mut self.players[self.current_player];
-                drawcards.into_iter().for_each(|card| player.add_card(card));
+                self.end_turn();
+                if card.face == Face::DrawFour && self.rules.challenge_draw_four {
+                    self.phase = Phase::Challenge { offender: seat };
+                    self.bluffed = bluffed;
+                } else if !self.rules.stacking {
+                    self.take_penalty();
+                }
+                return TurnResult::Success(card);
             },
             Face::Reverse => {
                 self.direction = !self.direction;
                 if self.players.len() < 3 {
-                    self.do_turn_increase();
+                    let skipped = self.do_turn_increase();
+                    self.events.push(Event::Skipped { seat: skipped });
                 }
             },
-            Face::Skip => { self.do_turn_increase(); }, //skip next player
+            Face::Skip => { //skip next player
+                let skipped = self.do_turn_increase();
+                self.events.push(Event::Skipped { seat: skipped });
+            },
+            Face::Seven if self.rules.seven_o => {
+                self.phase = Phase::Swap;
+                return TurnResult::Success(card);
+            },
+            Face::Zero if self.rules.seven_o => self.rotate_hands(),
             _ => {},
         }
 
-        self.do_turn_increase();
+        self.end_turn();
         TurnResult::Success(card)
     }
 
+    /// Passes every hand to the next player in the direction of play.
+    fn rotate_hands(&mut self) {
+        let mut hands: Vec<Deck> = self.players.iter_mut().map(|p| core::mem::replace(p.get_hand_mut(), Vec::new().into())).collect();
+        match self.direction {
+            GameDirection::Clockwise => hands.rotate_right(1),
+            GameDirection::CounterClockwise => hands.rotate_left(1),
+        }
+        self.players.iter_mut().zip(hands).for_each(|(p, hand)| *p.get_hand_mut() = hand);
+        self.events.push(Event::Rotated);
+    }
+
+    /// The current player draws the pending penalty and loses their turn.
+    fn take_penalty(&mut self) -> Vec<Card> {
+        let amount = core::mem::replace(&mut self.pending_draw, 0);
+        let cards = self.deal(self.current_player, amount);
+        self.end_turn();
+        cards
+    }
+
+    /// Forces `amount` cards on `seat`.
+    fn deal(&mut self, seat: usize, amount: usize) -> Vec<Card> {
+        //work around borrowing &mut self multiple times
+        let drawcards: Vec<_> = (0..amount).map(|_| self.draw_card()).collect();
+        let player = &mut self.players[seat];
+        drawcards.iter().for_each(|&card| player.add_card(card));
+        if amount > 0 {
+            self.events.push(Event::Drew { seat, count: amount, forced: true });
+        }
+        drawcards
+    }
+
+    fn end_turn(&mut self) {
+        self.phase = Phase::Play;
+        self.uno_called = false;
+        self.do_turn_increase();
+    }
+
     fn do_turn_increase(&mut self) -> usize {
         self.current_turn += 1;
         self.current_player = match self.current_player as isize + self.direction {
@@ -133,8 +513,10 @@ impl Uno {
                 if !(*self.discard).is_empty() {
                     self.draw_deck.reclaim(&mut self.discard);
                 } else {
-                    self.draw_deck.reclaim(&mut Deck::default());
+                    self.draw_deck.reclaim(&mut Deck::standard());
                 }
+                self.draw_deck.shuffle_with(&mut self.rng);
+                self.events.push(Event::Reshuffled);
 
                 self.discard += top;
                 self.draw_card()
@@ -143,10 +525,263 @@ impl Uno {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum TurnResult {
     Success(Card),
     InvalidMove(Card, Card),
     NotHoldingCard(Card),
+    /// The player drew these cards, either by choice or as a penalty.
+    Drew(Vec<Card>),
+    Passed,
+    Challenged { bluffed: bool },
+    Swapped(usize),
+    /// The action is not allowed in the current `Phase`.
+    IllegalAction(Action),
     GameOver,
 }
+
+/// What `Uno::legal_actions` lists, from what the player to move can see,
+/// so that a `PlayerView` gives the same answer.
+pub(crate) fn legal_actions<F: Fn(Card) -> bool>(hand: &[Card], phase: Phase, drawn: Option<Card>, seats: usize, seat: usize, can_play: F) -> Vec<Action> {
+    let mut actions = Vec::new();
+    let mut play = |card: Card| {
+        if !actions.contains(&Action::Play(card)) {
+            actions.push(Action::Play(card));
+        }
+    };
+
+    match phase {
+        Phase::Play => {
+            hand.iter().filter(|&&card| can_play(card)).for_each(|&card| play(card));
+            actions.push(Action::Draw);
+        },
+        Phase::Drawn => {
+            drawn.into_iter().for_each(play);
+            actions.push(Action::Pass);
+        },
+        Phase::Challenge { .. } => {
+            hand.iter().filter(|&&card| can_play(card)).for_each(|&card| play(card));
+            actions.push(Action::Challenge);
+            actions.push(Action::Accept);
+        },
+        Phase::Swap => {
+            (0..seats)
+                .filter(|&other| other != seat)
+                .for_each(|other| actions.push(Action::Swap(other)));
+        },
+        Phase::GameOver { .. } => {},
+    }
+
+    actions
+}
+
+/// See `Uno::can_play`.
+pub(crate) fn can_play(card: Card, top: Card, phase: Phase, pending_draw: usize, rules: &Rules) -> bool {
+    match phase {
+        Phase::Play | Phase::Drawn if pending_draw == 0 => card.can_play_on(top),
+        //only a stack can answer a pending penalty
+        Phase::Play | Phase::Challenge { .. } => rules.stacking && match card.face {
+            Face::DrawFour => true,
+            Face::DrawTwo => top.face == Face::DrawTwo,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(rules: Rules) -> Uno {
+        Uno::create_game_with(vec!["Ali".into(), "Bob".into(), "Cam".into()], rules, 42)
+    }
+
+    /// Replaces the current player's hand and the top of the discard pile.
+    fn rig(uno: &mut Uno, hand: &[&str], top: &str) {
+        *uno.current_player().get_hand_mut() = hand.iter().map(|c| c.parse().unwrap()).collect::<Vec<_>>().into();
+        uno.discard += top.parse::<Card>().unwrap();
+    }
+
+    #[test]
+    fn seeded_games_repeat() {
+        let a = game(Rules::official());
+        let b = game(Rules::official());
+        assert_eq!(*a.deck().to_vec(), *b.deck().to_vec());
+        assert_eq!(7, a.players()[0].get_hand().len());
+    }
+
+    #[test]
+    fn draw_then_pass() {
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["R1", "R2", "R3"], "B5");
+        let drawn: Card = "B7".parse().unwrap();
+        uno.draw_deck.push(drawn);
+        assert_eq!(TurnResult::Drew(vec![drawn]), uno.apply(Action::Draw));
+
+        assert_eq!(Phase::Drawn, uno.phase());
+        assert_eq!(Some(drawn), uno.drawn_card());
+        assert_eq!(vec![Action::Play(drawn), Action::Pass], uno.legal_actions());
+        assert_eq!(TurnResult::Passed, uno.apply(Action::Pass));
+        assert_eq!(1, uno.current_seat());
+
+        //a card that can't be played ends the turn straight away
+        rig(&mut uno, &["R1", "R2", "R3"], "B5");
+        uno.draw_deck.push("G8".parse().unwrap());
+        uno.apply(Action::Draw);
+        assert_eq!(Phase::Play, uno.phase());
+        assert_eq!(2, uno.current_seat());
+    }
+
+    #[test]
+    fn views_give_the_same_legal_actions() {
+        let mut rng = Xorshift::new(3);
+        for rules in [Rules::official(), Rules::stacking(), Rules::seven_o()].iter() {
+            let mut uno = game(rules.clone());
+            while uno.winner().is_none() && uno.current_turn() < 500 {
+                let legal = uno.legal_actions();
+                assert_eq!(legal, uno.view(uno.current_seat()).legal_actions());
+                uno.apply(legal[rng.below(legal.len())]);
+            }
+        }
+    }
+
+    #[test]
+    fn the_biggest_deals_still_start_on_a_color() {
+        let rules = Rules { hand_size: 12, ..Rules::official() };
+        for seed in 0..200 {
+            let players = (0..8).map(|i| Player::new(format!("P{}", i))).collect();
+            let uno = Uno::create_game_with(players, rules.clone(), seed);
+            assert!(!uno.top_card().face.is_wild(), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn draw_two_without_stacking() {
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["RT", "R2", "R3"], "R5");
+        let before = uno.players()[1].get_hand().len();
+        assert_eq!(TurnResult::Success("RT".parse().unwrap()), uno.play_card("RT".parse().unwrap()));
+        assert_eq!(before + 2, uno.players()[1].get_hand().len());
+        assert_eq!(2, uno.current_seat());
+    }
+
+    #[test]
+    fn stacking_passes_penalty_on() {
+        let mut uno = game(Rules::stacking());
+        rig(&mut uno, &["RT", "R2", "R3"], "R5");
+        uno.play_card("RT".parse().unwrap());
+        assert_eq!(1, uno.current_seat());
+        assert_eq!(2, uno.pending_draw());
+
+        rig(&mut uno, &["GT", "G2", "G3"], "RT");
+        assert!(uno.legal_actions().contains(&Action::Play("GT".parse().unwrap())));
+        assert!(!uno.legal_actions().contains(&Action::Play("G2".parse().unwrap())));
+        uno.play_card("GT".parse().unwrap());
+
+        let before = uno.players()[2].get_hand().len();
+        assert_eq!(4, uno.pending_draw());
+        uno.apply(Action::Draw);
+        assert_eq!(before + 4, uno.players()[2].get_hand().len());
+        assert_eq!(0, uno.current_seat());
+    }
+
+    #[test]
+    fn challenge_a_bluff() {
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["GD", "R2", "R3"], "R5");
+        uno.play_card("wild draw four green".parse().unwrap());
+        assert_eq!(Phase::Challenge { offender: 0 }, uno.phase());
+
+        let before = uno.players()[0].get_hand().len();
+        assert_eq!(TurnResult::Challenged { bluffed: true }, uno.apply(Action::Challenge));
+        assert_eq!(before + 4, uno.players()[0].get_hand().len());
+        assert_eq!(1, uno.current_seat());
+        assert_eq!(Phase::Play, uno.phase());
+    }
+
+    #[test]
+    fn uno_penalty() {
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["R1", "R2"], "R5");
+        uno.play_card("R1".parse().unwrap());
+        assert_eq!(3, uno.players()[0].get_hand().len());
+
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["R1", "R2"], "R5");
+        uno.call_uno();
+        uno.play_card("R1".parse().unwrap());
+        assert_eq!(1, uno.players()[0].get_hand().len());
+    }
+
+    #[test]
+    fn events_are_logged() {
+        let mut uno = game(Rules::official());
+        rig(&mut uno, &["RS", "R2", "R3"], "R5");
+        uno.play_card("RS".parse().unwrap());
+        assert_eq!(
+            &[Event::Played { seat: 0, card: "RS".parse().unwrap() }, Event::Skipped { seat: 1 }],
+            uno.events()
+        );
+        assert_eq!(2, uno.current_seat());
+    }
+
+    #[test]
+    fn seven_o() {
+        let mut uno = game(Rules::seven_o());
+        rig(&mut uno, &["R7", "R2", "R3"], "R5");
+        let theirs = uno.players()[2].get_hand().to_vec();
+        uno.play_card("R7".parse().unwrap());
+        assert_eq!(Phase::Swap, uno.phase());
+        assert_eq!(TurnResult::Swapped(2), uno.apply(Action::Swap(2)));
+        assert_eq!(theirs, uno.players()[0].get_hand().to_vec());
+        assert_eq!(2, uno.players()[2].get_hand().len());
+
+        let mut uno = game(Rules::seven_o());
+        rig(&mut uno, &["R0", "R2", "R3"], "R5");
+        let names: Vec<_> = uno.players().iter().map(|p| p.name().to_string()).collect();
+        let first = uno.players()[1].get_hand().to_vec();
+        uno.play_card("R0".parse().unwrap());
+        assert_eq!(first, uno.players()[2].get_hand().to_vec());
+        assert_eq!(2, uno.players()[1].get_hand().len());
+        assert_eq!(names, uno.players().iter().map(|p| p.name().to_string()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn determinize_keeps_what_is_seen() {
+        let mut uno = game(Rules::official());
+        uno.apply(Action::Draw);
+        let view = uno.view(1);
+        let mut rng = Xorshift::new(7);
+        let sampled = Uno::determinize(&view, &mut rng);
+
+        assert_eq!(uno.players()[1].get_hand().to_vec(), sampled.players()[1].get_hand().to_vec());
+        assert_eq!(uno.discard().to_vec(), sampled.discard().to_vec());
+        assert_eq!(uno.deck().len(), sampled.deck().len());
+        assert_eq!(view.hand_sizes, sampled.players().iter().map(|p| p.get_hand().len()).collect::<Vec<_>>());
+        assert_eq!(uno.legal_actions().len() > 1, sampled.legal_actions().len() > 1);
+        assert_ne!(uno.players()[0].get_hand().to_vec(), sampled.players()[0].get_hand().to_vec());
+    }
+
+    #[test]
+    fn determinize_with_follows_beliefs() {
+        let uno = game(Rules::official());
+        let view = uno.view(1);
+        let mut beliefs = crate::belief::Beliefs::new();
+        let events = [
+            Event::Played { seat: 1, card: "B5".parse().unwrap() },
+            Event::Played { seat: 0, card: "RW".parse().unwrap() },
+            Event::Challenged { seat: 1, offender: 0, bluffed: false },
+        ];
+        events.iter().for_each(|event| beliefs.observe(event));
+        let estimate = beliefs.estimate(&view);
+
+        let mut rng = Xorshift::new(7);
+        for _ in 0..20 {
+            let sampled = Uno::determinize_with(&view, &estimate, &mut rng);
+            assert_eq!(view.hand_sizes, sampled.players().iter().map(|p| p.get_hand().len()).collect::<Vec<_>>());
+            assert_eq!(uno.deck().len(), sampled.deck().len());
+            assert!(!sampled.players()[0].get_hand().iter().any(|card| !card.face.is_wild() && card.color == Color::Blue));
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Games saved to text and loaded back.
+//!
+//! A save is one `KEY VALUE` pair per line, starting with `uno-save` and the
+//! format version:
+//!
+//! ```text
+//! uno-save 1
+//! rules hand-size=7,stacking=off,seven-o=off,challenge=on,uno-penalty=2
+//! rng 88172645463325252
+//! turn 3
+//! current 1
+//! direction cw
+//! phase play
+//! pending 0
+//! uno-called no
+//! drawn -
+//! bluffed no
+//! deck G5,RC,...
+//! discard B5,B7
+//! player Y0,RD,... Ann
+//! player G7,B2,... Bo
+//! event PLAYED 0 B5
+//! ```
+//!
+//! Card lists are comma separated, or `-` when empty, with the top of the
+//! draw pile last and the top of the discard pile first. Players are listed
+//! in seat order with the name after the hand, and events oldest first, in
+//! the same words as the network protocol.
+//!
+//! Compatibility: a build loads saves of its own `SAVE_VERSION` and every
+//! version before it. A new version may only add keys, which get a default
+//! when an older save doesn't have them, and must bump the number. Saves from
+//! a newer version are refused outright rather than loaded with parts missing.
+
+use super::{Phase, Uno};
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::direction::GameDirection;
+use crate::event::Event;
+use crate::player::Player;
+use crate::protocol::{cards_text, event_text, parse_cards, parse_event, parse_phase, phase_text};
+use crate::rules::Rules;
+
+use shuffle::Xorshift;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The version of the save format written by this build.
+pub const SAVE_VERSION: u32 = 1;
+
+impl Uno {
+    /// The whole game as text, to be given back to `Uno::load`.
+    pub fn save(&self) -> String {
+        let flag = |on: bool| if on { "yes" } else { "no" };
+        let cards = |cards: &[Card]| if cards.is_empty() { "-".to_string() } else { cards_text(cards) };
+        let direction = match self.direction {
+            GameDirection::Clockwise => "cw",
+            GameDirection::CounterClockwise => "ccw",
+        };
+
+        let mut lines = vec![
+            format!("uno-save {}", SAVE_VERSION),
+            format!("rules {}", self.rules),
+            format!("rng {}", self.rng.state()),
+            format!("turn {}", self.current_turn),
+            format!("current {}", self.current_player),
+            format!("direction {}", direction),
+            format!("phase {}", phase_text(self.phase)),
+            format!("pending {}", self.pending_draw),
+            format!("uno-called {}", flag(self.uno_called)),
+            format!("drawn {}", self.drawn.map(|card| card.to_string()).unwrap_or_else(|| "-".into())),
+            format!("bluffed {}", flag(self.bluffed)),
+            format!("deck {}", cards(&self.draw_deck)),
+            format!("discard {}", cards(&self.discard)),
+        ];
+        lines.extend(self.players.iter().map(|player| format!("player {} {}", cards(player.get_hand()), player.name())));
+        lines.extend(self.events.iter().map(|event| format!("event {}", event_text(event))));
+        lines.join("\n") + "\n"
+    }
+
+    /// Reads a game written by `Uno::save`, from this or an earlier version.
+    pub fn load(text: &str) -> Result<Uno, SaveError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())).filter(|(_, line)| !line.is_empty());
+        let version = match lines.next().and_then(|(_, line)| line.strip_prefix("uno-save ")) {
+            Some(version) => version.trim().parse::<u32>().map_err(|_| SaveError::NotASave)?,
+            None => return Err(SaveError::NotASave),
+        };
+        if version == 0 {
+            return Err(SaveError::NotASave);
+        }
+        if version > SAVE_VERSION {
+            return Err(SaveError::NewerVersion { found: version, supported: SAVE_VERSION });
+        }
+
+        let mut values = HashMap::new();
+        let mut players = Vec::new();
+        let mut events = Vec::new();
+        for (number, line) in lines {
+            let bad = || SaveError::BadLine { line: number, text: line.to_string() };
+            let (key, value) = line.split_once(' ').ok_or_else(bad)?;
+            match key {
+                "player" => {
+                    let (hand, name) = value.split_once(' ').ok_or_else(bad)?;
+                    let mut player = Player::new(name);
+                    *player.get_hand_mut() = card_list(hand).ok_or_else(bad)?.into();
+                    players.push(player);
+                },
+                "event" => events.push(parse_event(&value.split_whitespace().collect::<Vec<_>>()).ok_or_else(bad)?),
+                "rules" | "rng" | "turn" | "current" | "direction" | "phase" | "pending" | "uno-called" | "drawn" | "bluffed" | "deck"
+                | "discard" => {
+                    if values.insert(key, (number, value)).is_some() {
+                        return Err(bad());
+                    }
+                },
+                _ => return Err(bad()),
+            }
+        }
+
+        let flag = |value: &str| match value {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        };
+
+        let uno = Uno {
+            draw_deck: Deck::new(field(&values, "deck", card_list)?),
+            discard: Deck::new(field(&values, "discard", card_list)?),
+            direction: field(&values, "direction", |value| match value {
+                "cw" => Some(GameDirection::Clockwise),
+                "ccw" => Some(GameDirection::CounterClockwise),
+                _ => None,
+            })?,
+            current_turn: field(&values, "turn", |value| value.parse().ok())?,
+            current_player: field(&values, "current", |value| value.parse().ok())?,
+            rules: field(&values, "rules", parse_rules)?,
+            rng: Xorshift::from_state(field(&values, "rng", |value| value.parse().ok())?),
+            phase: field(&values, "phase", parse_phase)?,
+            pending_draw: field(&values, "pending", |value| value.parse().ok())?,
+            uno_called: field(&values, "uno-called", flag)?,
+            drawn: field(&values, "drawn", |value| match value {
+                "-" => Some(None),
+                card => card.parse().ok().map(Some),
+            })?,
+            bluffed: field(&values, "bluffed", flag)?,
+            players,
+            events,
+        };
+        uno.check()?;
+        Ok(uno)
+    }
+
+    /// Writes `Uno::save` to a file.
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        fs::write(path, self.save()).map_err(SaveError::Io)
+    }
+
+    /// Reads a game from a file written by `Uno::save_to`.
+    pub fn load_from<P: AsRef<Path>>(path: P) -> Result<Uno, SaveError> {
+        Uno::load(&fs::read_to_string(path).map_err(SaveError::Io)?)
+    }
+
+    /// Whether a loaded game could have come out of actual play.
+    fn check(&self) -> Result<(), SaveError> {
+        let seats = self.players.len();
+        if let Err(e) = self.rules.check(seats) {
+            return Err(SaveError::Inconsistent(e.to_string()));
+        }
+        let seat_ok = |seat: usize| seat < seats;
+        let phase_ok = match self.phase {
+            Phase::Challenge { offender: seat } | Phase::GameOver { winner: seat } => seat_ok(seat),
+            _ => true,
+        };
+        if !seat_ok(self.current_player) || !phase_ok {
+            return Err(SaveError::Inconsistent(format!("there are only {} seats", seats)));
+        }
+        let events_ok = self.events.iter().all(|event| {
+            let other = match *event {
+                Event::Challenged { offender, .. } => Some(offender),
+                Event::Swapped { with, .. } => Some(with),
+                _ => None,
+            };
+            event.seat().into_iter().chain(other).all(seat_ok)
+        });
+        if !events_ok {
+            return Err(SaveError::Inconsistent(format!("an event names a seat but there are only {}", seats)));
+        }
+        if self.discard.is_empty() {
+            return Err(SaveError::Inconsistent("the discard pile is empty".into()));
+        }
+
+        let mut cards: Vec<Card> = self.draw_deck.iter().chain(self.discard.iter()).copied().collect();
+        cards.extend(self.players.iter().flat_map(|player| player.get_hand().iter().copied()));
+        //running out of cards with nothing to reshuffle brings in a whole new deck
+        let deck = Deck::standard().to_vec();
+        let decks = (cards.len() / deck.len()).max(1);
+        let mut standard: Vec<Card> = deck.iter().copied().cycle().take(decks * deck.len()).collect();
+        for card in cards {
+            match standard.iter().position(|&other| other == card) {
+                Some(at) => {
+                    standard.swap_remove(at);
+                },
+                None => return Err(SaveError::Inconsistent(format!("there are too many {}", card))),
+            }
+        }
+        if !standard.is_empty() {
+            return Err(SaveError::Inconsistent(format!("{} cards are missing", standard.len())));
+        }
+        Ok(())
+    }
+}
+
+/// Parses the value of the `key` line, given with the number of the line it was on.
+fn field<T, F: Fn(&str) -> Option<T>>(values: &HashMap<&str, (usize, &str)>, key: &'static str, parse: F) -> Result<T, SaveError> {
+    let (line, value) = *values.get(key).ok_or(SaveError::Missing(key))?;
+    parse(value).ok_or_else(|| SaveError::BadLine { line, text: format!("{} {}", key, value) })
+}
+
+fn card_list(text: &str) -> Option<Vec<Card>> {
+    match text {
+        "-" => Some(Vec::new()),
+        _ => parse_cards(text),
+    }
+}
+
+fn parse_rules(text: &str) -> Option<Rules> {
+    let mut rules = Rules::official();
+    for option in text.split(',') {
+        let (key, setting) = option.split_once('=')?;
+        rules.set(key, setting).ok()?;
+    }
+    Some(rules)
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The text doesn't start with a `uno-save` line.
+    NotASave,
+    /// The save was written by a newer version of the format.
+    NewerVersion { found: u32, supported: u32 },
+    BadLine { line: usize, text: String },
+    Missing(&'static str),
+    /// Everything could be read, but it isn't a game that could have been played.
+    Inconsistent(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::NotASave => write!(f, "not a saved game"),
+            SaveError::NewerVersion { found, supported } => {
+                write!(f, "the game was saved in format version {}, but only versions up to {} can be loaded", found, supported)
+            },
+            SaveError::BadLine { line, text } => write!(f, "line {} is not understood: \"{}\"", line, text),
+            SaveError::Missing(key) => write!(f, "the save has no \"{}\" line", key),
+            SaveError::Inconsistent(why) => write!(f, "the saved game is inconsistent: {}", why),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{Driver, Seat};
+    use crate::strategy;
+
+    /// A game saved by the first version of the format. It has to keep
+    /// loading as long as version 1 is supported.
+    const VERSION_1: &str = "\
+uno-save 1
+rules hand-size=5,stacking=off,seven-o=off,challenge=on,uno-penalty=2
+rng 5428900098020717515
+turn 3
+current 0
+direction cw
+phase play
+pending 0
+uno-called no
+drawn -
+bluffed no
+deck B5,G1,R4,RC,G5,RT,RD,G4,RC,R7,R8,RR,B7,Y1,BT,G3,R8,R9,YS,G3,Y3,B8,R7,Y5,Y6,R5,R6,GS,G4,Y1,B4,YT,G8,GT,BS,G8,B9,G6,Y3,R2,Y6,B1,R1,B5,GT,Y4,RD,B2,G6,R2,R3,B3,RD,RR,Y8,B4,B8,RC,B1,G7,G1,R6,BS,BR,Y0,RS,B6,G0,G5,B9,B6,B7,Y2,YR,R3,B3,YT,RD,Y7,R1,R0,G7,G9,R9,G2,Y2,GR,RS,RT,Y9,Y7,YS,R5,RC,Y8,BT,GS
+discard Y9,G9,G2
+player Y4,B0,B2,YR Ann
+player BR,Y5,GR,R4 Bo Peep
+event PLAYED 0 G9
+event PLAYED 1 Y9
+";
+
+    fn bots(uno: Uno) -> Driver {
+        let seats = (0..uno.players().len()).map(|seed| Seat::Computer(strategy::by_name("basic", seed as u64).unwrap())).collect();
+        Driver::new(uno, seats)
+    }
+
+    #[test]
+    fn saved_games_play_on_the_same() {
+        let mut rules = Rules::official();
+        rules.seven_o = true;
+        let uno = Uno::create_game_with(vec!["Ann".into(), "Bo".into(), "Cy".into()], rules, 21);
+        let mut driver = bots(uno);
+        for _ in 0..15 {
+            driver.step();
+        }
+        let saved = driver.uno().save();
+        let loaded = Uno::load(&saved).unwrap();
+        assert_eq!(saved, loaded.save());
+
+        //the draw pile and the random numbers carry on where they left off
+        let mut copy = bots(loaded);
+        while let Some(result) = driver.step() {
+            assert_eq!(Some(result), copy.step());
+        }
+        assert_eq!(driver.uno().save(), copy.uno().save());
+        assert!(driver.uno().winner().is_some());
+    }
+
+    #[test]
+    fn older_versions_load_and_newer_ones_are_refused() {
+        let uno = Uno::load(VERSION_1).unwrap();
+        assert_eq!(5, uno.rules().hand_size);
+        assert_eq!(0, uno.current_seat());
+        assert_eq!(3, uno.current_turn());
+        assert_eq!("Bo Peep", uno.players()[1].name());
+        assert_eq!("Y9".parse::<Card>().unwrap(), uno.top_card());
+        assert_eq!(2, uno.events().len());
+
+        let newer = VERSION_1.replacen("uno-save 1", &format!("uno-save {}", SAVE_VERSION + 1), 1);
+        match Uno::load(&newer) {
+            Err(SaveError::NewerVersion { found, supported }) => assert_eq!((SAVE_VERSION + 1, SAVE_VERSION), (found, supported)),
+            other => panic!("{:?}", other.map(|uno| uno.save())),
+        }
+    }
+
+    #[test]
+    fn broken_saves_are_refused() {
+        let broken = |from: &str, to: &str| Uno::load(&VERSION_1.replacen(from, to, 1)).err().map(|e| e.to_string());
+        assert!(matches!(Uno::load("hello"), Err(SaveError::NotASave)));
+        assert!(matches!(Uno::load(&VERSION_1.replacen("uno-save 1", "uno-save 0", 1)), Err(SaveError::NotASave)));
+        assert!(matches!(Uno::load(&VERSION_1.replacen("pending 0\n", "", 1)), Err(SaveError::Missing("pending"))));
+        assert!(matches!(Uno::load(&VERSION_1.replacen("turn 3", "turn three", 1)), Err(SaveError::BadLine { line: 4, .. })));
+        assert!(broken("bluffed no", "bluffed no\nscore 10").is_some());
+        assert!(broken("current 0", "current 2").is_some());
+        //a card that isn't in the deck
+        assert!(broken("Y4,B0", "Y4,Y4").is_some());
+        assert!(broken("player BR,Y5,GR,R4 Bo Peep\n", "").is_some());
+        //seats that would be looked up when the log is shown
+        assert!(broken("event PLAYED 1 Y9", "event PLAYED 9 Y9").is_some());
+        assert!(broken("event PLAYED 1 Y9", "event SWAPPED 1 9").is_some());
+    }
+
+    #[test]
+    fn games_with_a_second_deck_load() {
+        let mut uno = Uno::create_game_with(vec!["Ann".into(), "Bo".into()], Rules::official(), 4);
+        //everyone holds the rest of the deck, so the next draw needs a fresh one
+        let rest: Vec<Card> = uno.draw_deck.drain(..).collect();
+        rest.into_iter().for_each(|card| uno.players[0].add_card(card));
+        let card = uno.draw_card();
+        uno.players[1].add_card(card);
+        assert_eq!(Some(&Event::Reshuffled), uno.events().last());
+
+        let saved = uno.save();
+        assert_eq!(saved, Uno::load(&saved).unwrap().save());
+        let extra = saved.replacen("\ndiscard ", "\ndiscard Y4,", 1);
+        assert!(Uno::load(&extra).err().is_some_and(|e| e.to_string().contains("too many")));
+    }
+}
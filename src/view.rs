@@ -0,0 +1,73 @@
+use crate::card::Card;
+use crate::direction::GameDirection;
+use crate::rules::Rules;
+use crate::uno::{self, Action, Phase, Uno};
+
+use alloc::vec::Vec;
+
+/// What one player can see of a game: their own hand plus public information.
+#[derive(Clone, Debug)]
+pub struct PlayerView {
+    pub seat: usize,
+    pub hand: Vec<Card>,
+    /// The top of the discard pile. A wild carries its declared color.
+    pub top: Card,
+    /// The whole discard pile, top first. Everything in it has been seen.
+    pub discard: Vec<Card>,
+    pub direction: GameDirection,
+    pub current_seat: usize,
+    pub phase: Phase,
+    /// The card this player just drew, while they decide whether to play it.
+    pub drawn: Option<Card>,
+    pub pending_draw: usize,
+    /// How many cards each seat holds, in seat order.
+    pub hand_sizes: Vec<usize>,
+    pub draw_pile: usize,
+    pub turn: usize,
+    pub rules: Rules,
+}
+
+impl PlayerView {
+    /// Seats in the order they will play after this one.
+    pub fn opponents(&self) -> Vec<usize> {
+        let count = self.hand_sizes.len();
+        (1..count)
+            .map(|step| match self.direction {
+                GameDirection::Clockwise => (self.seat + step) % count,
+                GameDirection::CounterClockwise => (self.seat + count - step) % count,
+            })
+            .collect()
+    }
+
+    /// The seat that plays after this one.
+    pub fn next_seat(&self) -> usize {
+        self.opponents()[0]
+    }
+
+    /// What `Uno::legal_actions` gives while this seat is the one to move.
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let can_play = |card| uno::can_play(card, self.top, self.phase, self.pending_draw, &self.rules);
+        uno::legal_actions(&self.hand, self.phase, self.drawn, self.hand_sizes.len(), self.seat, can_play)
+    }
+}
+
+impl Uno {
+    /// Everything `seat` is allowed to know about the game.
+    pub fn view(&self, seat: usize) -> PlayerView {
+        PlayerView {
+            seat,
+            hand: self.players()[seat].get_hand().to_vec(),
+            top: self.top_card(),
+            discard: self.discard().to_vec(),
+            direction: self.direction(),
+            current_seat: self.current_seat(),
+            phase: self.phase(),
+            drawn: self.drawn_card().filter(|_| seat == self.current_seat()),
+            pending_draw: self.pending_draw(),
+            hand_sizes: self.players().iter().map(|p| p.get_hand().len()).collect(),
+            draw_pile: self.deck().len(),
+            turn: self.current_turn(),
+            rules: self.rules().clone(),
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use uno::strategy::{Basic, Greedy};
+use uno::{Driver, Engine, EngineError, EngineLine, RefereeLine, Rules, Seat, Uno};
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const ENGINE: &str = env!("CARGO_BIN_EXE_sample_engine");
+
+#[test]
+fn handshake_follows_the_protocol() {
+    let mut child = Command::new(ENGINE).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+    let mut read = || {
+        let mut line = String::new();
+        output.read_line(&mut line).unwrap();
+        line.parse::<EngineLine>().unwrap_or_else(|_| panic!("not an engine line: {:?}", line))
+    };
+
+    writeln!(input, "{}", RefereeLine::Uno).unwrap();
+    let mut named = false;
+    loop {
+        match read() {
+            EngineLine::Id { key, .. } => named |= key == "name",
+            EngineLine::UnoOk => break,
+            line => panic!("{} before unook", line),
+        }
+    }
+    assert!(named);
+
+    writeln!(input, "{}", RefereeLine::IsReady).unwrap();
+    assert_eq!(EngineLine::ReadyOk, read());
+    writeln!(input, "{}", RefereeLine::Quit).unwrap();
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn plays_whole_games_legally() {
+    for seed in 0..5 {
+        for rules in [Rules::official(), Rules::stacking(), Rules::seven_o()].iter() {
+            let mut engine = Engine::start(ENGINE, Duration::from_secs(5)).unwrap();
+            let uno = Uno::create_game_with(vec!["E".into(), "B".into(), "G".into()], rules.clone(), seed);
+            let mut driver = Driver::new(uno, vec![Seat::Human, Seat::Computer(Box::new(Basic)), Seat::Computer(Box::new(Greedy))]);
+
+            let mut sent = 0;
+            while let Some(seat) = driver.run() {
+                for event in &driver.uno().events()[sent..] {
+                    engine.send(&RefereeLine::Event(*event)).unwrap();
+                }
+                sent = driver.uno().events().len();
+
+                let (action, uno) = engine.best_move(&driver.uno().view(seat), &driver.uno().legal_actions()).unwrap();
+                if uno {
+                    driver.call_uno();
+                }
+                driver.apply(action);
+            }
+            assert!(driver.uno().winner().is_some());
+            engine.ready().unwrap();
+            assert_eq!(0, engine.faults());
+        }
+    }
+}
+
+#[test]
+fn slow_answers_time_out() {
+    let mut engine = Engine::start(&format!("{} --think 200", ENGINE), Duration::from_millis(20)).unwrap();
+    assert_eq!("sample-basic", engine.name());
+    let uno = Uno::create_game_with(vec!["A".into(), "B".into()], Rules::official(), 1);
+    let (view, legal) = (uno.view(uno.current_seat()), uno.legal_actions());
+    assert_eq!(Err(EngineError::TimedOut { waiting_for: "bestmove" }), engine.best_move(&view, &legal));
+
+    //the late answer is skipped, not taken for the next one
+    engine.movetime = Duration::from_secs(5);
+    let (action, _) = engine.best_move(&view, &legal).unwrap();
+    assert!(legal.contains(&action));
+}
+
+#[test]
+fn referee_plays_engines_against_bots() {
+    let output = Command::new(env!("CARGO_BIN_EXE_referee"))
+        .args(["-g", "6", "greedy", ENGINE, &format!("other={} --bot random", ENGINE)])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = String::from_utf8(output.stdout).unwrap();
+    assert!(output.contains("sample-basic") && output.contains("other") && output.contains("greedy"), "{}", output);
+    assert!(output.contains("18 games"), "{}", output);
+}
+
+#[test]
+fn referee_reports_engines_that_do_not_start() {
+    let output = Command::new(env!("CARGO_BIN_EXE_referee"))
+        .args(["greedy", "./no-such-engine"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(Some(1), output.status.code(), "{}", stderr);
+    assert!(stderr.starts_with("referee: ") && stderr.contains("./no-such-engine") && !stderr.contains("panicked"), "{}", stderr);
+}
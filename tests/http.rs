@@ -0,0 +1,234 @@
+use uno::{Api, Json};
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Serves a fresh API on a free port.
+fn server() -> SocketAddr {
+    serve(Api::new())
+}
+
+fn serve(api: Api) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || Arc::new(api).serve(listener));
+    address
+}
+
+/// Makes one request and returns the status and the JSON it answered with.
+fn request(address: SocketAddr, method: &str, path: &str, token: Option<&str>, body: &str) -> (u16, Json) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    let auth = token.map(|token| format!("Authorization: Bearer {}\r\n", token)).unwrap_or_default();
+    write!(stream, "{} {} HTTP/1.1\r\nHost: test\r\n{}Content-Length: {}\r\n\r\n{}", method, path, auth, body.len(), body).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    let body = if body.is_empty() { Json::Null } else { body.parse().unwrap() };
+    (status, body)
+}
+
+/// Creates a game and joins every human seat, returning the game's id and the tokens.
+fn game(address: SocketAddr, body: &str, humans: usize) -> (u64, Vec<String>) {
+    let (status, created) = request(address, "POST", "/games", None, body);
+    assert_eq!(201, status, "{}", created);
+    let id = created.get("game").and_then(Json::as_u64).unwrap();
+    let tokens = (0..humans)
+        .map(|i| {
+            let (status, joined) = request(address, "POST", &format!("/games/{}/join", id), None, &format!("{{\"name\": \"P{}\"}}", i));
+            assert_eq!(200, status, "{}", joined);
+            joined.get("token").and_then(Json::as_str).unwrap().to_string()
+        })
+        .collect();
+    (id, tokens)
+}
+
+fn view(address: SocketAddr, id: u64, token: &str) -> Json {
+    let (status, view) = request(address, "GET", &format!("/games/{}/view", id), Some(token), "");
+    assert_eq!(200, status, "{}", view);
+    view
+}
+
+fn field(json: &Json, key: &str) -> u64 {
+    json.get(key).and_then(Json::as_u64).unwrap_or_else(|| panic!("no {} in {}", key, json))
+}
+
+#[test]
+fn oversized_bodies_are_refused_unread() {
+    let address = server();
+    for length in ["18446744073709551615", "1000000"].iter() {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "POST /games HTTP/1.1\r\nHost: test\r\nContent-Length: {}\r\n\r\n", length).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "), "{}", response);
+    }
+    //the server is still up
+    assert_eq!(201, request(address, "POST", "/games", None, "{}").0);
+}
+
+#[test]
+fn finished_and_idle_games_are_forgotten() {
+    let address = serve(Api::with_ttl(Duration::from_secs(3600), Duration::ZERO));
+    let (over, _) = game(address, r#"{"seats": ["greedy", "greedy"], "seed": 5}"#, 0);
+    let (playing, tokens) = game(address, r#"{"seed": 5}"#, 2);
+    //the bots played to the end as soon as they sat down
+    assert_eq!(404, request(address, "GET", &format!("/games/{}/events?wait=0", over), None, "").0);
+    assert_eq!(200, request(address, "GET", &format!("/games/{}/view", playing), Some(&tokens[0]), "").0);
+
+    let address = serve(Api::with_ttl(Duration::ZERO, Duration::ZERO));
+    let (idle, tokens) = game(address, r#"{"seed": 5}"#, 2);
+    game(address, r#"{"seed": 6}"#, 2);
+    assert_eq!(404, request(address, "GET", &format!("/games/{}/view", idle), Some(&tokens[0]), "").0);
+}
+
+#[test]
+fn seats_need_their_token() {
+    let address = server();
+    let (status, _) = request(address, "POST", "/games", None, r#"{"seats": ["human", "nobody"]}"#);
+    assert_eq!(400, status);
+    let (status, _) = request(address, "POST", "/games", None, r#"{"options": {"hand-size": 20}}"#);
+    assert_eq!(400, status);
+
+    let (id, tokens) = game(address, r#"{"seed": 4}"#, 2);
+    assert_ne!(tokens[0], tokens[1]);
+    let (status, _) = request(address, "POST", &format!("/games/{}/join", id), None, r#"{"name": "Late"}"#);
+    assert_eq!(409, status);
+
+    let path = format!("/games/{}/view", id);
+    assert_eq!(401, request(address, "GET", &path, None, "").0);
+    assert_eq!(403, request(address, "GET", &path, Some("0123"), "").0);
+    assert_eq!(404, request(address, "GET", "/games/99/view", Some(&tokens[0]), "").0);
+    assert_eq!(405, request(address, "DELETE", &path, Some(&tokens[0]), "").0);
+    assert_eq!(204, request(address, "OPTIONS", &path, None, "").0);
+
+    //each seat sees only its own hand, and the other's size
+    let views: Vec<Json> = tokens.iter().map(|token| view(address, id, token)).collect();
+    for (seat, view) in views.iter().enumerate() {
+        assert_eq!(seat as u64, field(view, "seat"));
+        assert_eq!(7, view.get("hand").and_then(Json::as_array).unwrap().len());
+        assert_eq!(Some(true), view.get("started").and_then(Json::as_bool));
+    }
+    assert_ne!(views[0].get("hand"), views[1].get("hand"));
+
+    //only the current seat may act
+    let waiting = 1 - field(&views[0], "current") as usize;
+    let (status, actions) = request(address, "GET", &format!("/games/{}/actions", id), Some(&tokens[waiting]), "");
+    assert_eq!(200, status);
+    assert_eq!(Some(0), actions.get("actions").and_then(Json::as_array).map(|actions| actions.len()));
+    let (status, _) = request(address, "POST", &format!("/games/{}/actions", id), Some(&tokens[waiting]), r#"{"type": "draw"}"#);
+    assert_eq!(409, status);
+}
+
+#[test]
+fn waiting_for_events_wakes_on_a_move() {
+    let address = server();
+    let (id, tokens) = game(address, r#"{"seed": 2}"#, 2);
+    let current = field(&view(address, id, &tokens[0]), "current") as usize;
+    let since = field(&view(address, id, &tokens[0]), "events");
+
+    let waiting = tokens[1 - current].clone();
+    let poll = thread::spawn(move || {
+        let start = Instant::now();
+        let (status, events) = request(address, "GET", &format!("/games/{}/events?since={}&wait=20", id, since), Some(&waiting), "");
+        (status, events, start.elapsed())
+    });
+    thread::sleep(Duration::from_millis(200));
+    let (status, result) = request(address, "POST", &format!("/games/{}/actions", id), Some(&tokens[current]), r#"{"type": "draw"}"#);
+    assert_eq!(200, status, "{}", result);
+    assert_eq!(Some("drew"), result.get("result").and_then(|result| result.get("type")).and_then(Json::as_str));
+
+    let (status, events, waited) = poll.join().unwrap();
+    assert_eq!(200, status);
+    assert!(waited < Duration::from_secs(10), "{:?}", waited);
+    let events = events.get("events").and_then(Json::as_array).unwrap().to_vec();
+    assert_eq!(Some("drew"), events[0].get("type").and_then(Json::as_str));
+    assert_eq!(Some(current as u64), events[0].get("seat").and_then(Json::as_u64));
+}
+
+#[test]
+fn a_human_plays_a_bot_to_the_end() {
+    let address = server();
+    let (id, tokens) = game(address, r#"{"seats": ["human", "greedy"], "preset": "stacking", "options": {"hand-size": 5}, "seed": 11}"#, 1);
+    let token = &tokens[0];
+
+    for _ in 0..2000 {
+        let view = view(address, id, token);
+        if view.get("phase").and_then(Json::as_str) == Some("over") {
+            let (_, events) = request(address, "GET", &format!("/games/{}/events?wait=0", id), Some(token), "");
+            let events = events.get("events").and_then(Json::as_array).unwrap().to_vec();
+            assert_eq!(Some("won"), events.last().and_then(|event| event.get("type")).and_then(Json::as_str));
+            return;
+        }
+        //bots move straight after the human, so it is always the human's turn
+        assert_eq!(0, field(&view, "current"));
+        let (_, actions) = request(address, "GET", &format!("/games/{}/actions", id), Some(token), "");
+        let action = actions.get("actions").and_then(Json::as_array).unwrap()[0].to_string();
+        let (status, result) = request(address, "POST", &format!("/games/{}/actions", id), Some(token), &action);
+        assert_eq!(200, status, "{}", result);
+        assert_ne!(Some("illegal"), result.get("result").and_then(|result| result.get("type")).and_then(Json::as_str));
+    }
+    panic!("the game did not end");
+}
+
+#[test]
+fn rooms_are_set_up_and_played() {
+    let address = server();
+    let (status, created) = request(address, "POST", "/rooms", None, r#"{"name": "Ann", "seats": 3}"#);
+    assert_eq!(201, status, "{}", created);
+    let code = created.get("room").and_then(Json::as_str).unwrap().to_string();
+    let ann = created.get("token").and_then(Json::as_str).unwrap().to_string();
+    let path = |endpoint: &str| format!("/rooms/{}/{}", code, endpoint);
+
+    let (status, claimed) = request(address, "POST", &path("claim"), None, r#"{"name": "Bo"}"#);
+    assert_eq!(200, status, "{}", claimed);
+    assert_eq!(1, field(&claimed, "seat"));
+    let bo = claimed.get("token").and_then(Json::as_str).unwrap().to_string();
+    assert_eq!(409, request(address, "POST", &path("claim"), None, r#"{"name": "Bo"}"#).0);
+
+    //only the host fills seats with bots and picks the rules
+    let greedy = r#"{"seat": 2, "bot": "greedy"}"#;
+    assert_eq!(403, request(address, "POST", &path("seats"), Some(&bo), greedy).0);
+    assert_eq!(200, request(address, "POST", &path("seats"), Some(&ann), greedy).0);
+    let (status, room) = request(address, "POST", &path("rules"), Some(&ann), r#"{"preset": "stacking"}"#);
+    assert_eq!(200, status, "{}", room);
+    assert!(room.get("rules").and_then(Json::as_str).unwrap().contains("stacking=on"), "{}", room);
+
+    //everyone has to be ready first
+    assert_eq!(409, request(address, "POST", &path("start"), Some(&ann), "").0);
+    assert_eq!(200, request(address, "POST", &path("ready"), Some(&ann), "").0);
+    assert_eq!(200, request(address, "POST", &path("ready"), Some(&bo), "").0);
+    let (status, room) = request(address, "POST", &path("start"), Some(&ann), r#"{"seed": 3}"#);
+    assert_eq!(200, status, "{}", room);
+    assert_eq!(Some("playing"), room.get("status").and_then(Json::as_str));
+    let (_, rooms) = request(address, "GET", "/rooms", None, "");
+    assert_eq!(Some(code.as_str()), rooms.get("rooms").and_then(Json::as_array).unwrap()[0].get("room").and_then(Json::as_str));
+
+    for _ in 0..2000 {
+        let (_, room) = request(address, "GET", &format!("/rooms/{}", code), None, "");
+        if room.get("status").and_then(Json::as_str) == Some("finished") {
+            let (_, events) = request(address, "GET", &path("events"), Some(&ann), "");
+            let events = events.get("events").and_then(Json::as_array).unwrap().to_vec();
+            assert_eq!(Some("won"), events.last().and_then(|event| event.get("type")).and_then(Json::as_str));
+
+            assert_eq!(403, request(address, "POST", &path("close"), Some(&bo), "").0);
+            assert_eq!(200, request(address, "POST", &path("close"), Some(&ann), "").0);
+            assert_eq!(404, request(address, "GET", &format!("/rooms/{}", code), None, "").0);
+            return;
+        }
+        //the bot moves straight away, so one of the people is always to move
+        for token in [&ann, &bo].iter() {
+            let (_, actions) = request(address, "GET", &path("actions"), Some(token), "");
+            if let Some(action) = actions.get("actions").and_then(Json::as_array).unwrap().first() {
+                let (status, result) = request(address, "POST", &path("actions"), Some(token), &action.to_string());
+                assert_eq!(200, status, "{}", result);
+                break;
+            }
+        }
+    }
+    panic!("the game did not end");
+}
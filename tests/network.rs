@@ -0,0 +1,301 @@
+use uno::{ClientLine, ServerLine};
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::process::{Child, ChildStdout, Command, Stdio};
+
+/// Starts a server on a free port with the given options and returns it with its address.
+fn server(args: &[&str]) -> (Child, BufReader<ChildStdout>, String) {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_server"))
+        .args(["-a", "127.0.0.1:0"])
+        .args(args)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let address = line.trim().strip_prefix("listening on ").expect(&line).to_string();
+    (server, stdout, address)
+}
+
+fn read_line(reader: &mut BufReader<TcpStream>) -> ServerLine {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    line.parse().unwrap()
+}
+
+#[test]
+fn bots_play_a_game_through_clients() {
+    let (mut server, mut log, address) = server(&["-n", "4", "-s", "7", "-c", "4"]);
+    let clients: Vec<Child> = ["Ann", "Bo", "Cy"].iter()
+        .map(|name| {
+            Command::new(env!("CARGO_BIN_EXE_client"))
+                .args(["-a", &address, "--name", name, "--bot", "greedy", "--locale", "en"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+
+    for client in clients {
+        let output = client.wait_with_output().unwrap();
+        assert!(output.status.success());
+        let output = String::from_utf8(output.stdout).unwrap();
+        assert!(output.contains("Game over!"), "{}", output);
+        assert!(output.contains("Thanks for playing!"));
+    }
+    assert!(server.wait().unwrap().success());
+
+    let mut rest = String::new();
+    while log.read_line(&mut rest).unwrap() > 0 {}
+    //clients take seats in the order they connect
+    assert!(["Ann", "Bo", "Cy"].iter().all(|name| rest.contains(&format!("{} joined in seat", name))), "{}", rest);
+    assert!(rest.contains(" won"), "{}", rest);
+}
+
+#[test]
+fn clients_only_see_their_own_hand() {
+    let (mut server, _log, address) = server(&["-n", "2", "-s", "3"]);
+    let mut streams = Vec::new();
+    for name in ["Ann", "Ann", "Bo"].iter() {
+        let mut stream = TcpStream::connect(&address).unwrap();
+        writeln!(stream, "{}", ClientLine::Join(name.to_string())).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        if streams.len() == 1 && *name == "Ann" {
+            //the name is taken, so the server turns this one away
+            assert!(matches!(read_line(&mut reader), ServerLine::Error(_)));
+            continue;
+        }
+        streams.push((stream, reader));
+    }
+
+    let mut current = None;
+    for (seat, (_, reader)) in streams.iter_mut().enumerate() {
+        match read_line(reader) {
+            ServerLine::Welcome { seat: welcomed, names } => assert_eq!((seat, vec!["Ann".to_string(), "Bo".to_string()]), (welcomed, names)),
+            line => panic!("expected a welcome, got {}", line),
+        }
+        loop {
+            match read_line(reader) {
+                ServerLine::Session(_) | ServerLine::Event(_) => {},
+                ServerLine::View(view) => {
+                    assert_eq!(seat, view.seat);
+                    assert_eq!(view.hand_sizes[seat], view.hand.len());
+                    if view.current_seat != seat {
+                        break;
+                    }
+                },
+                ServerLine::YourMove(legal) => {
+                    assert!(!legal.is_empty());
+                    current = Some(seat);
+                    break;
+                },
+                line => panic!("unexpected {}", line),
+            }
+        }
+    }
+
+    //moving out of turn is refused
+    let waiting = 1 - current.unwrap();
+    let (stream, reader) = &mut streams[waiting];
+    writeln!(stream, "DRAW").unwrap();
+    assert!(matches!(read_line(reader), ServerLine::Error(_)));
+
+    //once both leave, bots finish the game
+    for (stream, _) in streams.iter_mut() {
+        writeln!(stream, "{}", ClientLine::Quit).unwrap();
+    }
+    assert!(server.wait().unwrap().success());
+}
+
+/// Joins under `name`.
+fn join(address: &str, name: &str) -> (TcpStream, BufReader<TcpStream>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    writeln!(stream, "{}", ClientLine::Join(name.to_string())).unwrap();
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    (stream, reader)
+}
+
+#[test]
+fn silent_connections_do_not_hold_up_the_table() {
+    let (mut server, _log, address) = server(&["-n", "2", "-s", "5"]);
+    //connects but never says JOIN, so is dropped after the handshake time
+    let _silent = TcpStream::connect(&address).unwrap();
+    let (ann, mut ann_reader) = join(&address, "Ann");
+    let (bo, mut bo_reader) = join(&address, "Bo");
+    welcome(&mut ann_reader);
+    welcome(&mut bo_reader);
+
+    writeln!(&ann, "{}", ClientLine::Quit).unwrap();
+    writeln!(&bo, "{}", ClientLine::Quit).unwrap();
+    assert!(server.wait().unwrap().success());
+}
+
+/// Reads the welcome and the session token that follows it.
+fn welcome(reader: &mut BufReader<TcpStream>) -> (usize, String) {
+    let seat = match read_line(reader) {
+        ServerLine::Welcome { seat, .. } => seat,
+        line => panic!("expected a welcome, got {}", line),
+    };
+    match read_line(reader) {
+        ServerLine::Session(token) => (seat, token),
+        line => panic!("expected a session, got {}", line),
+    }
+}
+
+/// Reads until the view sent with a request to move, or any view if `moving` is false.
+fn next_view(reader: &mut BufReader<TcpStream>, moving: bool) -> uno::PlayerView {
+    let mut view = None;
+    loop {
+        match read_line(reader) {
+            ServerLine::View(latest) if !moving => return latest,
+            ServerLine::View(latest) => view = Some(latest),
+            ServerLine::YourMove(_) => return view.unwrap(),
+            _ => {},
+        }
+    }
+}
+
+#[test]
+fn lost_players_come_back_to_their_seat() {
+    let (mut server, _log, address) = server(&["-n", "2", "-s", "5", "--grace", "60000"]);
+    let (_ann, mut ann_reader) = join(&address, "Ann");
+    let (bo, mut bo_reader) = join(&address, "Bo");
+    let (ann_seat, ann_token) = welcome(&mut ann_reader);
+    let (_, bo_token) = welcome(&mut bo_reader);
+    assert_ne!(ann_token, bo_token);
+    let hand = next_view(&mut bo_reader, false).hand;
+
+    //Bo's connection drops and someone else tries to take over the seat
+    drop(bo_reader);
+    bo.shutdown(std::net::Shutdown::Both).unwrap();
+    let mut stranger = TcpStream::connect(&address).unwrap();
+    writeln!(stranger, "{}", ClientLine::Resume { token: "0123".into(), seen: None }).unwrap();
+    assert!(matches!(read_line(&mut BufReader::new(stranger)), ServerLine::Error(_)));
+    let mut late = TcpStream::connect(&address).unwrap();
+    writeln!(late, "{}", ClientLine::Join("Cy".into())).unwrap();
+    assert!(matches!(read_line(&mut BufReader::new(late)), ServerLine::Error(_)));
+
+    //Bo comes back to the same seat and hand, and nobody played for Bo meanwhile
+    let mut bo = TcpStream::connect(&address).unwrap();
+    writeln!(bo, "{}", ClientLine::Resume { token: bo_token.clone(), seen: None }).unwrap();
+    let mut bo_reader = BufReader::new(bo.try_clone().unwrap());
+    assert!(matches!(read_line(&mut bo_reader), ServerLine::Welcome { seat, .. } if seat == 1 - ann_seat));
+    assert!(matches!(read_line(&mut bo_reader), ServerLine::Session(token) if token == bo_token));
+    assert_eq!(hand, next_view(&mut bo_reader, false).hand);
+
+    drop(ann_reader);
+    writeln!(bo, "{}", ClientLine::Quit).unwrap();
+    server.kill().unwrap();
+}
+
+#[test]
+fn bots_stand_in_after_the_grace_period() {
+    let (mut server, mut log, address) = server(&["-n", "2", "-s", "5", "--grace", "100"]);
+    let (_ann, mut ann_reader) = join(&address, "Ann");
+    let (bo, mut bo_reader) = join(&address, "Bo");
+    let (_, ann_token) = welcome(&mut ann_reader);
+    let (bo_seat, _) = welcome(&mut bo_reader);
+
+    //Ann never comes back in time, so Bo gets to move every turn
+    drop((_ann, ann_reader));
+    for _ in 0..4 {
+        let view = next_view(&mut bo_reader, true);
+        assert_eq!(bo_seat, view.current_seat);
+        let action = match view.phase {
+            uno::Phase::Drawn => uno::Action::Pass,
+            _ => uno::Action::Draw,
+        };
+        writeln!(&bo, "{}", ClientLine::Act(action)).unwrap();
+    }
+
+    //Ann takes the seat back from the bot
+    let mut ann = TcpStream::connect(&address).unwrap();
+    writeln!(ann, "{}", ClientLine::Resume { token: ann_token, seen: None }).unwrap();
+    let mut ann_reader = BufReader::new(ann.try_clone().unwrap());
+    assert!(matches!(read_line(&mut ann_reader), ServerLine::Welcome { .. }));
+
+    writeln!(&bo, "{}", ClientLine::Quit).unwrap();
+    writeln!(ann, "{}", ClientLine::Quit).unwrap();
+    assert!(server.wait().unwrap().success());
+    let mut rest = String::new();
+    while log.read_line(&mut rest).unwrap() > 0 {}
+    assert!(rest.contains("Ann is still away, basic plays for them"), "{}", rest);
+    assert!(rest.contains("Ann is back in seat"), "{}", rest);
+}
+
+#[test]
+fn slow_players_have_their_moves_made() {
+    let (mut server, mut log, address) = server(&["-n", "2", "-s", "5", "--turn-time", "0.2"]);
+    let (ann, mut ann_reader) = join(&address, "Ann");
+    let (bo, mut bo_reader) = join(&address, "Bo");
+    let (ann_seat, _) = welcome(&mut ann_reader);
+    welcome(&mut bo_reader);
+
+    //nobody sends a move, but the one to move hears what was done for them
+    let current = next_view(&mut ann_reader, false).current_seat;
+    let reader = if current == ann_seat { &mut ann_reader } else { &mut bo_reader };
+    while !matches!(read_line(reader), ServerLine::Result(_)) {}
+
+    writeln!(&ann, "{}", ClientLine::Quit).unwrap();
+    writeln!(&bo, "{}", ClientLine::Quit).unwrap();
+    assert!(server.wait().unwrap().success());
+    let mut rest = String::new();
+    while log.read_line(&mut rest).unwrap() > 0 {}
+    assert!(rest.contains("ran out of time"), "{}", rest);
+}
+
+#[test]
+fn resumed_players_hear_what_they_missed() {
+    let (mut server, _log, address) = server(&["-n", "2", "-s", "5", "--grace", "60000"]);
+    let mut players: Vec<_> = ["Ann", "Bo"].iter().map(|name| join(&address, name)).collect();
+    let tokens: Vec<_> = players.iter_mut().map(|(_, reader)| welcome(reader).1).collect();
+    let mut seen = 0;
+    let current = loop {
+        match read_line(&mut players[0].1) {
+            ServerLine::Event(_) => seen += 1,
+            ServerLine::View(view) => break view.current_seat,
+            line => panic!("unexpected {}", line),
+        }
+    };
+
+    //whoever is waiting leaves, and misses a draw while away
+    let (away, mover) = (1 - current, current);
+    players[away].0.shutdown(std::net::Shutdown::Both).unwrap();
+    writeln!(&players[mover].0, "{}", ClientLine::Act(uno::Action::Draw)).unwrap();
+    while !matches!(read_line(&mut players[mover].1), ServerLine::Event(uno::Event::Drew { .. })) {}
+
+    //what each heard before leaving is the same, so the count from seat 0 will do
+    let mut back = TcpStream::connect(&address).unwrap();
+    writeln!(back, "{}", ClientLine::Resume { token: tokens[away].clone(), seen: Some(seen) }).unwrap();
+    let mut reader = BufReader::new(back.try_clone().unwrap());
+    welcome(&mut reader);
+    let mut missed = Vec::new();
+    loop {
+        match read_line(&mut reader) {
+            ServerLine::Event(event) => missed.push(event),
+            ServerLine::View(_) => break,
+            line => panic!("expected the missed events before the view, got {}", line),
+        }
+    }
+    assert_eq!(vec![uno::Event::Drew { seat: mover, count: 1, forced: false }], missed);
+
+    writeln!(back, "{}", ClientLine::Quit).unwrap();
+    writeln!(&players[mover].0, "{}", ClientLine::Quit).unwrap();
+    server.kill().unwrap();
+}
+
+#[test]
+fn silent_clients_are_taken_to_be_gone() {
+    let (mut server, mut log, address) = server(&["-n", "2", "-s", "5", "-c", "2", "--grace", "0", "--timeout", "0.3"]);
+    //Ann's connection stays open but nothing more comes down it, as when the peer vanishes
+    let (_ann, mut ann_reader) = join(&address, "Ann");
+    welcome(&mut ann_reader);
+
+    assert!(server.wait().unwrap().success());
+    let mut rest = String::new();
+    while log.read_line(&mut rest).unwrap() > 0 {}
+    assert!(rest.contains("Ann is still away, basic plays for them"), "{}", rest);
+    assert!(rest.contains(" won"), "{}", rest);
+}
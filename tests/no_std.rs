@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Everything but the core is behind the `std` feature, and only building
+/// without it shows that nothing in the core reaches for std.
+#[test]
+fn the_core_builds_without_std() {
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--no-default-features", "--quiet"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("CARGO_TARGET_DIR", PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("no_std"))
+        .output()
+        .unwrap_or_else(|e| panic!("could not run cargo: {}", e));
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}